@@ -0,0 +1,864 @@
+//! Detection of the rooms bridged from another network (WhatsApp, Signal,
+//! Telegram, etc.) with their `m.bridge` state event ([MSC2346][1]), the
+//! bridges the user can log into and manage by sending commands to their
+//! bot, and the state of their connection to their network.
+//!
+//! The state of a bridge is found from the `com.beeper.bridge_state` events
+//! sent by the bridge in the direct chat with its bot, from the state of the
+//! logins polled from its provisioning API, and from the answers of the bot
+//! to the ping command.
+//!
+//! The ghosts of a bridge, i.e., the Matrix users puppeting the remote
+//! contacts, are recognised from the user namespace of its registration, on
+//! the homeserver of the user unless the namespace names another server. The
+//! namespace defaults to the one generated by the bridge, and can be set with
+//! the `<NETWORK>_GHOST_NAMESPACE` environment variable, e.g.
+//! `WHATSAPP_GHOST_NAMESPACE='@wa_.*:example\.com'`, which is checked at
+//! startup with [`check_ghost_namespaces`].
+//!
+//! The mautrix bridges can also be driven through their provisioning API,
+//! with the client of [`provisioning`].
+//!
+//! [1]: <https://github.com/matrix-org/matrix-spec-proposals/pull/2346>
+
+use core::fmt::{self, Display, Formatter};
+use std::collections::HashMap;
+use std::env::{VarError, var};
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use matrix_sdk::Room;
+use matrix_sdk::deserialized_responses::RawAnySyncOrStrippedState;
+use matrix_sdk::ruma::events::StateEventType;
+use matrix_sdk::ruma::{
+    IdParseError, OwnedServerName, OwnedUserId, ServerName, UserId
+};
+use serde::Deserialize;
+
+use crate::room::DisplayRoom;
+
+pub mod provisioning;
+
+/// Types of the state events describing a bridge, the unstable one being
+/// still sent by most bridges.
+const BRIDGE_EVENT_TYPES: [&str; 2] = ["m.bridge", "uk.half-shot.bridge"];
+
+/// Type of the events in which the bridges report the state of their
+/// connection to their network
+const BRIDGE_STATE_EVENT_TYPE: &str = "com.beeper.bridge_state";
+
+/// Suffix of the environment variables setting the user namespaces of the
+/// ghosts, after the identifier of the network in uppercase
+const GHOST_NAMESPACE_VARIABLE: &str = "_GHOST_NAMESPACE";
+
+/// User namespaces of the ghosts of the known bridges, by identifier of their
+/// network, read once from the environment or from their default value.
+///
+/// The bridges whose default namespace isn't understood have no ghosts, but a
+/// namespace set in the environment that isn't understood is an error, cf.
+/// [`check_ghost_namespaces`].
+static GHOST_NAMESPACES: LazyLock<
+    Result<HashMap<&'static str, UserNamespace>, String>,
+> = LazyLock::new(|| {
+    let mut namespaces = HashMap::new();
+    for bridge in &KNOWN_BRIDGES {
+        let variable = format!(
+            "{}{GHOST_NAMESPACE_VARIABLE}",
+            bridge.protocol_id.to_uppercase()
+        );
+        let namespace = match var(&variable) {
+            Ok(regex) =>
+                Some(UserNamespace::parse(&regex).ok_or_else(|| {
+                    format!(
+                        "{variable} must be a user namespace such as `{}` or \
+                         `{}:example.com`, not `{regex}`",
+                        bridge.ghost_namespace, bridge.ghost_namespace
+                    )
+                })?),
+            Err(VarError::NotPresent) =>
+                UserNamespace::parse(bridge.ghost_namespace),
+            Err(VarError::NotUnicode(_)) =>
+                return Err(format!("{variable} must be valid unicode")),
+        };
+        if let Some(namespace) = namespace {
+            namespaces.insert(bridge.protocol_id, namespace);
+        }
+    }
+    Ok(namespaces)
+});
+
+/// Last states of the known bridges polled from their provisioning API, by
+/// identifier of their network
+static POLLED_STATES: LazyLock<
+    Mutex<HashMap<&'static str, ReportedBridgeState>>,
+> = LazyLock::new(Mutex::default);
+
+/// Bridges the user can log into, with the default settings of their bot.
+///
+/// Only the commands understood in the direct chat with the bot are listed,
+/// not the ones acting on a portal.
+///
+/// The `mautrix-whatsapp` bridge is the one run by the development server.
+pub static KNOWN_BRIDGES: [KnownBridge; 1] = [KnownBridge {
+    bot_localpart:    "whatsappbot",
+    commands:         &[
+        BridgeCommand {
+            arguments:   "",
+            description: "Show the commands of the bot",
+            name:        "help",
+        },
+        BridgeCommand {
+            arguments:   "<invite link>",
+            description: "Join a group with an invite link",
+            name:        "join",
+        },
+        BridgeCommand {
+            arguments:   "[search]",
+            description: "List the WhatsApp contacts",
+            name:        "list-contacts",
+        },
+        BridgeCommand {
+            arguments:   "<qr|phone>",
+            description: "Log into WhatsApp",
+            name:        "login",
+        },
+        BridgeCommand {
+            arguments:   "",
+            description: "Log out of WhatsApp",
+            name:        "logout",
+        },
+        BridgeCommand {
+            arguments:   "",
+            description: "Check the connection to WhatsApp",
+            name:        "ping",
+        },
+        BridgeCommand {
+            arguments:   "<phone number>",
+            description: "Start a private chat with a phone number",
+            name:        "pm",
+        },
+        BridgeCommand {
+            arguments:   "",
+            description: "Reconnect to WhatsApp",
+            name:        "reconnect",
+        },
+        BridgeCommand {
+            arguments:   "<contacts|groups|space>",
+            description: "Synchronise the chats from WhatsApp",
+            name:        "sync",
+        },
+        BridgeCommand {
+            arguments:   "",
+            description: "Show the version of the bridge",
+            name:        "version",
+        },
+    ],
+    failure_prefixes: &["Login failed", "Failed to", "Invalid"],
+    ghost_namespace:  "@whatsapp_.*",
+    login_command:    "login qr",
+    name:             "WhatsApp",
+    ping_command:     "ping",
+    protocol_id:      "whatsapp",
+    provisioning_url: "http://localhost:29318",
+    start_chat:       "pm",
+    status_answers:   StatusAnswers {
+        connected:    &["connection to WhatsApp OK"],
+        disconnected: &[
+            "don't have a WhatsApp connection",
+            "not connected to WhatsApp",
+        ],
+        error:        &["Failed to connect", "Unknown error"],
+        logged_out:   &["You're not logged in", "You're not logged into"],
+    },
+    success_prefixes: &["Successfully logged in", "Logged in as"],
+}];
+
+/// Command understood by the bot of a bridge, sent as a text message in the
+/// direct chat with the bot
+pub struct BridgeCommand {
+    /// Hint of the arguments expected after the name, empty if there are none
+    arguments:   &'static str,
+    /// What the command does
+    description: &'static str,
+    /// Name of the command, typed first
+    name:        &'static str,
+}
+
+impl BridgeCommand {
+    /// Returns the hint of the arguments expected after the name, empty if
+    /// there are none
+    #[must_use]
+    pub const fn as_arguments(&self) -> &'static str {
+        self.arguments
+    }
+
+    /// Returns the name of the command, typed first
+    #[must_use]
+    pub const fn as_name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Display for BridgeCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.arguments.is_empty() {
+            write!(f, "{}: {}", self.name, self.description)
+        } else {
+            write!(f, "{} {}: {}", self.name, self.arguments, self.description)
+        }
+    }
+}
+
+/// Content of a bridge state event
+#[derive(Deserialize)]
+struct BridgeEventContent {
+    /// User id of the bot of the bridge
+    bridgebot: Option<OwnedUserId>,
+    /// Remote channel the room is bridged to
+    channel:   BridgeSection,
+    /// Network the room is bridged from
+    protocol:  BridgeSection,
+}
+
+/// Content of a `com.beeper.bridge_state` event
+#[derive(Clone, Deserialize)]
+struct BridgeStateContent {
+    /// Code of the error, if the bridge is in error
+    error:       Option<String>,
+    /// Human readable description of the state
+    message:     Option<String>,
+    /// State of the bridge, e.g. `CONNECTED` or `TRANSIENT_DISCONNECT`
+    state_event: String,
+    /// When the state was reported, in seconds since the Unix epoch
+    timestamp:   Option<u64>,
+}
+
+/// Section of a bridge state event describing a remote entity
+#[derive(Deserialize)]
+struct BridgeSection {
+    /// Human readable name of the entity
+    displayname: Option<String>,
+    /// Identifier of the entity on the remote network
+    id:          String,
+}
+
+impl BridgeSection {
+    /// Returns the name of the entity, or its id if it has none, and its id
+    fn into_name_and_id(self) -> (String, String) {
+        (self.displayname.unwrap_or_else(|| self.id.clone()), self.id)
+    }
+}
+
+/// Information on the bridge of a room
+#[derive(Clone)]
+pub struct BridgeInfo {
+    /// User id of the bot of the bridge, if advertised
+    bot:         Option<OwnedUserId>,
+    /// Name of the remote channel the room is bridged to
+    channel:     String,
+    /// Identifier of the remote channel on the remote network
+    channel_id:  String,
+    /// Name of the network the room is bridged from, e.g. `WhatsApp`
+    protocol:    String,
+    /// Identifier of the network the room is bridged from, e.g. `whatsapp`
+    protocol_id: String,
+}
+
+impl BridgeInfo {
+    /// Returns the user id of the bot of the bridge, if advertised
+    #[must_use]
+    pub const fn as_bot(&self) -> Option<&OwnedUserId> {
+        self.bot.as_ref()
+    }
+
+    /// Returns the name of the remote channel the room is bridged to
+    #[must_use]
+    pub fn as_channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Returns the identifier of the remote channel on the remote network,
+    /// e.g. the phone number of a `WhatsApp` contact
+    #[must_use]
+    pub fn as_channel_id(&self) -> &str {
+        &self.channel_id
+    }
+
+    /// Returns the name of the network the room is bridged from
+    #[must_use]
+    pub fn as_protocol(&self) -> &str {
+        &self.protocol
+    }
+
+    /// Returns the identifier of the network the room is bridged from, which
+    /// is the same for all the rooms of a network, contrary to its name that
+    /// is chosen by each bridge.
+    #[must_use]
+    pub fn as_protocol_id(&self) -> &str {
+        &self.protocol_id
+    }
+}
+
+/// State of the connection of a bridge to its network
+#[derive(Clone, PartialEq, Eq)]
+pub enum BridgeState {
+    /// The bridge is connected, the messages are bridged
+    Connected,
+    /// The bridge reported an error, with its description
+    Error(String),
+    /// The user isn't logged into the network, and must log in again
+    LoggedOut,
+    /// The bridge lost its connection, and is trying to reconnect
+    TransientDisconnect,
+    /// The bridge didn't report its state yet
+    Unknown,
+}
+
+/// Bridge the user can log into, by sending a login command to its bot in a
+/// direct chat and following its answers
+pub struct KnownBridge {
+    /// Local part of the user id of the bot, which is on the homeserver of
+    /// the user
+    bot_localpart:    &'static str,
+    /// Commands understood by the bot, sorted by name
+    commands:         &'static [BridgeCommand],
+    /// Beginnings of the answers of the bot when the login failed
+    failure_prefixes: &'static [&'static str],
+    /// Default user namespace of the ghosts of the bridge, i.e., of the
+    /// Matrix users puppeting the remote contacts, as the regex of the
+    /// registration generated by the bridge, e.g. `@whatsapp_.*`
+    ghost_namespace:  &'static str,
+    /// Command to send to the bot to start the login
+    login_command:    &'static str,
+    /// Name of the network the bridge connects to
+    name:             &'static str,
+    /// Command to send to the bot to get the state of the bridge
+    ping_command:     &'static str,
+    /// Identifier of the network in the `m.bridge` events of the portals
+    protocol_id:      &'static str,
+    /// Address of the bridge, serving its provisioning API, which is the
+    /// address of its appservice in its default configuration
+    provisioning_url: &'static str,
+    /// Command to send to the bot to start a chat with a contact, followed
+    /// by their phone number or remote id
+    start_chat:       &'static str,
+    /// Parts of the answers of the bot to the ping command, by state
+    status_answers:   StatusAnswers,
+    /// Beginnings of the answers of the bot once the user is logged in
+    success_prefixes: &'static [&'static str],
+}
+
+impl KnownBridge {
+    /// Returns the commands understood by the bot, sorted by name
+    #[must_use]
+    pub const fn as_commands(&self) -> &'static [BridgeCommand] {
+        self.commands
+    }
+
+    /// Returns the command to send to the bot to start the login
+    #[must_use]
+    pub const fn as_login_command(&self) -> &'static str {
+        self.login_command
+    }
+
+    /// Returns the name of the network the bridge connects to
+    #[must_use]
+    pub const fn as_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the command to send to the bot to get the state of the bridge
+    #[must_use]
+    pub const fn as_ping_command(&self) -> &'static str {
+        self.ping_command
+    }
+
+    /// Returns the identifier of the network in the `m.bridge` events of the
+    /// portals
+    #[must_use]
+    pub const fn as_protocol_id(&self) -> &'static str {
+        self.protocol_id
+    }
+
+    /// Returns the address of the bridge, serving its provisioning API
+    #[must_use]
+    pub const fn as_provisioning_url(&self) -> &'static str {
+        self.provisioning_url
+    }
+
+    /// Returns the user id of the bot of the bridge on the given homeserver
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server name doesn't make a valid user id.
+    pub fn bot_user_id(
+        &self,
+        server_name: &ServerName,
+    ) -> Result<OwnedUserId, IdParseError> {
+        UserId::parse(format!("@{}:{server_name}", self.bot_localpart))
+    }
+
+    /// Finds the state of the bridge from the direct chat with its bot and
+    /// from its provisioning API, the most recent of the reported state, of
+    /// the polled state and of the answers of the bot to the ping command
+    /// being the one that counts.
+    #[must_use]
+    pub fn health(&self, room: &DisplayRoom) -> BridgeState {
+        let server_name = room.own_user_id().server_name();
+        let answer = room.as_messages().ok().and_then(|messages| {
+            messages.iter().rev().find_map(|message| {
+                UserId::parse(message.as_sender_id())
+                    .is_ok_and(|sender| self.is_bot(&sender, server_name))
+                    .then(|| self.status_answers.state_of(message.as_body()))
+                    .flatten()
+                    .map(|state| (state, message.timestamp().unwrap_or(0)))
+            })
+        });
+        let reported = room
+            .reported_bridge_state()
+            .map(|reported| (reported.state.clone(), reported.timestamp));
+        let polled = POLLED_STATES
+            .lock()
+            .ok()
+            .and_then(|states| states.get(self.protocol_id).cloned())
+            .map(|polled| (polled.state, polled.timestamp));
+        [reported, polled, answer]
+            .into_iter()
+            .flatten()
+            .max_by_key(|(_, timestamp)| *timestamp)
+            .map_or(BridgeState::Unknown, |(state, _)| state)
+    }
+
+    /// Returns the remote id of the contact puppeted by a ghost of the
+    /// bridge, or `None` if the user isn't a ghost of the bridge.
+    ///
+    /// The ghosts are on the given homeserver, i.e., the homeserver of the
+    /// user, unless the namespace of the bridge names another server.
+    #[must_use]
+    pub fn ghost_remote_id<'user>(
+        &self,
+        user_id: &'user UserId,
+        server_name: &ServerName,
+    ) -> Option<&'user str> {
+        GHOST_NAMESPACES
+            .as_ref()
+            .ok()?
+            .get(self.protocol_id)?
+            .remote_id(user_id, server_name)
+    }
+
+    /// Checks if a user is the bot of the bridge on the given homeserver,
+    /// i.e., the homeserver of the user.
+    ///
+    /// Users with the same local part on other servers aren't the bot, they
+    /// could be anyone.
+    #[must_use]
+    pub fn is_bot(&self, user_id: &UserId, server_name: &ServerName) -> bool {
+        user_id.localpart() == self.bot_localpart
+            && user_id.server_name() == server_name
+    }
+
+    /// Checks if a room is the direct chat with the bot of the bridge
+    #[must_use]
+    pub fn is_bot_room(&self, room: &DisplayRoom) -> bool {
+        room.is_direct()
+            && !room.is_left()
+            && room.members().and_then(Result::ok).is_some_and(|members| {
+                let server_name = room.own_user_id().server_name();
+                members
+                    .iter()
+                    .any(|member| self.is_bot(member.as_user_id(), server_name))
+            })
+    }
+
+    /// Checks if a user is a ghost of the bridge, puppeting a remote contact,
+    /// with the homeserver of the user
+    #[must_use]
+    pub fn is_ghost(&self, user_id: &UserId, server_name: &ServerName) -> bool {
+        self.ghost_remote_id(user_id, server_name).is_some()
+    }
+
+    /// Checks if a room is the portal of the bridge to the chat with a
+    /// contact, given their phone number or remote id, according to the
+    /// bridge of the room.
+    #[must_use]
+    pub fn is_portal_of(&self, bridge: &BridgeInfo, remote_id: &str) -> bool {
+        if !bridge.as_protocol_id().eq_ignore_ascii_case(self.protocol_id) {
+            return false;
+        }
+        let channel_id = bridge.as_channel_id();
+        let remote_id = normalise_remote_id(remote_id);
+        let channel_user =
+            channel_id.split_once('@').map_or(channel_id, |(user, _)| user);
+        channel_id == remote_id
+            || channel_user.trim_start_matches('+')
+                == remote_id.trim_start_matches('+')
+    }
+
+    /// Finds how the login is going from the answers of the bot, the last
+    /// conclusive answer being the one that counts, e.g. if a first attempt
+    /// failed before a successful one.
+    #[must_use]
+    pub fn login_progress<'answer, Answers>(
+        &self,
+        answers: Answers,
+    ) -> LoginProgress
+    where
+        Answers: IntoIterator<Item = &'answer str>,
+    {
+        let has_prefix = |answer: &str, prefixes: &[&str]| {
+            prefixes.iter().any(|prefix| answer.trim().starts_with(prefix))
+        };
+        answers
+            .into_iter()
+            .filter_map(|answer| {
+                if has_prefix(answer, self.success_prefixes) {
+                    Some(LoginProgress::LoggedIn)
+                } else if has_prefix(answer, self.failure_prefixes) {
+                    Some(LoginProgress::Failed)
+                } else {
+                    None
+                }
+            })
+            .last()
+            .unwrap_or(LoginProgress::InProgress)
+    }
+
+    /// Stores the state of the bridge polled from its provisioning API, as of
+    /// now.
+    pub(crate) fn set_polled_state(&self, state: BridgeState) {
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+            });
+        if let Ok(mut states) = POLLED_STATES.lock() {
+            states.insert(self.protocol_id, ReportedBridgeState {
+                state,
+                timestamp,
+            });
+        }
+    }
+
+    /// Returns the command to send to the bot to start a chat with a
+    /// contact, given their phone number or remote id
+    #[must_use]
+    pub fn start_chat_command(&self, remote_id: &str) -> String {
+        format!("{} {}", self.start_chat, normalise_remote_id(remote_id))
+    }
+}
+
+/// Progress of the login to a bridge
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LoginProgress {
+    /// The bot reported that the login failed
+    Failed,
+    /// The bot didn't conclude yet, it may be waiting for the user
+    InProgress,
+    /// The bot reported that the user is logged in
+    LoggedIn,
+}
+
+/// State of a bridge, as reported by the bridge itself in a
+/// `com.beeper.bridge_state` event
+#[derive(Clone)]
+pub struct ReportedBridgeState {
+    /// Reported state
+    state:     BridgeState,
+    /// When the state was reported, in milliseconds since the Unix epoch
+    timestamp: u64,
+}
+
+/// Parts of the answers of the bot of a bridge to the ping command, to find
+/// the state of the bridge
+struct StatusAnswers {
+    /// The bridge is connected
+    connected:    &'static [&'static str],
+    /// The user is logged in, but the bridge isn't connected
+    disconnected: &'static [&'static str],
+    /// The bridge failed to connect
+    error:        &'static [&'static str],
+    /// The user isn't logged in
+    logged_out:   &'static [&'static str],
+}
+
+impl StatusAnswers {
+    /// Finds the state of the bridge from an answer of the bot, if it is an
+    /// answer to the ping command
+    fn state_of(&self, answer: &str) -> Option<BridgeState> {
+        let contains =
+            |parts: &[&str]| parts.iter().any(|part| answer.contains(part));
+        if contains(self.logged_out) {
+            Some(BridgeState::LoggedOut)
+        } else if contains(self.disconnected) {
+            Some(BridgeState::TransientDisconnect)
+        } else if contains(self.connected) {
+            Some(BridgeState::Connected)
+        } else if contains(self.error) {
+            Some(BridgeState::Error(answer.trim().to_owned()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Kind of the sender of a message or of a member of a room, to tell the
+/// bridged contacts and the bridge bots from the Matrix users
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UserKind {
+    /// Bot of a bridge, with the identifier of its network
+    BridgeBot(&'static str),
+    /// Ghost of a bridge puppeting a remote contact, with the identifier of
+    /// its network
+    Ghost(&'static str),
+    /// Matrix user
+    Matrix,
+    /// Logged in user
+    Own,
+}
+
+impl UserKind {
+    /// Returns the identifier of the network of the bridge of the user, if
+    /// they are a ghost or a bridge bot
+    #[must_use]
+    pub const fn network(self) -> Option<&'static str> {
+        match self {
+            Self::BridgeBot(network) | Self::Ghost(network) => Some(network),
+            Self::Matrix | Self::Own => None,
+        }
+    }
+
+    /// Finds the kind of a user from the namespaces of the known bridges on
+    /// the homeserver of the logged in user
+    #[must_use]
+    pub fn of(user_id: &UserId, own_user_id: &UserId) -> Self {
+        if user_id == own_user_id {
+            return Self::Own;
+        }
+        let server_name = own_user_id.server_name();
+        KNOWN_BRIDGES
+            .iter()
+            .find_map(|bridge| {
+                if bridge.is_bot(user_id, server_name) {
+                    Some(Self::BridgeBot(bridge.protocol_id))
+                } else if bridge.is_ghost(user_id, server_name) {
+                    Some(Self::Ghost(bridge.protocol_id))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(Self::Matrix)
+    }
+}
+
+/// User namespace of an appservice, as the regex of the `namespaces.users`
+/// section of its registration, e.g. `@whatsapp_.*:example\.com`.
+///
+/// Only the regexes generated by the bridges are understood, i.e., a local
+/// part prefix followed by `.*`, with an optional server name.
+struct UserNamespace {
+    /// Beginning of the local parts of the users
+    localpart_prefix: String,
+    /// Server of the users, or `None` for the homeserver of the user
+    server_name:      Option<OwnedServerName>,
+}
+
+impl UserNamespace {
+    /// Parses the regex of a namespace, returning `None` if it isn't
+    /// understood
+    fn parse(regex: &str) -> Option<Self> {
+        let regex = regex.trim().trim_start_matches('^').trim_end_matches('$');
+        let (prefix, server) = regex.strip_prefix('@')?.split_once(".*")?;
+        let server_name = match server {
+            "" | ":.*" => None,
+            _ => Some(
+                ServerName::parse(unescape_regex(server.strip_prefix(':')?))
+                    .ok()?,
+            ),
+        };
+        let localpart_prefix = unescape_regex(prefix);
+        localpart_prefix
+            .chars()
+            .all(|character| {
+                character.is_ascii_lowercase()
+                    || character.is_ascii_digit()
+                    || "._=-/+".contains(character)
+            })
+            .then_some(Self { localpart_prefix, server_name })
+    }
+
+    /// Returns the rest of the local part of a user of the namespace, e.g.
+    /// the remote id of the contact puppeted by a ghost, or `None` if the
+    /// user isn't in the namespace.
+    ///
+    /// The server of the user must be the server of the namespace, or the
+    /// given homeserver if the namespace has none.
+    fn remote_id<'user>(
+        &self,
+        user_id: &'user UserId,
+        homeserver: &ServerName,
+    ) -> Option<&'user str> {
+        let server_name = self.server_name.as_deref().unwrap_or(homeserver);
+        if user_id.server_name() != server_name {
+            return None;
+        }
+        user_id
+            .localpart()
+            .strip_prefix(self.localpart_prefix.as_str())
+            .filter(|remote_id| !remote_id.is_empty())
+    }
+}
+
+impl From<BridgeEventContent> for BridgeInfo {
+    fn from(content: BridgeEventContent) -> Self {
+        let (channel, channel_id) = content.channel.into_name_and_id();
+        let (protocol, protocol_id) = content.protocol.into_name_and_id();
+        Self {
+            bot: content.bridgebot,
+            channel,
+            channel_id,
+            protocol,
+            protocol_id,
+        }
+    }
+}
+
+impl From<BridgeStateContent> for BridgeState {
+    fn from(content: BridgeStateContent) -> Self {
+        match content.state_event.as_str() {
+            "BACKFILLING" | "CONNECTED" => Self::Connected,
+            "BAD_CREDENTIALS" | "LOGGED_OUT" => Self::LoggedOut,
+            "CONNECTING" | "STARTING" | "TRANSIENT_DISCONNECT" =>
+                Self::TransientDisconnect,
+            _ => Self::Error(
+                content
+                    .message
+                    .or(content.error)
+                    .unwrap_or(content.state_event),
+            ),
+        }
+    }
+}
+
+/// Checks that the user namespaces of the ghosts set in the environment, with
+/// the `<NETWORK>_GHOST_NAMESPACE` variables, are understood.
+///
+/// # Errors
+///
+/// Returns an error naming the variable whose namespace isn't understood.
+pub fn check_ghost_namespaces() -> Result<(), matrix_sdk::Error> {
+    GHOST_NAMESPACES
+        .as_ref()
+        .map(|_| ())
+        .map_err(|err| matrix_sdk::Error::UnknownError(err.clone().into()))
+}
+
+/// Finds the bridge of a room, if it is bridged.
+///
+/// This works for joined rooms and for invited rooms, if the bridge sent its
+/// state event in the stripped state of the invitation.
+pub async fn get_bridge_info(room: &Room) -> Option<BridgeInfo> {
+    for event_type in BRIDGE_EVENT_TYPES {
+        let Ok(events) =
+            room.get_state_events(StateEventType::from(event_type)).await
+        else {
+            continue;
+        };
+        for event in events {
+            let content = match event {
+                RawAnySyncOrStrippedState::Sync(raw) =>
+                    raw.get_field::<BridgeEventContent>("content"),
+                RawAnySyncOrStrippedState::Stripped(raw) =>
+                    raw.get_field::<BridgeEventContent>("content"),
+            };
+            if let Ok(Some(content)) = content {
+                return Some(BridgeInfo::from(content));
+            }
+        }
+    }
+    None
+}
+
+/// Finds the last state reported by a bridge in a room, i.e., the direct chat
+/// with its bot.
+pub async fn get_reported_bridge_state(
+    room: &Room,
+) -> Option<ReportedBridgeState> {
+    let events = room
+        .get_state_events(StateEventType::from(BRIDGE_STATE_EVENT_TYPE))
+        .await
+        .ok()?;
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let (content, origin_server_ts) = match event {
+                RawAnySyncOrStrippedState::Sync(raw) => (
+                    raw.get_field::<BridgeStateContent>("content"),
+                    raw.get_field::<u64>("origin_server_ts").ok().flatten(),
+                ),
+                RawAnySyncOrStrippedState::Stripped(raw) =>
+                    (raw.get_field::<BridgeStateContent>("content"), None),
+            };
+            let content = content.ok()??;
+            let timestamp = origin_server_ts
+                .or_else(|| {
+                    content.timestamp.and_then(|secs| secs.checked_mul(1000))
+                })
+                .unwrap_or(0);
+            Some(ReportedBridgeState {
+                state: BridgeState::from(content),
+                timestamp,
+            })
+        })
+        .max_by_key(|reported| reported.timestamp)
+}
+
+/// Returns the name to display for a ghost of a known bridge without a
+/// display name, i.e., the phone number or remote id of the contact it
+/// puppets, or `None` if the user isn't a ghost on the given homeserver.
+#[must_use]
+pub fn ghost_name(
+    user_id: &UserId,
+    server_name: &ServerName,
+) -> Option<String> {
+    KNOWN_BRIDGES
+        .iter()
+        .find_map(|bridge| bridge.ghost_remote_id(user_id, server_name))
+        .map(normalise_remote_id)
+}
+
+/// Normalises the phone number or remote id of a contact on a bridged network.
+///
+/// Phone numbers are written in the international format without separators,
+/// e.g. `+33 6 12-34-56-78` becomes `+33612345678`. The other ids are kept as
+/// they are.
+#[must_use]
+pub fn normalise_remote_id(remote_id: &str) -> String {
+    let remote_id = remote_id.trim();
+    let is_phone =
+        remote_id.chars().any(|character| character.is_ascii_digit())
+            && remote_id.chars().all(|character| {
+                character.is_ascii_digit() || " +-().".contains(character)
+            });
+    if is_phone {
+        let digits =
+            remote_id.chars().filter(char::is_ascii_digit).collect::<String>();
+        format!("+{digits}")
+    } else {
+        remote_id.to_owned()
+    }
+}
+
+/// Removes the escaping backslashes of the literal part of a regex, e.g.
+/// `example\.com` becomes `example.com`
+fn unescape_regex(regex: &str) -> String {
+    let mut unescaped = String::with_capacity(regex.len());
+    let mut characters = regex.chars();
+    while let Some(character) = characters.next() {
+        if character == '\\' {
+            unescaped.extend(characters.next());
+        } else {
+            unescaped.push(character);
+        }
+    }
+    unescaped
+}
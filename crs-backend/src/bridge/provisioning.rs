@@ -0,0 +1,712 @@
+//! Client for the provisioning HTTP API of the mautrix bridges, to log into a
+//! bridge, and to find and contact the users of its network, without sending
+//! commands to its bot and parsing its answers.
+//!
+//! The requests are authenticated with the Matrix access token of the user,
+//! which the bridge checks with the homeserver. The base URL is the address
+//! of the bridge, e.g. a local mock server.
+//!
+//! See the [provisioning API of the mautrix bridges][1].
+//!
+//! [1]: <https://docs.mau.fi/bridges/general/provisioning.html>
+
+use core::fmt::{self, Write as _};
+
+use matrix_sdk::Error;
+use reqwest::{Client, Method, Url};
+use serde::Deserialize;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde_json::{Map, Value};
+
+use crate::bridge::{BridgeState, BridgeStateContent};
+
+#[cfg(test)]
+mod tests;
+
+/// Segments of the path of the provisioning API, after the base URL of the
+/// bridge
+const API_PATH: [&str; 3] = ["_matrix", "provision", "v3"];
+
+/// Identifier of the logins to select all of them, e.g. to log out of all
+/// the accounts
+pub const ALL_LOGINS: &str = "all";
+
+/// Error returned by the bridge
+#[derive(Deserialize)]
+struct ApiError {
+    /// Description of the error
+    error:   Option<String>,
+    /// Code of the error, e.g. `M_NOT_FOUND`
+    errcode: Option<String>,
+}
+
+/// List of the contacts of a login
+#[derive(Deserialize)]
+struct ContactList {
+    /// Contacts of the login
+    contacts: Vec<RemoteContact>,
+}
+
+/// Step of a login in which the user copies cookies from a website
+#[derive(Deserialize)]
+struct CookiesParams {
+    /// Cookies and other values to extract
+    fields: Vec<CookieField>,
+    /// Page of the website to log into
+    url:    String,
+}
+
+/// Cookie or other value to extract from the website
+#[derive(Deserialize)]
+struct CookieField {
+    /// Identifier of the field, used to submit its value
+    id: String,
+}
+
+/// Step of a login in which the bridge displays a code and waits
+#[derive(Deserialize)]
+struct DisplayAndWaitParams {
+    /// Data to display, e.g. the content of the QR code
+    #[serde(default)]
+    data:         String,
+    /// Type of data to display: `qr`, `emoji`, `code` or `nothing`
+    #[serde(rename = "type")]
+    display_type: String,
+}
+
+/// Last step of a successful login
+#[derive(Deserialize)]
+struct LoginCompleteParams {
+    /// Identifier of the new login
+    user_login_id: Option<String>,
+}
+
+/// List of the ways to log into the bridge
+#[derive(Deserialize)]
+struct LoginFlowList {
+    /// Ways to log into the bridge
+    flows: Vec<LoginFlow>,
+}
+
+/// Step of a login as sent by the bridge, only one of the parameters being
+/// set, according to its type
+#[derive(Deserialize)]
+struct RawLoginStep {
+    /// Parameters of a complete step
+    complete:         Option<LoginCompleteParams>,
+    /// Parameters of a cookie step
+    cookies:          Option<CookiesParams>,
+    /// Parameters of a display and wait step
+    display_and_wait: Option<DisplayAndWaitParams>,
+    /// Instructions to display to the user
+    #[serde(default)]
+    instructions:     String,
+    /// Identifier of the login process
+    login_id:         String,
+    /// Identifier of the step
+    step_id:          String,
+    /// Type of the step
+    #[serde(rename = "type")]
+    step_type:        String,
+    /// Parameters of a user input step
+    user_input:       Option<UserInputParams>,
+}
+
+/// Step of a login in which the user fills a form
+#[derive(Deserialize)]
+struct UserInputParams {
+    /// Fields of the form
+    fields: Vec<LoginInputField>,
+}
+
+/// Way to log into the bridge, e.g. with a QR code or a phone number
+#[derive(Deserialize)]
+pub struct LoginFlow {
+    /// Description of the flow
+    #[serde(default)]
+    description: String,
+    /// Identifier of the flow, to start a login with it
+    id:          String,
+    /// Name of the flow
+    name:        String,
+}
+
+impl LoginFlow {
+    /// Returns the description of the flow
+    #[must_use]
+    pub fn as_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the identifier of the flow, to start a login with it
+    #[must_use]
+    pub fn as_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the name of the flow
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Field of the form of a login step, e.g. the phone number
+#[derive(Deserialize)]
+pub struct LoginInputField {
+    /// Description of the field
+    #[serde(default)]
+    description: String,
+    /// Type of the value, e.g. `phone_number`, `password` or `2fa_code`
+    #[serde(rename = "type")]
+    field_type:  String,
+    /// Identifier of the field, used to submit its value
+    id:          String,
+    /// Name of the field
+    name:        String,
+    /// Regular expression the value must match, if any
+    pattern:     Option<String>,
+}
+
+impl LoginInputField {
+    /// Returns the description of the field
+    #[must_use]
+    pub fn as_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the type of the value, e.g. `phone_number` or `password`
+    #[must_use]
+    pub fn as_field_type(&self) -> &str {
+        &self.field_type
+    }
+
+    /// Returns the identifier of the field, used to submit its value
+    #[must_use]
+    pub fn as_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the name of the field
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the regular expression the value must match, if any
+    #[must_use]
+    pub fn as_pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+}
+
+/// Step of a login through the provisioning API
+pub struct LoginStep {
+    /// Instructions to display to the user
+    instructions: String,
+    /// What the step expects from the user
+    kind:         LoginStepKind,
+    /// Identifier of the login process
+    process_id:   String,
+    /// Identifier of the step
+    step_id:      String,
+}
+
+impl LoginStep {
+    /// Returns the instructions to display to the user
+    #[must_use]
+    pub fn as_instructions(&self) -> &str {
+        &self.instructions
+    }
+
+    /// Returns what the step expects from the user
+    #[must_use]
+    pub const fn as_kind(&self) -> &LoginStepKind {
+        &self.kind
+    }
+
+    /// Checks if the login is complete
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        matches!(self.kind, LoginStepKind::Complete { .. })
+    }
+}
+
+impl TryFrom<RawLoginStep> for LoginStep {
+    type Error = Error;
+
+    fn try_from(raw: RawLoginStep) -> Result<Self, Error> {
+        let kind = match raw.step_type.as_str() {
+            "complete" => LoginStepKind::Complete {
+                login_id: raw
+                    .complete
+                    .and_then(|complete| complete.user_login_id),
+            },
+            "cookies" => {
+                let cookies = raw.cookies.ok_or_else(|| {
+                    Error::UnknownError("Missing cookie parameters".into())
+                })?;
+                LoginStepKind::Cookies {
+                    field_ids: cookies
+                        .fields
+                        .into_iter()
+                        .map(|field| field.id)
+                        .collect(),
+                    url:       cookies.url,
+                }
+            }
+            "display_and_wait" => {
+                let display = raw.display_and_wait.ok_or_else(|| {
+                    Error::UnknownError("Missing display parameters".into())
+                })?;
+                LoginStepKind::DisplayAndWait {
+                    data:         display.data,
+                    display_type: display.display_type,
+                }
+            }
+            "user_input" => LoginStepKind::UserInput(
+                raw.user_input.map(|input| input.fields).unwrap_or_default(),
+            ),
+            other =>
+                return Err(Error::UnknownError(
+                    format!("Unknown login step type {other}").into(),
+                )),
+        };
+        Ok(Self {
+            instructions: raw.instructions,
+            kind,
+            process_id: raw.login_id,
+            step_id: raw.step_id,
+        })
+    }
+}
+
+/// What a login step expects from the user
+pub enum LoginStepKind {
+    /// The login succeeded
+    Complete {
+        /// Identifier of the new login, if given by the bridge
+        login_id: Option<String>,
+    },
+    /// The user must copy cookies from a website, and submit them with
+    /// [`ProvisioningClient::submit_login_fields`]
+    Cookies {
+        /// Identifiers of the cookies to submit
+        field_ids: Vec<String>,
+        /// Page of the website to log into
+        url:       String,
+    },
+    /// The user must scan or enter a code, while the bridge waits with
+    /// [`ProvisioningClient::wait_login_step`]
+    DisplayAndWait {
+        /// Data to display, e.g. the content of the QR code
+        data:         String,
+        /// Type of data to display: `qr`, `emoji`, `code` or `nothing`
+        display_type: String,
+    },
+    /// The user must fill a form, and submit it with
+    /// [`ProvisioningClient::submit_login_fields`]
+    UserInput(Vec<LoginInputField>),
+}
+
+/// Client for the provisioning API of a bridge, on behalf of a user
+pub struct ProvisioningClient {
+    /// Matrix access token of the user
+    access_token: String,
+    /// Address of the bridge, without the path of the API
+    base_url:     Url,
+    /// HTTP client sending the requests
+    http:         Client,
+    /// Matrix user id of the user
+    user_id:      String,
+}
+
+impl ProvisioningClient {
+    /// Lists the contacts of the user on the network of the bridge, for one
+    /// of their logins or for the default one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the bridge refused it.
+    pub async fn contacts(
+        &self,
+        login_id: Option<&str>,
+    ) -> Result<Vec<RemoteContact>, Error> {
+        let mut url = self.url(&["contacts"]);
+        if let Some(login_id) = login_id {
+            url.query_pairs_mut().append_pair("login_id", login_id);
+        }
+        let list: ContactList = self.send(Method::GET, url, None).await?;
+        Ok(list.contacts)
+    }
+
+    /// Starts a direct chat with a user of the network of the bridge, given
+    /// their phone number, username or remote id, and returns them with the
+    /// id of the portal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the user wasn't found.
+    pub async fn create_dm(
+        &self,
+        identifier: &str,
+    ) -> Result<RemoteContact, Error> {
+        let url = self.url(&["create_dm", identifier]);
+        self.send(Method::POST, url, None).await
+    }
+
+    /// Lists the ways to log into the bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the bridge refused it.
+    pub async fn login_flows(&self) -> Result<Vec<LoginFlow>, Error> {
+        let url = self.url(&["login", "flows"]);
+        let list: LoginFlowList = self.send(Method::GET, url, None).await?;
+        Ok(list.flows)
+    }
+
+    /// Logs out of a login of the user, or of all of them with
+    /// [`ALL_LOGINS`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the login wasn't found.
+    pub async fn logout(&self, login_id: &str) -> Result<(), Error> {
+        let url = self.url(&["logout", login_id]);
+        let _: IgnoredAny = self.send(Method::POST, url, None).await?;
+        Ok(())
+    }
+
+    /// Creates a client for the provisioning API of the bridge at the given
+    /// address, authenticated as the given Matrix user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address isn't a valid base URL.
+    pub fn new(
+        base_url: &str,
+        user_id: &str,
+        access_token: &str,
+    ) -> Result<Self, Error> {
+        let base_url = Url::parse(base_url)
+            .map_err(|err| Error::UnknownError(err.into()))?;
+        if base_url.cannot_be_a_base() {
+            return Err(Error::UnknownError(
+                format!("{base_url} can't be the address of a bridge").into(),
+            ));
+        }
+        Ok(Self {
+            access_token: access_token.to_owned(),
+            base_url,
+            http: Client::new(),
+            user_id: user_id.to_owned(),
+        })
+    }
+
+    /// Finds a user of the network of the bridge, given their phone number,
+    /// username or remote id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the user wasn't found.
+    pub async fn resolve_identifier(
+        &self,
+        identifier: &str,
+    ) -> Result<RemoteContact, Error> {
+        let url = self.url(&["resolve_identifier", identifier]);
+        self.send(Method::GET, url, None).await
+    }
+
+    /// Sends a request to the API, and parses the answer of the bridge.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&Value>,
+    ) -> Result<T, Error> {
+        let mut request =
+            self.http.request(method, url).bearer_auth(&self.access_token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| Error::UnknownError(err.into()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let error = response.json::<ApiError>().await.ok();
+            let message = error
+                .and_then(|error| error.error.or(error.errcode))
+                .unwrap_or_else(|| status.to_string());
+            return Err(Error::UnknownError(
+                format!("The bridge refused the request: {message}").into(),
+            ));
+        }
+        response
+            .json::<T>()
+            .await
+            .map_err(|err| Error::UnknownError(err.into()))
+    }
+
+    /// Starts a login with one of the flows of the bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the flow doesn't exist.
+    pub async fn start_login(&self, flow_id: &str) -> Result<LoginStep, Error> {
+        let url = self.url(&["login", "start", flow_id]);
+        let raw: RawLoginStep = self.send(Method::POST, url, None).await?;
+        LoginStep::try_from(raw)
+    }
+
+    /// Submits the values of the fields of a user input or cookie step, with
+    /// their identifier, and returns the next step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the step doesn't expect fields, if the request
+    /// failed, or if the bridge refused the values.
+    pub async fn submit_login_fields(
+        &self,
+        step: &LoginStep,
+        values: &[(&str, &str)],
+    ) -> Result<LoginStep, Error> {
+        let step_type = match step.kind {
+            LoginStepKind::Cookies { .. } => "cookies",
+            LoginStepKind::UserInput(_) => "user_input",
+            LoginStepKind::Complete { .. }
+            | LoginStepKind::DisplayAndWait { .. } =>
+                return Err(Error::UnknownError(
+                    "This login step doesn't expect values".into(),
+                )),
+        };
+        let url = self.url(&[
+            "login",
+            "step",
+            &step.process_id,
+            &step.step_id,
+            step_type,
+        ]);
+        let body = Value::Object(
+            values
+                .iter()
+                .map(|&(id, value)| (id.to_owned(), Value::from(value)))
+                .collect::<Map<_, _>>(),
+        );
+        let raw: RawLoginStep =
+            self.send(Method::POST, url, Some(&body)).await?;
+        LoginStep::try_from(raw)
+    }
+
+    /// Builds the URL of an endpoint of the API, with the user on behalf of
+    /// whom the request is sent.
+    ///
+    /// The segments are percent-encoded, e.g. the `+` of phone numbers, that
+    /// some servers would read as a space.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        let path = API_PATH.iter().chain(segments).fold(
+            url.path().trim_end_matches('/').to_owned(),
+            |path, segment| format!("{path}/{}", encode_segment(segment)),
+        );
+        url.set_path(&path);
+        url.query_pairs_mut().append_pair("user_id", &self.user_id);
+        url
+    }
+
+    /// Waits for the user to scan or enter the code displayed by a display
+    /// and wait step, and returns the next step.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the step doesn't display a code, if the request
+    /// failed, or if the code expired.
+    pub async fn wait_login_step(
+        &self,
+        step: &LoginStep,
+    ) -> Result<LoginStep, Error> {
+        if !matches!(step.kind, LoginStepKind::DisplayAndWait { .. }) {
+            return Err(Error::UnknownError(
+                "This login step doesn't wait for the user".into(),
+            ));
+        }
+        let url = self.url(&[
+            "login",
+            "step",
+            &step.process_id,
+            &step.step_id,
+            "display_and_wait",
+        ]);
+        let raw: RawLoginStep = self.send(Method::POST, url, None).await?;
+        LoginStep::try_from(raw)
+    }
+
+    /// Returns the network of the bridge, and the logins of the user with
+    /// their state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed or if the bridge refused it,
+    /// e.g. if the user isn't allowed to use it.
+    pub async fn whoami(&self) -> Result<Whoami, Error> {
+        let url = self.url(&["whoami"]);
+        self.send(Method::GET, url, None).await
+    }
+}
+
+/// User of the network of a bridge
+#[derive(Deserialize)]
+pub struct RemoteContact {
+    /// Matrix room id of the direct chat with the user, if there is one
+    dm_room_mxid: Option<String>,
+    /// Identifier of the user on the network
+    id:           String,
+    /// Phone numbers, usernames and other identifiers of the user
+    #[serde(default)]
+    identifiers:  Vec<String>,
+    /// Matrix user id of the ghost of the user, i.e., the puppet that
+    /// represents them on Matrix
+    mxid:         Option<String>,
+    /// Name of the user, if known
+    name:         Option<String>,
+}
+
+impl RemoteContact {
+    /// Returns the Matrix room id of the direct chat with the user, if there
+    /// is one
+    #[must_use]
+    pub fn as_dm_room_id(&self) -> Option<&str> {
+        self.dm_room_mxid.as_deref()
+    }
+
+    /// Returns the identifier of the user on the network
+    #[must_use]
+    pub fn as_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the phone numbers, usernames and other identifiers of the user
+    #[must_use]
+    pub fn as_identifiers(&self) -> &[String] {
+        &self.identifiers
+    }
+
+    /// Returns the Matrix user id of the ghost of the user, if known
+    #[must_use]
+    pub fn as_mxid(&self) -> Option<&str> {
+        self.mxid.as_deref()
+    }
+
+    /// Returns the name of the user, if known
+    #[must_use]
+    pub fn as_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Account of the user on the network of the bridge
+#[derive(Deserialize)]
+pub struct RemoteLogin {
+    /// Identifier of the login, e.g. to log out
+    id:    String,
+    /// Name of the account, e.g. the phone number
+    #[serde(default)]
+    name:  String,
+    /// State of the connection of the account to the network
+    state: Option<BridgeStateContent>,
+}
+
+impl RemoteLogin {
+    /// Returns the identifier of the login, e.g. to log out
+    #[must_use]
+    pub fn as_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the name of the account, e.g. the phone number
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the state of the connection of the account to the network
+    #[must_use]
+    pub fn state(&self) -> BridgeState {
+        self.state.clone().map_or(BridgeState::Unknown, BridgeState::from)
+    }
+}
+
+/// Network of the bridge
+#[derive(Deserialize)]
+struct RemoteNetwork {
+    /// Name of the network, e.g. `WhatsApp`
+    displayname: String,
+    /// Identifier of the network, e.g. `whatsapp`
+    network_id:  String,
+}
+
+/// Information on the bridge and on the logins of the user
+#[derive(Deserialize)]
+pub struct Whoami {
+    /// Matrix user id of the bot of the bridge
+    bridge_bot:  String,
+    /// Ways to log into the bridge
+    #[serde(default)]
+    login_flows: Vec<LoginFlow>,
+    /// Accounts of the user on the network
+    #[serde(default)]
+    logins:      Vec<RemoteLogin>,
+    /// Network of the bridge
+    network:     RemoteNetwork,
+}
+
+impl Whoami {
+    /// Returns the Matrix user id of the bot of the bridge
+    #[must_use]
+    pub fn as_bridge_bot(&self) -> &str {
+        &self.bridge_bot
+    }
+
+    /// Returns the ways to log into the bridge
+    #[must_use]
+    pub fn as_login_flows(&self) -> &[LoginFlow] {
+        &self.login_flows
+    }
+
+    /// Returns the accounts of the user on the network
+    #[must_use]
+    pub fn as_logins(&self) -> &[RemoteLogin] {
+        &self.logins
+    }
+
+    /// Returns the identifier of the network, e.g. `whatsapp`, as in the
+    /// `m.bridge` events of the portals
+    #[must_use]
+    pub fn as_network_id(&self) -> &str {
+        &self.network.network_id
+    }
+
+    /// Returns the name of the network, e.g. `WhatsApp`
+    #[must_use]
+    pub fn as_network_name(&self) -> &str {
+        &self.network.displayname
+    }
+}
+
+/// Percent-encodes a segment of the path of a URL, keeping only the
+/// unreserved characters
+fn encode_segment(segment: &str) -> String {
+    segment.bytes().fold(
+        String::with_capacity(segment.len()),
+        |mut encoded, byte| {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                encoded.push(char::from(byte));
+            } else {
+                let _: fmt::Result = write!(encoded, "%{byte:02X}");
+            }
+            encoded
+        },
+    )
+}
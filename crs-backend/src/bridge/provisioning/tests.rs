@@ -0,0 +1,292 @@
+//! Tests of the provisioning client against a mock bridge, listening on a
+//! local port and answering the requests with canned responses.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::panic,
+    reason = "tests"
+)]
+
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::bridge::BridgeState;
+use crate::bridge::provisioning::{LoginStepKind, ProvisioningClient};
+
+/// Access token sent by the client
+const ACCESS_TOKEN: &str = "secret-token";
+
+/// Path of the provisioning API on the mock bridge
+const API: &str = "/_matrix/provision/v3";
+
+/// Headers of the responses of the mock bridge, but their length
+const RESPONSE_HEADERS: &str =
+    "content-type: application/json\r\nconnection: close\r\n";
+
+/// Query of all the requests, with the user on behalf of whom they are sent
+const USER_QUERY: &str = "?user_id=%40alice%3Alocalhost";
+
+/// Request received by the mock bridge
+struct Received {
+    /// Body of the request
+    body:    String,
+    /// Headers of the request, in lowercase
+    headers: String,
+    /// Method and path of the request, e.g. `GET /_matrix/provision/v3/whoami`
+    target:  String,
+}
+
+/// Starts a mock bridge on a local port, answering the requests in order with
+/// the given statuses and bodies.
+///
+/// Returns the client of the bridge, and the task returning the requests
+/// received by the bridge once all the responses were sent.
+async fn mock_bridge(
+    responses: Vec<(u16, &'static str)>,
+) -> (ProvisioningClient, JoinHandle<Vec<Received>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let task = tokio::spawn(async move {
+        let mut received = vec![];
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            received.push(read_request(&mut stream).await);
+            let length = body.len();
+            let response = format!(
+                "HTTP/1.1 {status} Mock\r\n{RESPONSE_HEADERS}content-length: \
+                 {length}\r\n\r\n{body}"
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+        received
+    });
+    let client =
+        ProvisioningClient::new(&base_url, "@alice:localhost", ACCESS_TOKEN)
+            .unwrap();
+    (client, task)
+}
+
+/// Reads an HTTP request, with the body given by its `content-length`
+async fn read_request(stream: &mut TcpStream) -> Received {
+    let mut buffer = vec![];
+    let mut chunk = [0_u8; 1024];
+    let head_length = loop {
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed before the end of the request");
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) =
+            buffer.windows(4).position(|window| window == b"\r\n\r\n")
+        {
+            break position + 4;
+        }
+    };
+    let head = String::from_utf8(buffer[..head_length].to_vec()).unwrap();
+    let (request_line, headers) = head.split_once("\r\n").unwrap();
+    let headers = headers.to_lowercase();
+    let content_length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .map_or(0, |length| length.trim().parse::<usize>().unwrap());
+    while buffer.len() < head_length + content_length {
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed before the end of the body");
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let target = request_line
+        .rsplit_once(' ')
+        .map_or(request_line, |(target, _)| target)
+        .to_owned();
+    Received {
+        body: String::from_utf8(buffer[head_length..].to_vec()).unwrap(),
+        headers,
+        target,
+    }
+}
+
+/// The answer of the bridge to `whoami` is parsed, and the request is
+/// authenticated with the access token of the user.
+#[tokio::test]
+async fn whoami() {
+    let (client, bridge) = mock_bridge(vec![(
+        200,
+        r#"{
+            "bridge_bot": "@whatsappbot:localhost",
+            "network": {"displayname": "WhatsApp", "network_id": "whatsapp"},
+            "login_flows": [
+                {"id": "qr", "name": "QR code", "description": "Scan it"}
+            ],
+            "logins": [
+                {
+                    "id": "33612345678",
+                    "name": "+33 6 12 34 56 78",
+                    "state": {"state_event": "CONNECTED"}
+                },
+                {"id": "33687654321"}
+            ]
+        }"#,
+    )])
+    .await;
+
+    let whoami = client.whoami().await.unwrap();
+    assert_eq!(whoami.as_bridge_bot(), "@whatsappbot:localhost");
+    assert_eq!(whoami.as_network_id(), "whatsapp");
+    assert_eq!(whoami.as_network_name(), "WhatsApp");
+    let flows = whoami.as_login_flows();
+    assert_eq!(flows.len(), 1);
+    assert_eq!(flows[0].as_id(), "qr");
+    assert_eq!(flows[0].as_description(), "Scan it");
+    let logins = whoami.as_logins();
+    assert_eq!(logins.len(), 2);
+    assert_eq!(logins[0].as_name(), "+33 6 12 34 56 78");
+    assert!(logins[0].state() == BridgeState::Connected);
+    assert_eq!(logins[1].as_name(), "");
+    assert!(logins[1].state() == BridgeState::Unknown);
+
+    let received = bridge.await.unwrap();
+    assert_eq!(received[0].target, format!("GET {API}/whoami{USER_QUERY}"));
+    assert!(
+        received[0]
+            .headers
+            .contains(&format!("authorization: bearer {ACCESS_TOKEN}"))
+    );
+}
+
+/// A login goes from a form, to a code to scan, to its completion, each step
+/// being sent to the endpoint of the previous one.
+#[tokio::test]
+async fn login_steps() {
+    let (client, bridge) = mock_bridge(vec![
+        (
+            200,
+            r#"{
+                "type": "user_input",
+                "login_id": "process",
+                "step_id": "phone",
+                "instructions": "Enter your phone number",
+                "user_input": {"fields": [{
+                    "type": "phone_number",
+                    "id": "phone_number",
+                    "name": "Phone number"
+                }]}
+            }"#,
+        ),
+        (
+            200,
+            r#"{
+                "type": "display_and_wait",
+                "login_id": "process",
+                "step_id": "code",
+                "display_and_wait": {"type": "code", "data": "ABCD-EFGH"}
+            }"#,
+        ),
+        (
+            200,
+            r#"{
+                "type": "complete",
+                "login_id": "process",
+                "step_id": "done",
+                "complete": {"user_login_id": "33612345678"}
+            }"#,
+        ),
+    ])
+    .await;
+
+    let form = client.start_login("phone").await.unwrap();
+    assert_eq!(form.as_instructions(), "Enter your phone number");
+    let LoginStepKind::UserInput(fields) = form.as_kind() else {
+        panic!("expected a form");
+    };
+    assert_eq!(fields[0].as_id(), "phone_number");
+    assert_eq!(fields[0].as_field_type(), "phone_number");
+
+    let code = client
+        .submit_login_fields(&form, &[("phone_number", "+33612345678")])
+        .await
+        .unwrap();
+    let LoginStepKind::DisplayAndWait { data, display_type } = code.as_kind()
+    else {
+        panic!("expected a code to display");
+    };
+    assert_eq!(data, "ABCD-EFGH");
+    assert_eq!(display_type, "code");
+    assert!(client.submit_login_fields(&code, &[]).await.is_err());
+
+    let complete = client.wait_login_step(&code).await.unwrap();
+    assert!(complete.is_complete());
+    let LoginStepKind::Complete { login_id } = complete.as_kind() else {
+        panic!("expected the login to be complete");
+    };
+    assert_eq!(login_id.as_deref(), Some("33612345678"));
+    assert!(client.wait_login_step(&complete).await.is_err());
+
+    let received = bridge.await.unwrap();
+    let targets = received
+        .iter()
+        .map(|request| request.target.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(targets, [
+        format!("POST {API}/login/start/phone{USER_QUERY}"),
+        format!("POST {API}/login/step/process/phone/user_input{USER_QUERY}"),
+        format!(
+            "POST {API}/login/step/process/code/display_and_wait{USER_QUERY}"
+        ),
+    ]);
+    let body =
+        serde_json::from_str::<serde_json::Value>(&received[1].body).unwrap();
+    assert_eq!(body, serde_json::json!({"phone_number": "+33612345678"}));
+}
+
+/// The errors of the bridge are reported with their description, their code,
+/// or the HTTP status if the body isn't an error of the API.
+#[tokio::test]
+async fn api_errors() {
+    let (client, bridge) = mock_bridge(vec![
+        (404, r#"{"errcode": "M_NOT_FOUND", "error": "User not found"}"#),
+        (403, r#"{"errcode": "M_FORBIDDEN"}"#),
+        (502, "Bad gateway"),
+    ])
+    .await;
+
+    for expected in ["User not found", "M_FORBIDDEN", "502 Bad Gateway"] {
+        let error = client.whoami().await.err().unwrap().to_string();
+        assert!(
+            error.contains(&format!(
+                "The bridge refused the request: {expected}"
+            )),
+            "unexpected error: {error}"
+        );
+    }
+    assert_eq!(bridge.await.unwrap().len(), 3);
+}
+
+/// The `+` of a phone number is percent-encoded in the path, not to be read
+/// as a space.
+#[tokio::test]
+async fn resolve_phone_number() {
+    let (client, bridge) = mock_bridge(vec![(
+        200,
+        r#"{
+            "id": "33612345678",
+            "name": "Bob",
+            "mxid": "@whatsapp_33612345678:localhost",
+            "identifiers": ["tel:+33612345678"]
+        }"#,
+    )])
+    .await;
+
+    let contact = client.resolve_identifier("+33612345678").await.unwrap();
+    assert_eq!(contact.as_id(), "33612345678");
+    assert_eq!(contact.as_name(), Some("Bob"));
+    assert_eq!(contact.as_mxid(), Some("@whatsapp_33612345678:localhost"));
+    assert_eq!(contact.as_dm_room_id(), None);
+
+    let received = bridge.await.unwrap();
+    assert_eq!(
+        received[0].target,
+        format!("GET {API}/resolve_identifier/%2B33612345678{USER_QUERY}")
+    );
+}
@@ -0,0 +1,148 @@
+//! Tracking of the parts of the state of the rooms that changed since they
+//! were last loaded.
+//!
+//! The rooms are reloaded at every refresh, but fetching their bridge, their
+//! invitation, their space links, their members or their read receipt is
+//! costly: these parts are only fetched again when a sync brought an event
+//! changing them.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use matrix_sdk::ruma::OwnedRoomId;
+use matrix_sdk::ruma::events::receipt::SyncReceiptEvent;
+use matrix_sdk::ruma::events::{AnyStrippedStateEvent, AnySyncStateEvent};
+use matrix_sdk::ruma::serde::Raw;
+use matrix_sdk::{Client, Room};
+
+/// Parts of the state of the rooms that changed since they were last loaded,
+/// by room id.
+///
+/// The rooms that aren't listed were never loaded.
+static CHANGES: LazyLock<Mutex<HashMap<OwnedRoomId, Changes>>> =
+    LazyLock::new(Mutex::default);
+
+/// Parts of the state of a room that changed since they were last loaded
+#[derive(Clone, Copy)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "one flag per part of the state"
+)]
+pub struct Changes {
+    /// The bridge of the room, i.e. its `m.bridge` state events
+    pub bridge:       bool,
+    /// The state reported by the bridge in the room
+    pub bridge_state: bool,
+    /// The pending invitation to the room
+    pub invitation:   bool,
+    /// The members of the room
+    pub members:      bool,
+    /// The read receipts of the room
+    pub receipt:      bool,
+    /// The children and the parents of the space
+    pub space:        bool,
+}
+
+impl Changes {
+    /// All the parts of the state changed, e.g. for a room never loaded
+    pub const ALL: Self = Self {
+        bridge:       true,
+        bridge_state: true,
+        invitation:   true,
+        members:      true,
+        receipt:      true,
+        space:        true,
+    };
+    /// None of the parts of the state changed
+    pub const NONE: Self = Self {
+        bridge:       false,
+        bridge_state: false,
+        invitation:   false,
+        members:      false,
+        receipt:      false,
+        space:        false,
+    };
+
+    /// Changes made by a state event of the given type, the membership of the
+    /// user changing all the state they can see
+    fn from_state_event(event_type: &str, is_own_member: bool) -> Self {
+        match event_type {
+            "m.bridge" | "uk.half-shot.bridge" =>
+                Self { bridge: true, ..Self::NONE },
+            "com.beeper.bridge_state" =>
+                Self { bridge_state: true, ..Self::NONE },
+            "m.room.member" if is_own_member => Self::ALL,
+            "m.room.member" => Self { members: true, ..Self::NONE },
+            "m.space.child" | "m.space.parent" =>
+                Self { space: true, ..Self::NONE },
+            _ => Self::NONE,
+        }
+    }
+
+    /// Adds the changes of another set of changes
+    const fn merge(&mut self, other: Self) {
+        self.bridge |= other.bridge;
+        self.bridge_state |= other.bridge_state;
+        self.invitation |= other.invitation;
+        self.members |= other.members;
+        self.receipt |= other.receipt;
+        self.space |= other.space;
+    }
+}
+
+/// Records changes of the state of a room, if the room was already loaded
+fn mark_changes(room: &Room, changes: Changes) {
+    if let Ok(mut all_changes) = CHANGES.lock()
+        && let Some(room_changes) = all_changes.get_mut(room.room_id())
+    {
+        room_changes.merge(changes);
+    }
+}
+
+/// Returns the parts of the state of a room that changed since the last call,
+/// and marks them as loaded.
+///
+/// All the parts changed if the room is loaded for the first time.
+pub fn take_changes(room: &Room) -> Changes {
+    CHANGES.lock().map_or(Changes::ALL, |mut all_changes| {
+        all_changes
+            .insert(room.room_id().to_owned(), Changes::NONE)
+            .unwrap_or(Changes::ALL)
+    })
+}
+
+/// Watches the events of the syncs to record the changes of the state of the
+/// rooms.
+///
+/// The changes are recorded as soon as the handlers are called, the returned
+/// futures having nothing left to do.
+pub fn watch_changes(client: &Client) {
+    let own_user_id = client.user_id().map(ToOwned::to_owned);
+    let _state_handle = client.add_event_handler(
+        move |event: Raw<AnySyncStateEvent>, room: Room| {
+            if let Ok(Some(event_type)) = event.get_field::<String>("type") {
+                let state_key =
+                    event.get_field::<String>("state_key").ok().flatten();
+                let is_own_member = state_key.is_some_and(|key| {
+                    own_user_id.as_ref().is_some_and(|own| own.as_str() == key)
+                });
+                mark_changes(
+                    &room,
+                    Changes::from_state_event(&event_type, is_own_member),
+                );
+            }
+            async {}
+        },
+    );
+    let _stripped_handle = client.add_event_handler(
+        |_: Raw<AnyStrippedStateEvent>, room: Room| {
+            mark_changes(&room, Changes { invitation: true, ..Changes::NONE });
+            async {}
+        },
+    );
+    let _receipt_handle =
+        client.add_event_handler(|_: SyncReceiptEvent, room: Room| {
+            mark_changes(&room, Changes { receipt: true, ..Changes::NONE });
+            async {}
+        });
+}
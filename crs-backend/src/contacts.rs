@@ -0,0 +1,296 @@
+//! Contact book gathering the people the user chats with, on Matrix and on
+//! the bridged networks, and the meta-contacts linking several identities of
+//! the same person, e.g. their Matrix account and their `WhatsApp` ghost.
+//!
+//! The contacts of the user on a bridged network are also listed from the
+//! provisioning API of its bridge, even if they have no portal yet.
+//!
+//! The meta-contacts are stored in the account data of the user, so that they
+//! are shared by their devices.
+
+use matrix_sdk::ruma::events::GlobalAccountDataEventType;
+use matrix_sdk::ruma::serde::Raw;
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId, RoomId, UserId};
+use matrix_sdk::{Client, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::value::to_raw_value;
+
+use crate::bridge::provisioning::RemoteContact;
+
+#[cfg(test)]
+mod tests;
+
+/// Type of the account data event storing the meta-contacts
+const META_CONTACTS_EVENT_TYPE: &str = "io.github.t-webber.crs.meta_contacts";
+
+/// Person the user chats with, with one or several identities.
+pub struct Contact {
+    /// Identities of the person, the Matrix ones first
+    identities: Vec<Identity>,
+    /// Whether the identities were linked by the user into a meta-contact
+    is_linked:  bool,
+    /// Name of the person
+    name:       String,
+}
+
+impl Contact {
+    /// Returns the identities of the person, the Matrix ones first
+    #[must_use]
+    pub fn as_identities(&self) -> &[Identity] {
+        &self.identities
+    }
+
+    /// Returns the name of the person
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Indicates whether the identities were linked by the user into a
+    /// meta-contact
+    #[must_use]
+    pub const fn is_linked(&self) -> bool {
+        self.is_linked
+    }
+
+    /// Checks if the name of the person, or the name or the user id of one of
+    /// their identities contains the search, which must be in lowercase.
+    ///
+    /// An empty search matches all the contacts.
+    #[must_use]
+    pub fn matches(&self, search: &str) -> bool {
+        search.is_empty()
+            || self.name.to_lowercase().contains(search)
+            || self.identities.iter().any(|identity| {
+                identity.name.to_lowercase().contains(search)
+                    || identity.user_id.as_str().contains(search)
+            })
+    }
+}
+
+/// Identity of a person on a network, with the direct chat with them
+#[derive(Clone)]
+pub struct Identity {
+    /// Name of the person on the network
+    name:      String,
+    /// Identifier of the network the person is bridged from, or `None` for
+    /// Matrix users
+    network:   Option<String>,
+    /// Identifier of the person on the bridged network, to start a chat with
+    /// them, if they were listed by the bridge
+    remote_id: Option<String>,
+    /// Direct chat with the person, or portal to the chat with them, if there
+    /// is one
+    room_id:   Option<OwnedRoomId>,
+    /// Matrix user id of the person, or of their ghost for the bridged
+    /// networks
+    user_id:   OwnedUserId,
+}
+
+impl Identity {
+    /// Returns the name of the person on the network
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the identifier of the network the person is bridged from, or
+    /// `None` for Matrix users
+    #[must_use]
+    pub fn as_network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    /// Returns the identifier of the person on the bridged network, if they
+    /// were listed by the bridge
+    #[must_use]
+    pub fn as_remote_id(&self) -> Option<&str> {
+        self.remote_id.as_deref()
+    }
+
+    /// Returns the direct chat with the person, or the portal to the chat
+    /// with them, if there is one
+    #[must_use]
+    pub fn as_room_id(&self) -> Option<&RoomId> {
+        self.room_id.as_deref()
+    }
+
+    /// Returns the Matrix user id of the person, or of their ghost
+    #[must_use]
+    pub fn as_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+
+    /// Creates the identity of the other party of a chat
+    pub(crate) const fn new(
+        name: String,
+        network: Option<String>,
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+    ) -> Self {
+        Self { name, network, remote_id: None, room_id: Some(room_id), user_id }
+    }
+}
+
+/// Identities of a person linked by the user
+#[derive(Clone, Deserialize, Serialize)]
+pub struct MetaContact {
+    /// Name chosen for the person
+    name:     String,
+    /// Matrix user ids of the identities of the person
+    user_ids: Vec<OwnedUserId>,
+}
+
+/// Content of the account data event storing the meta-contacts
+#[derive(Default, Deserialize, Serialize)]
+struct MetaContactsContent {
+    /// Meta-contacts of the user
+    contacts: Vec<MetaContact>,
+}
+
+/// Returns the identities of the contacts listed by a bridge, on the network
+/// with the given identifier.
+///
+/// The contacts without a ghost are skipped, as they can't be linked to the
+/// portals.
+#[must_use]
+pub fn bridge_identities(
+    network: &str,
+    contacts: &[RemoteContact],
+) -> Vec<Identity> {
+    contacts
+        .iter()
+        .filter_map(|contact| {
+            let user_id = UserId::parse(contact.as_mxid()?).ok()?;
+            Some(Identity {
+                name: contact.as_name().unwrap_or(contact.as_id()).to_owned(),
+                network: Some(network.to_owned()),
+                remote_id: Some(contact.as_id().to_owned()),
+                room_id: contact
+                    .as_dm_room_id()
+                    .and_then(|room_id| RoomId::parse(room_id).ok()),
+                user_id,
+            })
+        })
+        .collect()
+}
+
+/// Builds the contact book from the identities found in the chats of the
+/// user, then from the ones listed by the bridges, grouping the identities
+/// linked in a meta-contact.
+///
+/// The identities of the same user in several chats are only listed once, the
+/// first one being kept, and the contacts are sorted by name.
+#[must_use]
+pub fn contact_book(
+    identities: Vec<Identity>,
+    meta_contacts: &[MetaContact],
+) -> Vec<Contact> {
+    let mut remaining = Vec::<Identity>::with_capacity(identities.len());
+    for identity in identities {
+        if !remaining.iter().any(|other| other.user_id == identity.user_id) {
+            remaining.push(identity);
+        }
+    }
+
+    let mut contacts = vec![];
+    for meta_contact in meta_contacts {
+        let (mut linked, others): (Vec<_>, Vec<_>) =
+            remaining.into_iter().partition(|identity| {
+                meta_contact.user_ids.contains(&identity.user_id)
+            });
+        remaining = others;
+        if !linked.is_empty() {
+            linked.sort_by(|first, second| first.network.cmp(&second.network));
+            contacts.push(Contact {
+                identities: linked,
+                is_linked:  true,
+                name:       meta_contact.name.clone(),
+            });
+        }
+    }
+    contacts.extend(remaining.into_iter().map(|identity| {
+        let name = identity.name.clone();
+        Contact { identities: vec![identity], is_linked: false, name }
+    }));
+    contacts.sort_by_cached_key(|contact| contact.name.to_lowercase());
+    contacts
+}
+
+/// Fetches the meta-contacts from the account data of the user.
+///
+/// # Errors
+///
+/// Returns an error if the account data couldn't be fetched or is invalid.
+pub async fn get_meta_contacts(
+    client: &Client,
+) -> Result<Vec<MetaContact>, Error> {
+    let raw = client
+        .account()
+        .account_data_raw(GlobalAccountDataEventType::from(
+            META_CONTACTS_EVENT_TYPE,
+        ))
+        .await?;
+    let Some(raw) = raw else { return Ok(vec![]) };
+    let content =
+        serde_json::from_str::<MetaContactsContent>(raw.json().get())?;
+    Ok(content.contacts)
+}
+
+/// Links two contacts into one meta-contact, named after the first one.
+///
+/// The meta-contacts they already belonged to are merged into the new one.
+pub fn link_contacts(
+    meta_contacts: &mut Vec<MetaContact>,
+    first: &Contact,
+    second: &Contact,
+) {
+    let mut user_ids = first
+        .identities
+        .iter()
+        .chain(&second.identities)
+        .map(|identity| identity.user_id.clone())
+        .collect::<Vec<_>>();
+    meta_contacts.retain(|meta_contact| {
+        let is_merged =
+            meta_contact.user_ids.iter().any(|id| user_ids.contains(id));
+        if is_merged {
+            user_ids.extend(meta_contact.user_ids.iter().cloned());
+        }
+        !is_merged
+    });
+    user_ids.sort();
+    user_ids.dedup();
+    meta_contacts.push(MetaContact { name: first.name.clone(), user_ids });
+}
+
+/// Stores the meta-contacts in the account data of the user.
+///
+/// # Errors
+///
+/// Returns an error if the account data couldn't be saved.
+pub async fn set_meta_contacts(
+    client: &Client,
+    meta_contacts: &[MetaContact],
+) -> Result<(), Error> {
+    let content = MetaContactsContent { contacts: meta_contacts.to_vec() };
+    client
+        .account()
+        .set_account_data_raw(
+            GlobalAccountDataEventType::from(META_CONTACTS_EVENT_TYPE),
+            Raw::from_json(to_raw_value(&content)?),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Unlinks the identities of a meta-contact, which are then listed as
+/// separate contacts.
+pub fn unlink_contact(meta_contacts: &mut Vec<MetaContact>, contact: &Contact) {
+    meta_contacts.retain(|meta_contact| {
+        !contact
+            .identities
+            .iter()
+            .any(|identity| meta_contact.user_ids.contains(&identity.user_id))
+    });
+}
@@ -0,0 +1,85 @@
+//! Tests of the grouping of the identities into contacts, and of the linking
+//! and unlinking of the meta-contacts.
+
+#![allow(clippy::unwrap_used, clippy::indexing_slicing, reason = "tests")]
+
+use matrix_sdk::ruma::{RoomId, UserId};
+
+use crate::contacts::{
+    Contact, Identity, contact_book, link_contacts, unlink_contact
+};
+
+/// Returns the identity of a person in a direct chat
+fn identity(name: &str, network: Option<&str>, user_id: &str) -> Identity {
+    Identity::new(
+        name.to_owned(),
+        network.map(str::to_owned),
+        RoomId::parse(format!("!{name}:localhost")).unwrap(),
+        UserId::parse(user_id).unwrap(),
+    )
+}
+
+/// Returns the identities of the tests: Bob on Matrix and on `WhatsApp`, and
+/// Alice on Matrix, found twice
+fn identities() -> Vec<Identity> {
+    vec![
+        identity("Bob (WA)", Some("whatsapp"), "@whatsapp_336:localhost"),
+        identity("Bob", None, "@bob:localhost"),
+        identity("Alice", None, "@alice:localhost"),
+        identity("Alice again", None, "@alice:localhost"),
+    ]
+}
+
+/// Returns the names of the contacts, in order
+fn names(contacts: &[Contact]) -> Vec<&str> {
+    contacts.iter().map(Contact::as_name).collect()
+}
+
+/// Without meta-contacts, each user is a contact, listed once and sorted by
+/// name.
+#[test]
+fn one_contact_per_user() {
+    let contacts = contact_book(identities(), &[]);
+    assert_eq!(names(&contacts), ["Alice", "Bob", "Bob (WA)"]);
+    assert!(contacts.iter().all(|contact| !contact.is_linked()));
+}
+
+/// Linking two contacts groups their identities under the name of the first
+/// one, the Matrix identity first, and unlinking them splits them again.
+#[test]
+fn link_and_unlink() {
+    let contacts = contact_book(identities(), &[]);
+    let mut meta_contacts = vec![];
+    link_contacts(&mut meta_contacts, &contacts[1], &contacts[2]);
+
+    let linked = contact_book(identities(), &meta_contacts);
+    assert_eq!(names(&linked), ["Alice", "Bob"]);
+    assert!(linked[1].is_linked());
+    let networks = linked[1]
+        .as_identities()
+        .iter()
+        .map(Identity::as_network)
+        .collect::<Vec<_>>();
+    assert_eq!(networks, [None, Some("whatsapp")]);
+    assert!(linked[1].matches("whatsapp_336"));
+
+    unlink_contact(&mut meta_contacts, &linked[1]);
+    assert!(meta_contacts.is_empty());
+    let unlinked = contact_book(identities(), &meta_contacts);
+    assert_eq!(names(&unlinked), ["Alice", "Bob", "Bob (WA)"]);
+}
+
+/// Linking a contact that is already linked merges the meta-contacts.
+#[test]
+fn merge_meta_contacts() {
+    let contacts = contact_book(identities(), &[]);
+    let mut meta_contacts = vec![];
+    link_contacts(&mut meta_contacts, &contacts[1], &contacts[2]);
+    let linked = contact_book(identities(), &meta_contacts);
+    link_contacts(&mut meta_contacts, &linked[1], &linked[0]);
+
+    assert_eq!(meta_contacts.len(), 1);
+    let merged = contact_book(identities(), &meta_contacts);
+    assert_eq!(names(&merged), ["Bob"]);
+    assert_eq!(merged[0].as_identities().len(), 3);
+}
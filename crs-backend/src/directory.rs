@@ -0,0 +1,194 @@
+//! Public room directory and user directory of the homeserver, and addresses
+//! of the rooms to join: aliases, ids and `matrix.to` links.
+
+use core::fmt::{self, Display, Formatter};
+
+use matrix_sdk::ruma::api::client::directory::get_public_rooms_filtered::v3::Request as PublicRoomsRequest;
+use matrix_sdk::ruma::directory::Filter;
+use matrix_sdk::ruma::matrix_uri::MatrixId;
+use matrix_sdk::ruma::{
+    MatrixToUri, OwnedRoomOrAliasId, OwnedServerName, OwnedUserId, UInt, UserId
+};
+use matrix_sdk::{Client, Error};
+
+/// Prefix of the `matrix.to` links
+const MATRIX_TO_PREFIX: &str = "https://matrix.to/#/";
+
+/// Number of rooms fetched per page of the directory
+const PAGE_SIZE: u32 = 20;
+
+/// Maximum number of users fetched when searching the user directory
+const USER_SEARCH_LIMIT: u64 = 10;
+
+/// User found in the user directory
+pub struct DirectoryUser {
+    /// Display name of the user, if they have one
+    display_name: Option<String>,
+    /// Unique identifier of the user
+    user_id:      OwnedUserId,
+}
+
+impl DirectoryUser {
+    /// Returns the unique identifier of the user
+    #[must_use]
+    pub fn as_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+}
+
+impl Display for DirectoryUser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.display_name {
+            Some(name) => write!(f, "{name} ({})", self.user_id),
+            None => Display::fmt(&self.user_id, f),
+        }
+    }
+}
+
+/// Room listed in the public room directory
+pub struct PublicRoom {
+    /// Canonical alias of the room, if it has one
+    pub alias:        Option<String>,
+    /// Number of members that joined the room
+    pub member_count: u64,
+    /// Name of the room, if it has one
+    pub name:         Option<String>,
+    /// Unique identifier of the room
+    pub room_id:      String,
+    /// Topic of the room, if it has one
+    pub topic:        Option<String>,
+}
+
+impl PublicRoom {
+    /// Checks if the name, the alias or the topic of the room contains the
+    /// search, which must be in lowercase.
+    ///
+    /// An empty search matches all the rooms.
+    #[must_use]
+    pub fn matches(&self, search: &str) -> bool {
+        search.is_empty()
+            || [&self.name, &self.alias, &self.topic].into_iter().any(|field| {
+                field
+                    .as_ref()
+                    .is_some_and(|value| value.to_lowercase().contains(search))
+            })
+    }
+}
+
+/// Page of results of the public room directory
+pub struct PublicRoomsPage {
+    /// Token to fetch the next page, if there is one
+    pub next_batch: Option<String>,
+    /// Token to fetch the previous page, if there is one
+    pub prev_batch: Option<String>,
+    /// Rooms of the page
+    pub rooms:      Vec<PublicRoom>,
+}
+
+/// Checks if a text is the address of a room: `#alias:server`,
+/// `!id:server` or a `matrix.to` link.
+#[must_use]
+pub fn is_room_address(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with('#')
+        || text.starts_with('!')
+        || text.starts_with(MATRIX_TO_PREFIX)
+}
+
+/// Checks if a text is a valid user id, i.e., `@user:server`.
+#[must_use]
+pub fn is_user_id(text: &str) -> bool {
+    UserId::parse(text.trim()).is_ok()
+}
+
+/// Parses the address of a room, with the servers to join it through if
+/// the address is a `matrix.to` link.
+///
+/// # Errors
+///
+/// When the address isn't a valid alias, id or `matrix.to` link to a room.
+pub fn parse_room_address(
+    address: &str,
+) -> Result<(OwnedRoomOrAliasId, Vec<OwnedServerName>), Error> {
+    let address = address.trim();
+    if !address.starts_with(MATRIX_TO_PREFIX) {
+        return Ok((OwnedRoomOrAliasId::try_from(address)?, vec![]));
+    }
+    let uri = MatrixToUri::parse(address)
+        .map_err(|err| Error::UnknownError(err.into()))?;
+    let room = if let MatrixId::Room(room_id) = uri.id() {
+        OwnedRoomOrAliasId::from(room_id.clone())
+    } else if let MatrixId::RoomAlias(alias) = uri.id() {
+        OwnedRoomOrAliasId::from(alias.clone())
+    } else {
+        return Err(Error::UnknownError(
+            "This link doesn't point to a room".into(),
+        ));
+    };
+    Ok((room, uri.via().to_vec()))
+}
+
+/// Searches the public room directory of the homeserver.
+///
+/// An empty search lists all the public rooms. The `since` token is the
+/// `next_batch` or `prev_batch` of another page, to paginate.
+///
+/// # Errors
+///
+/// Returns an error if the directory couldn't be fetched.
+pub async fn search_public_rooms(
+    client: &Client,
+    search: &str,
+    since: Option<&str>,
+) -> Result<PublicRoomsPage, Error> {
+    let mut filter = Filter::new();
+    let search = search.trim();
+    filter.generic_search_term =
+        (!search.is_empty()).then(|| search.to_owned());
+
+    let mut request = PublicRoomsRequest::new();
+    request.filter = filter;
+    request.limit = Some(UInt::from(PAGE_SIZE));
+    request.since = since.map(str::to_owned);
+
+    let response = client.public_rooms_filtered(request).await?;
+    Ok(PublicRoomsPage {
+        next_batch: response.next_batch,
+        prev_batch: response.prev_batch,
+        rooms:      response
+            .chunk
+            .into_iter()
+            .map(|chunk| PublicRoom {
+                alias:        chunk
+                    .canonical_alias
+                    .map(|alias| alias.to_string()),
+                member_count: u64::from(chunk.num_joined_members),
+                name:         chunk.name,
+                room_id:      chunk.room_id.to_string(),
+                topic:        chunk.topic,
+            })
+            .collect(),
+    })
+}
+
+/// Searches the user directory of the homeserver, by user id or display
+/// name.
+///
+/// # Errors
+///
+/// Returns an error if the directory couldn't be fetched.
+pub async fn search_users(
+    client: &Client,
+    search: &str,
+) -> Result<Vec<DirectoryUser>, Error> {
+    let response =
+        client.search_users(search.trim(), USER_SEARCH_LIMIT).await?;
+    Ok(response
+        .results
+        .into_iter()
+        .map(|user| DirectoryUser {
+            display_name: user.display_name,
+            user_id:      user.user_id,
+        })
+        .collect())
+}
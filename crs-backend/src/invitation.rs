@@ -0,0 +1,61 @@
+//! Preview of a room the user was invited to, built from the stripped state
+//! sent with the invitation.
+
+use matrix_sdk::{Room, RoomState};
+
+use crate::bridge::get_bridge_info;
+use crate::member::DisplayMember;
+
+/// Preview of a room with a pending invitation
+#[derive(Clone)]
+pub struct Invitation {
+    /// `mxc://` url of the avatar of the room, if it has one
+    pub avatar_url:   Option<String>,
+    /// Whether the messages of the room are encrypted
+    pub encrypted:    bool,
+    /// Member that sent the invitation, if known
+    pub inviter:      Option<DisplayMember>,
+    /// Whether the invitation is for a direct chat
+    pub is_direct:    bool,
+    /// Number of members that joined the room
+    pub member_count: u64,
+    /// Name of the room, if it has one
+    pub name:         Option<String>,
+    /// Name of the network the room is bridged from, if any
+    pub network:      Option<String>,
+    /// Topic of the room, if it has one
+    pub topic:        Option<String>,
+}
+
+/// Loads the preview of a room the user was invited to.
+///
+/// Returns `None` if there is no pending invitation for this room, or if the
+/// details of the invitation couldn't be fetched.
+pub async fn get_invitation(room: &Room) -> Option<Invitation> {
+    if !matches!(room.state(), RoomState::Invited) {
+        return None;
+    }
+    let invite = room.invite_details().await.ok()?;
+    let invitee_direct = invite
+        .invitee
+        .event()
+        .original_content()
+        .and_then(|content| content.is_direct)
+        .unwrap_or(false);
+
+    Some(Invitation {
+        avatar_url:   room.avatar_url().map(|url| url.to_string()),
+        encrypted:    room.encryption_state().is_encrypted(),
+        inviter:      invite
+            .inviter
+            .as_ref()
+            .map(|inviter| DisplayMember::new(inviter, room.own_user_id())),
+        is_direct:    invitee_direct || room.is_direct().await.unwrap_or(false),
+        member_count: room.joined_members_count(),
+        name:         room.name(),
+        network:      get_bridge_info(room)
+            .await
+            .map(|bridge| bridge.as_protocol().to_owned()),
+        topic:        room.topic(),
+    })
+}
@@ -45,6 +45,15 @@
     reason = "bad lints"
 )]
 
+pub mod bridge;
+mod changes;
+pub mod contacts;
+pub mod directory;
+pub mod invitation;
+pub mod member;
 pub mod message;
+mod qr;
 pub mod room;
+pub mod settings;
+pub mod space;
 pub mod user;
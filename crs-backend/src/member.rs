@@ -0,0 +1,298 @@
+//! Interface to display the members of a room
+
+use core::fmt::{self, Display, Formatter};
+
+use matrix_sdk::room::RoomMember;
+use matrix_sdk::ruma::events::presence::PresenceEvent;
+use matrix_sdk::ruma::events::room::member::MembershipState;
+use matrix_sdk::ruma::events::room::power_levels::UserPowerLevel;
+use matrix_sdk::ruma::presence::PresenceState;
+use matrix_sdk::ruma::{OwnedUserId, UserId};
+use matrix_sdk::{Room, RoomMemberships};
+
+use crate::bridge::{UserKind, ghost_name};
+
+/// Minimum power level of the administrators of a room
+const ADMINISTRATOR_POWER_LEVEL: i64 = 100;
+
+/// Link prefix used to mention a user
+const MENTION_LINK_PREFIX: &str = "https://matrix.to/#/";
+
+/// Minimum power level of the moderators of a room
+const MODERATOR_POWER_LEVEL: i64 = 50;
+
+/// Member of a room
+#[derive(Clone)]
+pub struct DisplayMember {
+    /// Display name of the member in the room, if they have one, or the
+    /// remote id of the contact for the ghosts of the bridges
+    display_name: Option<String>,
+    /// Whether the member is the logged in user, a ghost or a bridge bot
+    kind:         UserKind,
+    /// Whether the member joined the room, was invited to it or was banned
+    membership:   Membership,
+    /// Power level of the member in the room.
+    ///
+    /// `None` if the power level is infinite, i.e., if the member created
+    /// the room.
+    power_level:  Option<i64>,
+    /// Last presence of the member received by the client, if any
+    presence:     Option<Presence>,
+    /// Unique identifier of the member
+    user_id:      OwnedUserId,
+}
+
+impl DisplayMember {
+    /// Returns the name of the member.
+    ///
+    /// This is the display name if the member has one, or the user id
+    /// otherwise.
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or_else(|| self.user_id.as_str())
+    }
+
+    /// Returns the unique identifier of the member
+    #[must_use]
+    pub fn as_user_id(&self) -> &UserId {
+        &self.user_id
+    }
+
+    /// Returns whether the member is the logged in user, a ghost or a bridge
+    /// bot
+    #[must_use]
+    pub const fn kind(&self) -> UserKind {
+        self.kind
+    }
+
+    /// Returns the Markdown link to mention the member, which is rendered as
+    /// a pill by the clients.
+    ///
+    /// The characters of the name that would end the text of the link are
+    /// escaped.
+    #[must_use]
+    pub fn markdown_pill(&self) -> String {
+        let mut name = String::with_capacity(self.as_name().len());
+        for character in self.as_name().chars() {
+            if matches!(character, '[' | ']' | '\\') {
+                name.push('\\');
+            }
+            name.push(character);
+        }
+        format!("[{name}]({MENTION_LINK_PREFIX}{})", self.user_id)
+    }
+
+    /// Returns whether the member joined the room, was invited to it or was
+    /// banned
+    #[must_use]
+    pub const fn membership(&self) -> Membership {
+        self.membership
+    }
+
+    /// Returns the text written in a message to mention the member
+    #[must_use]
+    pub fn mention_text(&self) -> String {
+        format!("@{}", self.as_name())
+    }
+
+    /// Checks if the member is the bot of a known bridge
+    #[must_use]
+    pub const fn is_bridge_bot(&self) -> bool {
+        matches!(self.kind, UserKind::BridgeBot(_))
+    }
+
+    /// Creates a new [`DisplayMember`] from a [`RoomMember`], the logged in
+    /// user being the given one
+    #[must_use]
+    pub fn new(member: &RoomMember, own_user_id: &UserId) -> Self {
+        let power_level =
+            if let UserPowerLevel::Int(level) = member.power_level() {
+                Some(i64::from(level))
+            } else {
+                None
+            };
+        let user_id = member.user_id();
+        let kind = UserKind::of(user_id, own_user_id);
+        Self {
+            display_name: member
+                .display_name()
+                .map(str::to_owned)
+                .or_else(|| ghost_name(user_id, own_user_id.server_name())),
+            kind,
+            membership: Membership::from(member.membership()),
+            power_level,
+            presence: member.presence().map(Presence::from),
+            user_id: user_id.to_owned(),
+        }
+    }
+
+    /// Returns the power level of the member, or `None` if it is infinite
+    #[must_use]
+    pub const fn power_level(&self) -> Option<i64> {
+        self.power_level
+    }
+
+    /// Returns the last presence of the member received by the client
+    #[must_use]
+    pub const fn presence(&self) -> Option<Presence> {
+        self.presence
+    }
+
+    /// Returns the role of the member, deduced from their power level
+    #[must_use]
+    pub const fn role(&self) -> Role {
+        match self.power_level {
+            None => Role::Creator,
+            Some(level) if level >= ADMINISTRATOR_POWER_LEVEL =>
+                Role::Administrator,
+            Some(level) if level >= MODERATOR_POWER_LEVEL => Role::Moderator,
+            Some(_) => Role::User,
+        }
+    }
+}
+
+impl Display for DisplayMember {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(display_name) = self.display_name.as_ref() {
+            write!(f, "{display_name} ({})", self.user_id)
+        } else {
+            write!(f, "{}", self.user_id)
+        }
+    }
+}
+
+/// State of a member in a room
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Membership {
+    /// The member was banned from the room
+    Banned,
+    /// The member was invited but didn't join the room yet
+    Invited,
+    /// The member joined the room
+    Joined,
+}
+
+impl From<&MembershipState> for Membership {
+    fn from(state: &MembershipState) -> Self {
+        if *state == MembershipState::Ban {
+            Self::Banned
+        } else if *state == MembershipState::Invite {
+            Self::Invited
+        } else {
+            Self::Joined
+        }
+    }
+}
+
+/// Availability of a member
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    /// The member is disconnected
+    Offline,
+    /// The member is connected and active
+    Online,
+    /// The member is connected but idle
+    Unavailable,
+}
+
+impl From<&PresenceEvent> for Presence {
+    fn from(event: &PresenceEvent) -> Self {
+        let state = &event.content.presence;
+        if *state == PresenceState::Online {
+            Self::Online
+        } else if *state == PresenceState::Unavailable {
+            Self::Unavailable
+        } else {
+            Self::Offline
+        }
+    }
+}
+
+/// Role of a member in a room, deduced from their power level
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Member with a power level of at least 100
+    Administrator,
+    /// Member who created the room, with an infinite power level
+    Creator,
+    /// Member with a power level of at least 50
+    Moderator,
+    /// Member without any particular privilege
+    User,
+}
+
+impl Role {
+    /// Returns the name of the role, to group the members
+    #[must_use]
+    pub const fn as_name(self) -> &'static str {
+        match self {
+            Self::Administrator => "Administrators",
+            Self::Creator => "Creators",
+            Self::Moderator => "Moderators",
+            Self::User => "Users",
+        }
+    }
+
+    /// Returns the rank of the role, the most privileged role being first
+    #[must_use]
+    pub const fn rank(self) -> u8 {
+        match self {
+            Self::Creator => 0,
+            Self::Administrator => 1,
+            Self::Moderator => 2,
+            Self::User => 3,
+        }
+    }
+}
+
+/// Loads the members of a room that joined it, were invited to it or were
+/// banned from it, sorted by role and then by name.
+///
+/// # Errors
+///
+/// Returns an error if the members couldn't be fetched from the server.
+pub async fn get_room_members(
+    room: &Room,
+) -> Result<Vec<DisplayMember>, matrix_sdk::Error> {
+    let memberships =
+        RoomMemberships::JOIN | RoomMemberships::INVITE | RoomMemberships::BAN;
+    let mut members = room
+        .members(memberships)
+        .await?
+        .iter()
+        .map(|member| DisplayMember::new(member, room.own_user_id()))
+        .collect::<Vec<_>>();
+    members.sort_by(|first, second| {
+        first
+            .role()
+            .rank()
+            .cmp(&second.role().rank())
+            .then_with(|| first.as_name().cmp(second.as_name()))
+    });
+    Ok(members)
+}
+
+/// Checks if a text contains a word, not preceded nor followed by other
+/// characters of a word, e.g. `Tom` in `Hi Tom!` but not in `Tomorrow`.
+pub(crate) fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(index, _)| has_word_at(text, index, word))
+}
+
+/// Checks if a text contains a word at the given byte index, not preceded nor
+/// followed by other characters of a word.
+pub(crate) fn has_word_at(text: &str, index: usize, word: &str) -> bool {
+    let (Some(before), Some(after)) = (text.get(..index), text.get(index..))
+    else {
+        return false;
+    };
+    !word.is_empty()
+        && !before.ends_with(is_word_character)
+        && after
+            .strip_prefix(word)
+            .is_some_and(|rest| !rest.starts_with(is_word_character))
+}
+
+/// Checks if a character can be part of a word, e.g. of a name
+fn is_word_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
@@ -5,19 +5,120 @@
 use matrix_sdk::Room;
 use matrix_sdk::deserialized_responses::TimelineEvent;
 use matrix_sdk::room::MessagesOptions;
-use matrix_sdk::ruma::{UInt, UserId};
+use matrix_sdk::ruma::events::room::{EncryptedFile, MediaSource};
+use matrix_sdk::ruma::{OwnedMxcUri, OwnedUserId, UInt, UserId};
 use serde::{Deserialize, Serialize as Serialise}; // ignore-spell
 use serde_json::Value;
 
+use crate::bridge::{UserKind, ghost_name};
+use crate::member::contains_word;
+use crate::qr::decode_qr_image;
+
+/// Format of the `formatted_body` that can be displayed
+const HTML_FORMAT: &str = "org.matrix.custom.html";
+
+/// Type of the messages that contain an image
+const IMAGE_MSGTYPE: &str = "m.image";
+
+/// Type of the relation of an edit to the message it replaces
+const REPLACE_REL_TYPE: &str = "m.replace";
+
 #[derive(Serialise, Deserialize)]
 struct Content {
-    body: Option<String>,
+    body:                 Option<String>,
+    #[serde(rename = "fi.mau.double_puppet_source")]
+    double_puppet_source: Option<String>,
+    file:                 Option<EncryptedFile>,
+    format:               Option<String>,
+    formatted_body:       Option<String>,
+    #[serde(rename = "m.mentions")]
+    mentions:             Option<Mentions>,
+    msgtype:              Option<String>,
+    #[serde(rename = "m.new_content")]
+    new_content:          Option<Box<Content>>,
+    #[serde(rename = "m.relates_to")]
+    relates_to:           Option<Relation>,
+    url:                  Option<OwnedMxcUri>,
+}
+
+impl Content {
+    /// Returns the source of the image, if the message is an image
+    fn image_source(&self) -> Option<MediaSource> {
+        if self.msgtype.as_deref() != Some(IMAGE_MSGTYPE) {
+            return None;
+        }
+        self.file
+            .clone()
+            .map(|file| MediaSource::Encrypted(Box::new(file)))
+            .or_else(|| self.url.clone().map(MediaSource::Plain))
+    }
+
+    /// Returns the id of the message replaced by this one, if it is an edit
+    fn replaced_event(&self) -> Option<&str> {
+        let relation = self.relates_to.as_ref()?;
+        if relation.rel_type.as_deref() == Some(REPLACE_REL_TYPE) {
+            relation.event_id.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Serialise, Deserialize)]
+struct Relation {
+    event_id: Option<String>,
+    rel_type: Option<String>,
+}
+
+#[derive(Serialise, Deserialize)]
+struct Mentions {
+    #[serde(default)]
+    user_ids: Vec<String>,
 }
 
 #[derive(Serialise, Deserialize)]
 struct Message {
-    content: Content,
-    sender:  String,
+    content:          Content,
+    event_id:         Option<String>,
+    origin_server_ts: Option<u64>,
+    sender:           String,
+}
+
+/// QR code sent as an image in a message
+#[derive(Clone)]
+pub enum QrCode {
+    /// The sender sent a newer QR code, this one must not be scanned anymore
+    Expired,
+    /// Content of the QR code, to be rendered again
+    Valid(String),
+}
+
+/// Identity of the logged in user, to know if they are mentioned in a message
+struct Recipient {
+    display_name: Option<String>,
+    user_id:      OwnedUserId,
+}
+
+impl Recipient {
+    /// Checks if the user is mentioned in a message.
+    ///
+    /// If the message has `m.mentions`, they are the only ones taken into
+    /// account. Otherwise, the body is searched for the user id and the display
+    /// name, as whole words, e.g. `Tom` isn't mentioned in `Tomorrow`.
+    fn is_mentioned(&self, body: &str, mentions: Option<&Mentions>) -> bool {
+        mentions.map_or_else(
+            || {
+                contains_word(body, self.user_id.as_str())
+                    || self
+                        .display_name
+                        .as_ref()
+                        .is_some_and(|name| contains_word(body, name))
+            },
+            |explicit| {
+                explicit.user_ids.iter().any(|id| id == self.user_id.as_str())
+            },
+        )
+    }
 }
 
 /// Message from a room
@@ -25,8 +126,16 @@ struct Message {
 /// A message can represent anything: a rule, a reaction, an action (e.g.
 /// someone joined), etc.
 pub struct DisplayMessage {
-    body:   String,
-    sender: String,
+    body:           String,
+    event_id:       Option<String>,
+    formatted_body: Option<String>,
+    mentions_me:    bool,
+    puppet_source:  Option<String>,
+    qr_code:        Option<QrCode>,
+    sender:         String,
+    sender_id:      String,
+    sender_kind:    UserKind,
+    timestamp:      Option<u64>,
 }
 
 impl DisplayMessage {
@@ -36,40 +145,199 @@ impl DisplayMessage {
         &self.body
     }
 
+    /// Returns the id of the event of the message, if known
+    #[must_use]
+    pub fn as_event_id(&self) -> Option<&str> {
+        self.event_id.as_deref()
+    }
+
+    /// Returns the HTML body of the message, if it has one
+    ///
+    /// This is only provided for messages with the `org.matrix.custom.html`
+    /// format. The HTML is not sanitised.
+    #[must_use]
+    pub fn as_html(&self) -> Option<&str> {
+        self.formatted_body.as_deref()
+    }
+
     /// Returns the sender of the message
     #[must_use]
     pub fn as_sender(&self) -> &str {
         &self.sender
     }
 
+    /// Returns the user id of the sender of the message
+    #[must_use]
+    pub fn as_sender_id(&self) -> &str {
+        &self.sender_id
+    }
+
+    /// Creates a message from its content, downloading and decoding the
+    /// image if it is a QR code sent by the bot of a known bridge.
+    ///
+    /// Returns `None` if the content has no body, i.e., if it isn't a
+    /// message.
+    async fn from_content(
+        content: Content,
+        (event_id, timestamp): (Option<String>, Option<u64>),
+        (sender, sender_id): (String, String),
+        room: &Room,
+        recipient: &Recipient,
+    ) -> Option<Self> {
+        let image = content.image_source();
+        let body = content.body?;
+        let formatted_body = content
+            .formatted_body
+            .filter(|_| content.format.as_deref() == Some(HTML_FORMAT));
+        let mentions_me =
+            recipient.is_mentioned(&body, content.mentions.as_ref());
+        let sender_kind = UserId::parse(sender_id.as_str())
+            .map_or(UserKind::Matrix, |user_id| {
+                UserKind::of(&user_id, &recipient.user_id)
+            });
+        let qr_code = match image {
+            Some(source) if matches!(sender_kind, UserKind::BridgeBot(_)) =>
+                decode_qr_image(room, source).await.map(QrCode::Valid),
+            _ => None,
+        };
+        Some(Self {
+            body,
+            event_id,
+            formatted_body,
+            mentions_me,
+            puppet_source: content.double_puppet_source,
+            qr_code,
+            sender,
+            sender_id,
+            sender_kind,
+            timestamp,
+        })
+    }
+
+    /// Indicates whether the logged in user is mentioned in the message
+    #[must_use]
+    pub const fn mentions_me(&self) -> bool {
+        self.mentions_me
+    }
+
+    /// Returns the bridge that sent the message on behalf of the logged in
+    /// user with double puppeting, if the user sent it from the bridged
+    /// network, e.g. from their phone
+    #[must_use]
+    pub fn puppet_source(&self) -> Option<&str> {
+        self.puppet_source.as_deref()
+    }
+
+    /// Returns the QR code sent in the message, if the message is an image
+    /// of a QR code
+    #[must_use]
+    pub const fn qr_code(&self) -> Option<&QrCode> {
+        self.qr_code.as_ref()
+    }
+
+    /// Returns whether the sender is the logged in user, a ghost or a bridge
+    /// bot
+    #[must_use]
+    pub const fn sender_kind(&self) -> UserKind {
+        self.sender_kind
+    }
+
+    /// Returns when the message was sent, in milliseconds since the Unix
+    /// epoch, according to the homeserver of the sender
+    #[must_use]
+    pub const fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
     async fn try_from(
         message: Message,
         room: &Room,
+        recipient: &Recipient,
     ) -> Result<Option<Self>, matrix_sdk::Error> {
-        if let Some(body) = message.content.body
+        if message.content.body.is_some()
             && let user_id = UserId::parse(message.sender)?
             && let Some(member) = room.get_member(&user_id).await?
-            && let Some(name) = member.display_name()
+            && let Some(name) =
+                member.display_name().map(str::to_owned).or_else(|| {
+                    ghost_name(&user_id, recipient.user_id.server_name())
+                })
         {
-            Ok(Some(Self { body, sender: name.to_owned() }))
+            let sender = (name, user_id.to_string());
+            Ok(Self::from_content(
+                message.content,
+                (message.event_id, message.origin_server_ts),
+                sender,
+                room,
+                recipient,
+            )
+            .await)
         } else {
             Ok(None)
         }
     }
 }
 
-async fn parse_message(
+/// Replaces the content of a message by the content of its edit.
+///
+/// Edits sent by another user than the sender of the message are ignored.
+async fn apply_edit(
+    messages: &mut [DisplayMessage],
+    edit: Message,
     room: &Room,
-    event: TimelineEvent,
-) -> matrix_sdk::Result<Option<DisplayMessage>> {
+    recipient: &Recipient,
+) {
+    let Some(replaced) = edit.content.replaced_event() else { return };
+    let Some(original) = messages.iter_mut().find(|original| {
+        original.event_id.as_deref() == Some(replaced)
+            && original.sender_id == edit.sender
+    }) else {
+        return;
+    };
+    let Some(new_content) = edit.content.new_content else { return };
+    let sender = (original.sender.clone(), original.sender_id.clone());
+    let event = (original.event_id.clone(), original.timestamp);
+    if let Some(mut edited) = DisplayMessage::from_content(
+        *new_content,
+        event,
+        sender,
+        room,
+        recipient,
+    )
+    .await
+    {
+        edited.puppet_source =
+            edited.puppet_source.or_else(|| original.puppet_source.take());
+        *original = edited;
+    }
+}
+
+/// Marks the QR codes as expired when their sender sent a newer one, e.g.
+/// when a bridge bot sends a new QR code to log in.
+fn expire_old_qr_codes(messages: &mut [DisplayMessage]) {
+    let mut senders = vec![];
+    for message in messages.iter_mut().rev() {
+        if message.qr_code.is_none() {
+            continue;
+        }
+        if senders.contains(&message.sender_id) {
+            message.qr_code = Some(QrCode::Expired);
+        } else {
+            senders.push(message.sender_id.clone());
+        }
+    }
+}
+
+fn parse_message(event: TimelineEvent) -> matrix_sdk::Result<Message> {
     let json = event.into_raw();
     let value = json.deserialize_as::<Value>()?;
-    let message: Message = serde_json::from_value(value)?;
-    DisplayMessage::try_from(message, room).await
+    Ok(serde_json::from_value(value)?)
 }
 
 /// Loads and parses the messages of a room
 ///
+/// The edits are applied to the messages they replace, and only the last QR
+/// code of each sender is kept valid.
+///
 /// # Errors
 ///
 /// For connection errors
@@ -85,12 +353,25 @@ pub async fn get_room_messages(
 
     let events = room.messages(opts).await?.chunk;
 
+    let user_id = room.own_user_id();
+    let display_name = room
+        .get_member_no_sync(user_id)
+        .await?
+        .and_then(|member| member.display_name().map(str::to_owned));
+    let recipient = Recipient { display_name, user_id: user_id.to_owned() };
+
     let mut messages = Vec::with_capacity(events.len());
     for event in events {
-        if let Some(message) = parse_message(room, event).await? {
+        let message = parse_message(event)?;
+        if message.content.replaced_event().is_some() {
+            apply_edit(&mut messages, message, room, &recipient).await;
+        } else if let Some(message) =
+            DisplayMessage::try_from(message, room, &recipient).await?
+        {
             messages.push(message);
         }
     }
+    expire_old_qr_codes(&mut messages);
 
     Ok(messages)
 }
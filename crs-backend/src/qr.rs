@@ -0,0 +1,130 @@
+//! Decoding of the QR codes sent as images, e.g. by the bots of the bridges to
+//! log in.
+//!
+//! The images are downloaded and decoded only once, the decoded contents being
+//! cached by url: the messages of the rooms are reloaded at every refresh. The
+//! images that couldn't be downloaded are downloaded again after a delay,
+//! doubled after each failure, and only the last images are cached.
+
+use core::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+use matrix_sdk::Room;
+use matrix_sdk::media::{MediaFormat, MediaRequestParameters};
+use matrix_sdk::ruma::events::room::MediaSource;
+use rqrr::PreparedImage;
+
+/// Maximum number of images whose decoding is cached, the oldest ones being
+/// removed first
+const CACHE_CAPACITY: usize = 256;
+
+/// Maximum delay before downloading again an image that couldn't be
+/// downloaded
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Delay before downloading again an image that couldn't be downloaded,
+/// doubled after each failure
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Images that were already downloaded or failed to be, by url of the image.
+static DECODED: LazyLock<Mutex<Cache>> = LazyLock::new(Mutex::default);
+
+/// Cache of the images, bounded to [`CACHE_CAPACITY`] images
+#[derive(Default)]
+struct Cache {
+    /// Result of the download of the images, by url
+    entries: HashMap<String, Decoded>,
+    /// Urls of the images, the oldest first
+    order:   VecDeque<String>,
+}
+
+impl Cache {
+    /// Stores the result of the download of an image, removing the oldest
+    /// image if the cache is full
+    fn insert(&mut self, url: String, decoded: Decoded) {
+        if self.entries.insert(url.clone(), decoded).is_some() {
+            return;
+        }
+        self.order.push_back(url);
+        if self.order.len() > CACHE_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Result of the download of an image
+#[derive(Clone)]
+enum Decoded {
+    /// The image was downloaded, with the content of its QR code, or `None`
+    /// if it isn't one
+    Downloaded(Option<String>),
+    /// The image couldn't be downloaded
+    Failed {
+        /// Number of successive failures
+        failures: u32,
+        /// When to download the image again
+        retry_at: Instant,
+    },
+}
+
+/// Decodes the first QR code found in an image
+fn decode(bytes: &[u8]) -> Option<String> {
+    let image = image::load_from_memory(bytes).ok()?.to_luma8();
+    let mut prepared = PreparedImage::prepare(image);
+    let grids = prepared.detect_grids();
+    let (_, content) = grids.first()?.decode().ok()?;
+    Some(content)
+}
+
+/// Downloads an image, and decodes the QR code it contains.
+///
+/// Returns `None` if the image isn't a QR code, or if it couldn't be
+/// downloaded, in which case it will be downloaded again after a delay.
+pub async fn decode_qr_image(
+    room: &Room,
+    source: MediaSource,
+) -> Option<String> {
+    let url = match &source {
+        MediaSource::Plain(url) => url.to_string(),
+        MediaSource::Encrypted(file) => file.url.to_string(),
+    };
+    let previous_failures =
+        match DECODED.lock().ok()?.entries.get(&url).cloned() {
+            Some(Decoded::Downloaded(content)) => return content,
+            Some(Decoded::Failed { failures, retry_at }) => {
+                if Instant::now() < retry_at {
+                    return None;
+                }
+                failures
+            }
+            None => 0,
+        };
+
+    let request = MediaRequestParameters { source, format: MediaFormat::File };
+    let decoded =
+        match room.client().media().get_media_content(&request, true).await {
+            Ok(bytes) => Decoded::Downloaded(decode(&bytes)),
+            Err(_) => {
+                let failures = previous_failures.saturating_add(1);
+                let delay = RETRY_DELAY
+                    .saturating_mul(2_u32.saturating_pow(previous_failures))
+                    .min(MAX_RETRY_DELAY);
+                Decoded::Failed {
+                    failures,
+                    retry_at: Instant::now()
+                        .checked_add(delay)
+                        .unwrap_or_else(Instant::now),
+                }
+            }
+        };
+    let content = match &decoded {
+        Decoded::Downloaded(content) => content.clone(),
+        Decoded::Failed { .. } => None,
+    };
+    DECODED.lock().ok()?.insert(url, decoded);
+    content
+}
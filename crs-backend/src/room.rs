@@ -3,25 +3,80 @@
 
 extern crate alloc;
 use alloc::sync::Arc;
+use core::cmp::Reverse;
 
-use matrix_sdk::ruma::events::room::message::RoomMessageEventContent;
-use matrix_sdk::ruma::{OwnedRoomId, UserId};
-use matrix_sdk::{Room, RoomState, StoreError};
+use matrix_sdk::ruma::api::client::receipt::create_receipt::v3::ReceiptType as CreateReceiptType;
+use matrix_sdk::ruma::events::Mentions;
+use matrix_sdk::ruma::events::receipt::{Receipt, ReceiptThread, ReceiptType};
+use matrix_sdk::ruma::events::relation::InReplyTo;
+use matrix_sdk::ruma::events::room::guest_access::{
+    GuestAccess, RoomGuestAccessEventContent
+};
+use matrix_sdk::ruma::events::room::history_visibility::RoomHistoryVisibilityEventContent;
+use matrix_sdk::ruma::events::room::join_rules::{
+    JoinRule as MatrixJoinRule, RoomJoinRulesEventContent
+};
+use matrix_sdk::ruma::events::room::message::{
+    FormattedBody, Relation, RoomMessageEventContent
+};
+use matrix_sdk::ruma::events::room::power_levels::RoomPowerLevels;
+use matrix_sdk::ruma::{
+    EventId, Int, MxcUri, OwnedEventId, OwnedRoomId, UserId
+};
+use matrix_sdk::{Room, RoomMemberships, RoomState, StoreError};
 
+use crate::bridge::{
+    BridgeInfo, ReportedBridgeState, get_bridge_info, get_reported_bridge_state
+};
+use crate::changes::{Changes, take_changes};
+use crate::contacts::Identity;
+use crate::directory::{DirectoryUser, PublicRoomsPage, search_users};
+use crate::invitation::{Invitation, get_invitation};
+use crate::member::{DisplayMember, Membership, get_room_members, has_word_at};
 use crate::message::{DisplayMessage, get_room_messages};
+use crate::settings::{
+    HistoryVisibility, JoinRule, RoomSettings, get_room_settings
+};
+use crate::space::{
+    get_space_children, get_space_hierarchy, get_space_parents
+};
 
 /// Interface to display a room
 ///
 /// If one of the fields failed to load, the field will contain an error.
 pub struct DisplayRoom {
+    /// Bridge of the room, if it is bridged from another network
+    bridge:       Option<BridgeInfo>,
+    /// State reported by the bridge whose bot is in the room, if any
+    bridge_state: Option<ReportedBridgeState>,
+    /// Rooms contained in the space, if the room is a space
+    children:     Vec<OwnedRoomId>,
+    /// Preview of the room, if an invitation is pending
+    invitation:   Option<Invitation>,
+    /// Whether the room is a direct chat, according to the `m.direct` account
+    /// data
+    is_direct:    bool,
+    /// Whether the room is a space, grouping other rooms
+    is_space:     bool,
+    /// Parts of the state fetched when loading the room, the other ones being
+    /// kept when updating a room from this one
+    loaded:       Changes,
+    /// Members that joined the room, were invited to it or were banned from
+    /// it, if they were loaded
+    members:      Option<Result<Vec<DisplayMember>, matrix_sdk::Error>>,
     /// Matrix room
-    messages: Result<Vec<DisplayMessage>, matrix_sdk::Error>,
+    messages:     Result<Vec<DisplayMessage>, matrix_sdk::Error>,
     /// Room's list of messages
-    name:     Result<Arc<str>, StoreError>,
+    name:         Result<Arc<str>, StoreError>,
+    /// Spaces the room declares itself part of
+    parents:      Vec<OwnedRoomId>,
+    /// Event up to which the user read the room, according to their public or
+    /// private read receipt, if any
+    read_receipt: Option<String>,
     /// Inner associated matrix room
-    room:     Arc<Room>,
+    room:         Arc<Room>,
     /// Room unique identifier
-    room_id:  OwnedRoomId,
+    room_id:      OwnedRoomId,
 }
 
 impl DisplayRoom {
@@ -52,6 +107,81 @@ impl DisplayRoom {
         RoomWrap(Arc::clone(&self.room))
     }
 
+    /// Returns the bridge of the room, if it is bridged from another network
+    /// (`WhatsApp`, Signal, Telegram, etc.)
+    #[must_use]
+    pub const fn bridge(&self) -> Option<&BridgeInfo> {
+        self.bridge.as_ref()
+    }
+
+    /// Indicates whether the user left the room and is allowed to join it
+    /// again without being invited, i.e., if the room is public.
+    #[must_use]
+    pub fn can_rejoin(&self) -> bool {
+        self.is_left()
+            && self
+                .room
+                .join_rule()
+                .is_some_and(|rule| rule == MatrixJoinRule::Public)
+    }
+
+    /// Returns the identity of the other party of a direct chat, or of a
+    /// portal to the chat with a user of a bridged network.
+    ///
+    /// Returns `None` for the other rooms, or if the other party isn't a
+    /// member of the room anymore. The bots of the bridges aren't taken into
+    /// account.
+    #[must_use]
+    pub fn contact_identity(&self) -> Option<Identity> {
+        if self.is_left()
+            || self.is_space
+            || (!self.is_direct && self.bridge.is_none())
+        {
+            return None;
+        }
+        let own_user_id = self.room.own_user_id();
+        let bridge_bot = self.bridge.as_ref().and_then(BridgeInfo::as_bot);
+        let members = self.members.as_ref()?.as_ref().ok()?;
+        let mut others = members.iter().filter(|member| {
+            let user_id = member.as_user_id();
+            user_id != own_user_id
+                && member.membership() != Membership::Banned
+                && !member.is_bridge_bot()
+                && bridge_bot.is_none_or(|bot| bot.as_str() != user_id.as_str())
+        });
+        let other = others.next()?;
+        if others.next().is_some() {
+            return None;
+        }
+        Some(Identity::new(
+            other.as_name().to_owned(),
+            self.bridge
+                .as_ref()
+                .map(|bridge| bridge.as_protocol_id().to_owned()),
+            self.room_id.clone(),
+            other.as_user_id().to_owned(),
+        ))
+    }
+
+    /// Returns the name of the other party of a direct chat.
+    ///
+    /// Returns `None` if the room isn't a direct chat, or if the other party
+    /// isn't a member of the room anymore.
+    #[must_use]
+    pub fn direct_name(&self) -> Option<&str> {
+        if !self.is_direct {
+            return None;
+        }
+        let own_user_id = self.room.own_user_id();
+        self.members
+            .as_ref()?
+            .as_ref()
+            .ok()?
+            .iter()
+            .find(|member| member.as_user_id() != own_user_id)
+            .map(DisplayMember::as_name)
+    }
+
     /// Indicates whether an invitation is pending for this room.
     #[must_use]
     pub fn has_invitation(&self) -> bool {
@@ -64,29 +194,239 @@ impl DisplayRoom {
         &self.room_id
     }
 
-    /// Create a new display room from a [`Room`]
-    pub async fn new(room: Arc<Room>) -> Self {
+    /// Returns the preview of the room if an invitation is pending for it.
+    ///
+    /// This is `None` if the details of the invitation couldn't be fetched.
+    #[must_use]
+    pub const fn invitation(&self) -> Option<&Invitation> {
+        self.invitation.as_ref()
+    }
+
+    /// Indicates whether the room is a direct chat.
+    #[must_use]
+    pub const fn is_direct(&self) -> bool {
+        self.is_direct
+    }
+
+    /// Indicates whether the room belongs to the given space, either because
+    /// the space lists it as one of its children, or because the room lists
+    /// the space as one of its parents.
+    #[must_use]
+    pub fn is_in_space(&self, space: &Self) -> bool {
+        space.children.contains(&self.room_id)
+            || self.parents.contains(&space.room_id)
+    }
+
+    /// Indicates whether the user left the room.
+    #[must_use]
+    pub fn is_left(&self) -> bool {
+        matches!(self.room.state(), RoomState::Left)
+    }
+
+    /// Indicates whether the room is a space, grouping other rooms.
+    #[must_use]
+    pub const fn is_space(&self) -> bool {
+        self.is_space
+    }
+
+    /// Loads a room, fetching only the given parts of its state besides its
+    /// name, its messages, and whether it is a direct chat or a space.
+    ///
+    /// The bridge is also fetched when the members changed, as the members
+    /// are only loaded for the direct chats and the bridged rooms: the
+    /// members of the other rooms are loaded on demand, with
+    /// [`RoomWrap::members`].
+    async fn load(room: Arc<Room>, changes: Changes) -> Self {
         let name = get_room_name(&room).await;
         let messages = get_room_messages(&room).await;
+        let is_direct = room.is_direct().await.unwrap_or(false);
+        let is_space = room.is_space();
+        let mut loaded =
+            Changes { bridge: changes.bridge || changes.members, ..changes };
+        let bridge =
+            if loaded.bridge { get_bridge_info(&room).await } else { None };
+        let members = if loaded.members && (is_direct || bridge.is_some()) {
+            Some(get_room_members(&room).await)
+        } else {
+            None
+        };
+        loaded.members = members.is_some();
+        let bridge_state = if loaded.bridge_state {
+            get_reported_bridge_state(&room).await
+        } else {
+            None
+        };
+        let invitation =
+            if loaded.invitation { get_invitation(&room).await } else { None };
+        let (children, parents) = if loaded.space {
+            (get_space_children(&room).await, get_space_parents(&room).await)
+        } else {
+            (vec![], vec![])
+        };
+        let read_receipt =
+            if loaded.receipt { get_read_receipt(&room).await } else { None };
 
         let room_id = room.room_id().to_owned();
-        Self { messages, name, room, room_id }
+        Self {
+            bridge,
+            bridge_state,
+            children,
+            invitation,
+            is_direct,
+            is_space,
+            loaded,
+            members,
+            messages,
+            name,
+            parents,
+            read_receipt,
+            room,
+            room_id,
+        }
+    }
+
+    /// Loads a room, fetching only the parts of its state that changed since
+    /// it was last loaded, the other parts being kept by
+    /// [`Self::update_from`].
+    ///
+    /// All the state is fetched the first time the room is loaded.
+    pub async fn load_changes(room: Arc<Room>) -> Self {
+        let changes = take_changes(&room);
+        Self::load(room, changes).await
+    }
+
+    /// Lists the members of the room that joined it, were invited to it or
+    /// were banned from it, sorted by role.
+    ///
+    /// Returns `None` if they weren't loaded yet: they are loaded with the
+    /// direct chats and the bridged rooms, and on demand for the other rooms
+    /// with [`RoomWrap::members`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user failed to fetch the members on the server.
+    #[must_use]
+    pub fn members(
+        &self,
+    ) -> Option<Result<&[DisplayMember], &matrix_sdk::Error>> {
+        self.members.as_ref().map(|members| members.as_ref().map(Vec::as_slice))
+    }
+
+    /// Returns the user id of the logged in user
+    #[must_use]
+    pub fn own_user_id(&self) -> &UserId {
+        self.room.own_user_id()
+    }
+
+    /// Create a new display room from a [`Room`], fetching all its state
+    pub async fn new(room: Arc<Room>) -> Self {
+        Self::load(room, Changes::ALL).await
     }
 
-    /// Refreshes the name and the messages of a room
+    /// Refreshes the room, fetching again all its state
     pub async fn refresh(&mut self) {
-        self.update_with(
-            get_room_messages(&self.room).await,
-            get_room_name(&self.room).await,
+        let refreshed = Self::new(Arc::clone(&self.room)).await;
+        self.update_from(refreshed);
+    }
+
+    /// Returns the state reported by the bridge whose bot is in the room,
+    /// if any.
+    ///
+    /// See [`KnownBridge::health`](crate::bridge::KnownBridge::health) to
+    /// also take into account the answers of the bot.
+    #[must_use]
+    pub const fn reported_bridge_state(&self) -> Option<&ReportedBridgeState> {
+        self.bridge_state.as_ref()
+    }
+
+    /// Sets the members of the room, once loaded on demand with
+    /// [`RoomWrap::members`].
+    ///
+    /// The members already loaded are kept if the new ones failed to load.
+    pub fn set_members(
+        &mut self,
+        members: Result<Vec<DisplayMember>, matrix_sdk::Error>,
+    ) {
+        if members.is_ok() || self.members.as_ref().is_none_or(Result::is_err) {
+            self.members = Some(members);
+        }
+    }
+
+    /// Records that the user read the room up to the given event, once the
+    /// read receipt was sent, without waiting for the next sync
+    pub fn set_read_receipt(&mut self, event_id: &str) {
+        self.read_receipt = Some(event_id.to_owned());
+    }
+
+    /// Lists the messages the user didn't read yet, i.e., the messages after
+    /// their read receipt, or after their last message if it is more recent.
+    ///
+    /// If the event of the read receipt isn't among the loaded messages, the
+    /// number of unread messages computed by the client is used, before the
+    /// last message of the user, which is only relied on alone when they have
+    /// no read receipt.
+    #[must_use]
+    pub fn unread_messages(&self) -> &[DisplayMessage] {
+        let Ok(messages) = &self.messages else { return &[] };
+        let own_user_id = self.room.own_user_id().as_str();
+        let last_own = messages
+            .iter()
+            .rposition(|message| message.as_sender_id() == own_user_id);
+        let receipt_index = self.read_receipt.as_deref().map(|receipt| {
+            messages
+                .iter()
+                .rposition(|message| message.as_event_id() == Some(receipt))
+        });
+        let last_read = match receipt_index {
+            Some(Some(index)) =>
+                Some(last_own.map_or(index, |own| own.max(index))),
+            Some(None) => None,
+            None => last_own,
+        };
+        let first_unread = last_read.map_or_else(
+            || {
+                let nb_unread =
+                    usize::try_from(self.room.num_unread_messages())
+                        .unwrap_or(usize::MAX);
+                messages.len().saturating_sub(nb_unread)
+            },
+            |index| index.saturating_add(1),
         );
+        messages.get(first_unread..).unwrap_or_default()
     }
 
     /// Updates the content of a room but the contents of another room
+    ///
+    /// Only the parts of the state fetched when loading the other room are
+    /// updated.
     pub fn update_from(&mut self, other: Self) {
+        let loaded = other.loaded;
+        if loaded.bridge {
+            self.bridge = other.bridge;
+        }
+        if loaded.bridge_state {
+            self.bridge_state = other.bridge_state;
+        }
+        if loaded.receipt {
+            self.read_receipt = other.read_receipt;
+        }
+        if loaded.invitation {
+            self.invitation = other.invitation;
+        }
+        if loaded.space {
+            self.children = other.children;
+            self.parents = other.parents;
+        }
+        if let Some(members) = other.members {
+            self.set_members(members);
+        }
+        self.is_direct = other.is_direct;
+        self.is_space = other.is_space;
         self.update_with(other.messages, other.name);
     }
 
-    /// Updates the content of a room with another name and some other messages.
+    /// Updates the content of a room with another name and some other
+    /// messages.
     fn update_with(
         &mut self,
         messages: Result<Vec<DisplayMessage>, matrix_sdk::Error>,
@@ -111,13 +451,111 @@ impl RoomWrap {
     ///
     /// # Errors
     ///
-    /// If the room isn't in the "Invited" or "Left" state, or for regular
-    /// connection errors.
+    /// If the room isn't in the "Invited" state, or for regular connection
+    /// errors.
     pub async fn accept_invitation(
         self,
     ) -> Result<DisplayRoom, matrix_sdk::Error> {
-        self.0.join().await?;
-        Ok(DisplayRoom::new(self.0).await)
+        self.join().await
+    }
+
+    /// Bans a user from the room, with an optional reason
+    ///
+    /// # Errors
+    ///
+    /// - When the user id is invalid
+    /// - When the logged in user isn't allowed to ban this user
+    /// - For regular connection errors
+    pub async fn ban_user(
+        &self,
+        user_id: &str,
+        reason: Option<&str>,
+    ) -> Result<(), matrix_sdk::Error> {
+        let user_id = UserId::parse(user_id)?;
+        self.check_permission(
+            &user_id,
+            "ban",
+            RoomPowerLevels::user_can_ban_user,
+        )
+        .await?;
+        self.0.ban_user(&user_id, reason).await
+    }
+
+    /// Checks with the power levels of the room that the logged in user is
+    /// allowed to do an action on another user, before sending it.
+    ///
+    /// # Errors
+    ///
+    /// - When the power levels of the room couldn't be fetched
+    /// - When the action isn't allowed
+    async fn check_permission<Check>(
+        &self,
+        target: &UserId,
+        action: &str,
+        check: Check,
+    ) -> Result<(), matrix_sdk::Error>
+    where
+        Check: FnOnce(&RoomPowerLevels, &UserId, &UserId) -> bool,
+    {
+        let power_levels = self.0.power_levels().await?;
+        if check(&power_levels, self.0.own_user_id(), target) {
+            Ok(())
+        } else {
+            Err(matrix_sdk::Error::UnknownError(
+                format!(
+                    "You don't have the permission to {action} {target} in \
+                     this room"
+                )
+                .into(),
+            ))
+        }
+    }
+
+    /// Declines the invitation received to join the room.
+    ///
+    /// # Errors
+    ///
+    /// If the room isn't in the "Invited" state, or for regular connection
+    /// errors.
+    pub async fn decline_invitation(&self) -> Result<(), matrix_sdk::Error> {
+        self.0.leave().await
+    }
+
+    /// Enables the end-to-end encryption of the messages of the room.
+    ///
+    /// This can't be disabled afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encryption state event couldn't be sent.
+    pub async fn enable_encryption(&self) -> Result<(), matrix_sdk::Error> {
+        self.0.enable_encryption().await
+    }
+
+    /// Forgets a room that was left, removing it and its history from the
+    /// account of the user.
+    ///
+    /// # Errors
+    ///
+    /// If the room isn't in the "Left" state, or for regular connection
+    /// errors.
+    pub async fn forget(&self) -> Result<(), matrix_sdk::Error> {
+        self.0.forget().await
+    }
+
+    /// Declines the invitation received to join the room, and ignores the user
+    /// who sent it: their messages and invitations won't be received anymore.
+    ///
+    /// # Errors
+    ///
+    /// If the room isn't in the "Invited" state, if the user couldn't be
+    /// ignored, or for regular connection errors.
+    pub async fn ignore_inviter(&self) -> Result<(), matrix_sdk::Error> {
+        let invite = self.0.invite_details().await?;
+        if let Some(inviter) = invite.inviter {
+            self.0.client().account().ignore_user(inviter.user_id()).await?;
+        }
+        self.0.leave().await
     }
 
     /// Create a new room and invite a user to this room
@@ -133,15 +571,373 @@ impl RoomWrap {
         self.0.invite_user_by_id(&UserId::parse(user_id)?).await
     }
 
-    /// Sends a message in a room
+    /// Joins the room, e.g. to rejoin a public room that was left.
+    ///
+    /// The data is then refetched.
+    ///
+    /// # Errors
+    ///
+    /// If the room isn't in the "Invited" or "Left" state, if the join rules
+    /// don't allow the user to join, or for regular connection errors.
+    pub async fn join(self) -> Result<DisplayRoom, matrix_sdk::Error> {
+        self.0.join().await?;
+        Ok(DisplayRoom::new(self.0).await)
+    }
+
+    /// Lists the members that joined the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the members couldn't be fetched from the server.
+    pub async fn joined_members(
+        &self,
+    ) -> Result<Vec<DisplayMember>, matrix_sdk::Error> {
+        Ok(self
+            .0
+            .members(RoomMemberships::JOIN)
+            .await?
+            .iter()
+            .map(|member| DisplayMember::new(member, self.0.own_user_id()))
+            .collect())
+    }
+
+    /// Kicks a user out of the room, with an optional reason
+    ///
+    /// # Errors
+    ///
+    /// - When the user id is invalid
+    /// - When the logged in user isn't allowed to kick this user
+    /// - For regular connection errors
+    pub async fn kick_user(
+        &self,
+        user_id: &str,
+        reason: Option<&str>,
+    ) -> Result<(), matrix_sdk::Error> {
+        let user_id = UserId::parse(user_id)?;
+        self.check_permission(
+            &user_id,
+            "kick",
+            RoomPowerLevels::user_can_kick_user,
+        )
+        .await?;
+        self.0.kick_user(&user_id, reason).await
+    }
+
+    /// Leaves the room.
+    ///
+    /// The room can then be forgotten with [`Self::forget`].
+    ///
+    /// # Errors
+    ///
+    /// If the room isn't in the "Joined" state, or for regular connection
+    /// errors.
+    pub async fn leave(&self) -> Result<(), matrix_sdk::Error> {
+        self.0.leave().await
+    }
+
+    /// Sends a read receipt for an event of the room, to mark the room as
+    /// read up to this event.
+    ///
+    /// # Errors
+    ///
+    /// - When the event id is invalid
+    /// - When the receipt couldn't be sent
+    pub async fn mark_as_read(
+        &self,
+        event_id: &str,
+    ) -> Result<(), matrix_sdk::Error> {
+        let event_id = EventId::parse(event_id)?;
+        self.0
+            .send_single_receipt(
+                CreateReceiptType::Read,
+                ReceiptThread::Unthreaded,
+                event_id,
+            )
+            .await
+    }
+
+    /// Lists the members of the room that joined it, were invited to it or
+    /// were banned from it, sorted by role, to load them on demand in the
+    /// [`DisplayRoom`] with [`DisplayRoom::set_members`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the members couldn't be fetched from the server.
+    pub async fn members(
+        &self,
+    ) -> Result<Vec<DisplayMember>, matrix_sdk::Error> {
+        get_room_members(&self.0).await
+    }
+
+    /// Searches the user directory of the homeserver, to find users to
+    /// invite in the room.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory couldn't be fetched.
+    pub async fn search_users(
+        &self,
+        search: &str,
+    ) -> Result<Vec<DirectoryUser>, matrix_sdk::Error> {
+        search_users(&self.0.client(), search).await
+    }
+
+    /// Sends a message written in Markdown in a room
+    ///
+    /// The message is sent with its raw Markdown as `body` and, if it contains
+    /// any formatting, with the rendered HTML as `formatted_body`
+    /// (`org.matrix.custom.html` format).
+    ///
+    /// The given members whose mention text (cf.
+    /// [`DisplayMember::mention_text`]) is found in the message as a whole
+    /// word are mentioned: they are replaced by pills in the HTML body and are
+    /// added to the `m.mentions` of the message.
     ///
     /// # Errors
     ///
     /// Returns an error when join handle crashes.
-    pub async fn send_plain(&self, msg: &str) -> Result<(), matrix_sdk::Error> {
-        self.0.send(RoomMessageEventContent::text_plain(msg)).await?;
+    pub async fn send_markdown(
+        &self,
+        msg: &str,
+        mentions: &[DisplayMember],
+    ) -> Result<(), matrix_sdk::Error> {
+        let (linked, mentioned) = link_mentions(msg, mentions);
+
+        let mut content = if mentioned.is_empty() {
+            RoomMessageEventContent::text_markdown(msg)
+        } else {
+            FormattedBody::markdown(&linked).map_or_else(
+                || RoomMessageEventContent::text_plain(msg),
+                |html| RoomMessageEventContent::text_html(msg, html.body),
+            )
+        };
+
+        content.mentions = Some(Mentions::with_user_ids(
+            mentioned.iter().map(|member| member.as_user_id().to_owned()),
+        ));
+
+        self.0.send(content).await?;
         Ok(())
     }
+
+    /// Sends a message in a room, and returns the id of its event
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when join handle crashes.
+    pub async fn send_plain(
+        &self,
+        msg: &str,
+    ) -> Result<String, matrix_sdk::Error> {
+        let sent =
+            self.0.send(RoomMessageEventContent::text_plain(msg)).await?;
+        Ok(sent.response.event_id.to_string())
+    }
+
+    /// Sends a message in a room, in reply to the event with the given id
+    ///
+    /// # Errors
+    ///
+    /// - When the event id is invalid
+    /// - When the message couldn't be sent
+    pub async fn send_reply(
+        &self,
+        msg: &str,
+        event_id: &str,
+    ) -> Result<(), matrix_sdk::Error> {
+        let mut content = RoomMessageEventContent::text_plain(msg);
+        content.relates_to = Some(Relation::Reply {
+            in_reply_to: InReplyTo::new(EventId::parse(event_id)?),
+        });
+        self.0.send(content).await?;
+        Ok(())
+    }
+
+    /// Changes the avatar of the room, or removes it if the url is empty
+    ///
+    /// # Errors
+    ///
+    /// - When the url isn't a valid `mxc://` url
+    /// - When the avatar state event couldn't be sent
+    pub async fn set_avatar_url(
+        &self,
+        url: &str,
+    ) -> Result<(), matrix_sdk::Error> {
+        if url.is_empty() {
+            self.0.remove_avatar().await?;
+            return Ok(());
+        }
+        let mxc_uri = <&MxcUri>::from(url);
+        if !mxc_uri.is_valid() {
+            return Err(matrix_sdk::Error::UnknownError(
+                "Invalid avatar url, expected mxc://<server>/<id>".into(),
+            ));
+        }
+        self.0.set_avatar_url(mxc_uri, None).await?;
+        Ok(())
+    }
+
+    /// Allows or forbids guests to join the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the guest access state event couldn't be sent.
+    pub async fn set_guest_access(
+        &self,
+        allowed: bool,
+    ) -> Result<(), matrix_sdk::Error> {
+        let guest_access =
+            if allowed { GuestAccess::CanJoin } else { GuestAccess::Forbidden };
+        self.0
+            .send_state_event(RoomGuestAccessEventContent::new(guest_access))
+            .await?;
+        Ok(())
+    }
+
+    /// Changes who can read the history of the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history visibility state event couldn't be
+    /// sent.
+    pub async fn set_history_visibility(
+        &self,
+        visibility: HistoryVisibility,
+    ) -> Result<(), matrix_sdk::Error> {
+        self.0
+            .send_state_event(RoomHistoryVisibilityEventContent::new(
+                visibility.into(),
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Changes who can join the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the join rules state event couldn't be sent.
+    pub async fn set_join_rule(
+        &self,
+        rule: JoinRule,
+    ) -> Result<(), matrix_sdk::Error> {
+        self.0
+            .send_state_event(RoomJoinRulesEventContent::new(rule.into()))
+            .await?;
+        Ok(())
+    }
+
+    /// Renames the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name state event couldn't be sent.
+    pub async fn set_name(
+        &self,
+        name: String,
+    ) -> Result<(), matrix_sdk::Error> {
+        self.0.set_name(name).await?;
+        Ok(())
+    }
+
+    /// Changes the power level of a user in the room
+    ///
+    /// # Errors
+    ///
+    /// - When the user id is invalid
+    /// - When the logged in user isn't allowed to change the power level of
+    ///   this user
+    /// - When the level is above the one of the logged in user
+    /// - For regular connection errors
+    pub async fn set_power_level(
+        &self,
+        user_id: &str,
+        level: i64,
+    ) -> Result<(), matrix_sdk::Error> {
+        let user_id = UserId::parse(user_id)?;
+        self.check_permission(
+            &user_id,
+            "change the power level of",
+            RoomPowerLevels::user_can_change_user_power_level,
+        )
+        .await?;
+        let level = Int::new_saturating(level);
+        let own_level =
+            self.0.power_levels().await?.for_user(self.0.own_user_id());
+        if level > own_level {
+            return Err(matrix_sdk::Error::UnknownError(
+                format!(
+                    "You can't give a power level above your own ({own_level})"
+                )
+                .into(),
+            ));
+        }
+        self.0.update_power_levels(vec![(&user_id, level)]).await?;
+        Ok(())
+    }
+
+    /// Changes the topic of the room
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the topic state event couldn't be sent.
+    pub async fn set_topic(
+        &self,
+        topic: &str,
+    ) -> Result<(), matrix_sdk::Error> {
+        self.0.set_room_topic(topic).await?;
+        Ok(())
+    }
+
+    /// Loads the current settings of the room, with the ones the logged in
+    /// user is allowed to change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the power levels or the encryption state of the room
+    /// couldn't be fetched.
+    pub async fn settings(&self) -> Result<RoomSettings, matrix_sdk::Error> {
+        get_room_settings(&self.0).await
+    }
+
+    /// Fetches a page of the rooms of the space, including the ones the user
+    /// hasn't joined, filtered with the search.
+    ///
+    /// The `from` token is the `next_batch` of another page, to paginate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the room isn't a space, or if the hierarchy
+    /// couldn't be fetched.
+    pub async fn space_hierarchy(
+        &self,
+        search: &str,
+        from: Option<&str>,
+    ) -> Result<PublicRoomsPage, matrix_sdk::Error> {
+        get_space_hierarchy(&self.0, search, from).await
+    }
+
+    /// Lifts the ban of a user from the room, with an optional reason
+    ///
+    /// # Errors
+    ///
+    /// - When the user id is invalid
+    /// - When the logged in user isn't allowed to unban this user
+    /// - For regular connection errors
+    pub async fn unban_user(
+        &self,
+        user_id: &str,
+        reason: Option<&str>,
+    ) -> Result<(), matrix_sdk::Error> {
+        let user_id = UserId::parse(user_id)?;
+        self.check_permission(
+            &user_id,
+            "unban",
+            RoomPowerLevels::user_can_unban_user,
+        )
+        .await?;
+        self.0.unban_user(&user_id, reason).await
+    }
 }
 
 /// Computes the name of a room
@@ -154,3 +950,63 @@ impl RoomWrap {
 pub async fn get_room_name(room: &Room) -> Result<Arc<str>, StoreError> {
     room.display_name().await.map(|name| Arc::from(name.to_room_alias_name()))
 }
+
+/// Finds the event up to which the user read a room, according to the most
+/// recent of their public and private read receipts.
+async fn get_read_receipt(room: &Room) -> Option<String> {
+    let mut latest = None::<(OwnedEventId, Receipt)>;
+    for receipt_type in [ReceiptType::Read, ReceiptType::ReadPrivate] {
+        if let Ok(Some((event_id, receipt))) = room
+            .load_user_receipt(
+                receipt_type,
+                ReceiptThread::Unthreaded,
+                room.own_user_id(),
+            )
+            .await
+            && latest.as_ref().is_none_or(|(_, other)| receipt.ts > other.ts)
+        {
+            latest = Some((event_id, receipt));
+        }
+    }
+    latest.map(|(event_id, _)| event_id.to_string())
+}
+
+/// Replaces the mention texts of the members found in a message by their
+/// pills, and returns the linked message with the members mentioned in it.
+///
+/// The mentions are matched as whole words, the longest first, so that
+/// `@Tom` isn't found in `@Tomas`.
+fn link_mentions<'member>(
+    msg: &str,
+    members: &'member [DisplayMember],
+) -> (String, Vec<&'member DisplayMember>) {
+    let mut candidates = members
+        .iter()
+        .map(|member| (member.mention_text(), member))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(text, _)| Reverse(text.len()));
+
+    let mut linked = String::with_capacity(msg.len());
+    let mut mentioned = Vec::<&DisplayMember>::new();
+    let mut position = 0_usize;
+    while let Some(rest) = msg.get(position..)
+        && let Some(character) = rest.chars().next()
+    {
+        if let Some((text, member)) =
+            candidates.iter().find(|(text, _)| has_word_at(msg, position, text))
+        {
+            linked.push_str(&member.markdown_pill());
+            if !mentioned
+                .iter()
+                .any(|other| other.as_user_id() == member.as_user_id())
+            {
+                mentioned.push(member);
+            }
+            position = position.saturating_add(text.len());
+        } else {
+            linked.push(character);
+            position = position.saturating_add(character.len_utf8());
+        }
+    }
+    (linked, mentioned)
+}
@@ -0,0 +1,268 @@
+//! Settings of a room that can be edited after its creation: name, topic,
+//! avatar, access and history.
+
+use matrix_sdk::Room;
+use matrix_sdk::ruma::events::StateEventType;
+use matrix_sdk::ruma::events::room::guest_access::GuestAccess;
+use matrix_sdk::ruma::events::room::history_visibility::HistoryVisibility as MatrixHistoryVisibility;
+use matrix_sdk::ruma::events::room::join_rules::JoinRule as MatrixJoinRule;
+
+/// Who can read the history of a room
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HistoryVisibility {
+    /// Members can read the messages sent after they were invited
+    Invited,
+    /// Members can read the messages sent after they joined
+    Joined,
+    /// Members can read all the messages, even the ones sent before they
+    /// joined
+    Shared,
+    /// Anyone can read all the messages, even without joining
+    WorldReadable,
+}
+
+impl HistoryVisibility {
+    /// History visibilities in the order they are proposed to the user
+    pub const ALL: [Self; 4] =
+        [Self::Shared, Self::Invited, Self::Joined, Self::WorldReadable];
+
+    /// Returns the displayed name of the history visibility
+    #[must_use]
+    pub const fn as_name(self) -> &'static str {
+        match self {
+            Self::Invited => "Members, since they were invited",
+            Self::Joined => "Members, since they joined",
+            Self::Shared => "Members, full history",
+            Self::WorldReadable => "Anyone",
+        }
+    }
+}
+
+impl From<&MatrixHistoryVisibility> for HistoryVisibility {
+    fn from(visibility: &MatrixHistoryVisibility) -> Self {
+        if *visibility == MatrixHistoryVisibility::Invited {
+            Self::Invited
+        } else if *visibility == MatrixHistoryVisibility::Joined {
+            Self::Joined
+        } else if *visibility == MatrixHistoryVisibility::WorldReadable {
+            Self::WorldReadable
+        } else {
+            Self::Shared
+        }
+    }
+}
+
+impl From<HistoryVisibility> for MatrixHistoryVisibility {
+    fn from(visibility: HistoryVisibility) -> Self {
+        match visibility {
+            HistoryVisibility::Invited => Self::Invited,
+            HistoryVisibility::Joined => Self::Joined,
+            HistoryVisibility::Shared => Self::Shared,
+            HistoryVisibility::WorldReadable => Self::WorldReadable,
+        }
+    }
+}
+
+/// Who can join a room
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JoinRule {
+    /// Only the invited users can join
+    Invite,
+    /// Users can ask to join, and must then be invited
+    Knock,
+    /// Anyone can join
+    Public,
+}
+
+impl JoinRule {
+    /// Join rules in the order they are proposed to the user
+    pub const ALL: [Self; 3] = [Self::Invite, Self::Knock, Self::Public];
+
+    /// Returns the displayed name of the join rule
+    #[must_use]
+    pub const fn as_name(self) -> &'static str {
+        match self {
+            Self::Invite => "Invite only",
+            Self::Knock => "Ask to join",
+            Self::Public => "Public",
+        }
+    }
+}
+
+impl From<&MatrixJoinRule> for JoinRule {
+    fn from(rule: &MatrixJoinRule) -> Self {
+        if *rule == MatrixJoinRule::Public {
+            Self::Public
+        } else if *rule == MatrixJoinRule::Knock {
+            Self::Knock
+        } else {
+            Self::Invite
+        }
+    }
+}
+
+impl From<JoinRule> for MatrixJoinRule {
+    fn from(rule: JoinRule) -> Self {
+        match rule {
+            JoinRule::Invite => Self::Invite,
+            JoinRule::Knock => Self::Knock,
+            JoinRule::Public => Self::Public,
+        }
+    }
+}
+
+/// Editable setting of a room
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    /// Avatar of the room
+    Avatar,
+    /// End-to-end encryption of the messages
+    Encryption,
+    /// Whether guests can join the room
+    GuestAccess,
+    /// Who can read the history of the room
+    HistoryVisibility,
+    /// Who can join the room
+    JoinRule,
+    /// Name of the room
+    Name,
+    /// Topic of the room
+    Topic,
+}
+
+impl Setting {
+    /// Settings in the order they are displayed to the user
+    pub const ALL: [Self; 7] = [
+        Self::Name,
+        Self::Topic,
+        Self::Avatar,
+        Self::JoinRule,
+        Self::GuestAccess,
+        Self::HistoryVisibility,
+        Self::Encryption,
+    ];
+
+    /// Returns the type of the state event that holds the setting
+    const fn event_type(self) -> StateEventType {
+        match self {
+            Self::Avatar => StateEventType::RoomAvatar,
+            Self::Encryption => StateEventType::RoomEncryption,
+            Self::GuestAccess => StateEventType::RoomGuestAccess,
+            Self::HistoryVisibility => StateEventType::RoomHistoryVisibility,
+            Self::JoinRule => StateEventType::RoomJoinRules,
+            Self::Name => StateEventType::RoomName,
+            Self::Topic => StateEventType::RoomTopic,
+        }
+    }
+}
+
+/// Current settings of a room, with the ones the logged in user can change
+pub struct RoomSettings {
+    /// `mxc://` url of the avatar of the room, empty if the room has none
+    avatar_url:         String,
+    /// Settings the power level of the logged in user allows to change
+    editable:           Vec<Setting>,
+    /// Whether the messages are encrypted. This can't be disabled.
+    encrypted:          bool,
+    /// Whether guests can join the room
+    guest_access:       bool,
+    /// Who can read the history of the room
+    history_visibility: HistoryVisibility,
+    /// Who can join the room
+    join_rule:          JoinRule,
+    /// Name of the room, empty if the room has none
+    name:               String,
+    /// Topic of the room, empty if the room has none
+    topic:              String,
+}
+
+impl RoomSettings {
+    /// Returns the `mxc://` url of the avatar of the room, empty if the room
+    /// has none
+    #[must_use]
+    pub fn as_avatar_url(&self) -> &str {
+        &self.avatar_url
+    }
+
+    /// Returns who can read the history of the room
+    #[must_use]
+    pub const fn as_history_visibility(&self) -> HistoryVisibility {
+        self.history_visibility
+    }
+
+    /// Returns who can join the room
+    #[must_use]
+    pub const fn as_join_rule(&self) -> JoinRule {
+        self.join_rule
+    }
+
+    /// Returns the name of the room, empty if the room has none
+    #[must_use]
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the topic of the room, empty if the room has none
+    #[must_use]
+    pub fn as_topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Checks if the power level of the logged in user allows them to change
+    /// a setting
+    #[must_use]
+    pub fn can_edit(&self, setting: Setting) -> bool {
+        self.editable.contains(&setting)
+    }
+
+    /// Indicates whether guests can join the room
+    #[must_use]
+    pub const fn has_guest_access(&self) -> bool {
+        self.guest_access
+    }
+
+    /// Indicates whether the messages are encrypted
+    #[must_use]
+    pub const fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+}
+
+/// Loads the settings of a room
+///
+/// # Errors
+///
+/// Returns an error if the power levels or the encryption state of the room
+/// couldn't be fetched.
+pub async fn get_room_settings(
+    room: &Room,
+) -> Result<RoomSettings, matrix_sdk::Error> {
+    let power_levels = room.power_levels().await?;
+    let user_id = room.own_user_id();
+    let editable = Setting::ALL
+        .into_iter()
+        .filter(|setting| {
+            power_levels.user_can_send_state(user_id, setting.event_type())
+        })
+        .collect();
+
+    Ok(RoomSettings {
+        avatar_url: room
+            .avatar_url()
+            .map(|url| url.to_string())
+            .unwrap_or_default(),
+        editable,
+        encrypted: room.latest_encryption_state().await?.is_encrypted(),
+        guest_access: room.guest_access() == GuestAccess::CanJoin,
+        history_visibility: room
+            .history_visibility()
+            .map_or(HistoryVisibility::Shared, |visibility| {
+                HistoryVisibility::from(&visibility)
+            }),
+        join_rule: room
+            .join_rule()
+            .map_or(JoinRule::Invite, |rule| JoinRule::from(&rule)),
+        name: room.name().unwrap_or_default(),
+        topic: room.topic().unwrap_or_default(),
+    })
+}
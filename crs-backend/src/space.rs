@@ -0,0 +1,112 @@
+//! Spaces, i.e., rooms grouping other rooms ([spec][1]): the links between a
+//! space and its rooms, and the hierarchy of the rooms of a space, joined or
+//! not.
+//!
+//! [1]: <https://spec.matrix.org/latest/client-server-api/#spaces>
+
+use matrix_sdk::deserialized_responses::RawAnySyncOrStrippedState;
+use matrix_sdk::ruma::api::client::space::get_hierarchy::v1::Request as HierarchyRequest;
+use matrix_sdk::ruma::events::StateEventType;
+use matrix_sdk::ruma::{OwnedRoomId, OwnedServerName, UInt};
+use matrix_sdk::{Error, Room};
+use serde::Deserialize;
+
+use crate::directory::{PublicRoom, PublicRoomsPage};
+
+/// Number of rooms fetched per page of the hierarchy of a space
+const HIERARCHY_PAGE_SIZE: u32 = 20;
+
+/// Content of the `m.space.child` and `m.space.parent` state events
+#[derive(Deserialize)]
+struct SpaceLinkContent {
+    /// Servers to join the linked room through.
+    ///
+    /// The link was removed if there are none.
+    #[serde(default)]
+    via: Vec<OwnedServerName>,
+}
+
+/// Lists the children of a space, i.e., the rooms and the sub-spaces it
+/// contains, according to its `m.space.child` state events.
+pub async fn get_space_children(room: &Room) -> Vec<OwnedRoomId> {
+    get_space_links(room, StateEventType::SpaceChild).await
+}
+
+/// Fetches a page of the rooms of a space with the hierarchy API, including
+/// the ones the user hasn't joined.
+///
+/// Only the direct children of the space are listed. The rooms are filtered
+/// by name, alias and topic with the search, and the `from` token is the
+/// `next_batch` of another page, to paginate.
+///
+/// # Errors
+///
+/// Returns an error if the hierarchy couldn't be fetched, e.g. if the user
+/// isn't allowed to see the space.
+pub async fn get_space_hierarchy(
+    space: &Room,
+    search: &str,
+    from: Option<&str>,
+) -> Result<PublicRoomsPage, Error> {
+    let mut request = HierarchyRequest::new(space.room_id().to_owned());
+    request.from = from.map(str::to_owned);
+    request.limit = Some(UInt::from(HIERARCHY_PAGE_SIZE));
+    request.max_depth = Some(UInt::from(1_u32));
+
+    let response = space.client().send(request).await?;
+    let search = search.trim().to_lowercase();
+    Ok(PublicRoomsPage {
+        next_batch: response.next_batch,
+        prev_batch: None,
+        rooms:      response
+            .rooms
+            .into_iter()
+            .map(|chunk| chunk.summary)
+            .filter(|summary| summary.room_id != space.room_id())
+            .map(|summary| PublicRoom {
+                alias:        summary
+                    .canonical_alias
+                    .map(|alias| alias.to_string()),
+                member_count: u64::from(summary.num_joined_members),
+                name:         summary.name,
+                room_id:      summary.room_id.to_string(),
+                topic:        summary.topic,
+            })
+            .filter(|room| room.matches(&search))
+            .collect(),
+    })
+}
+
+/// Lists the ids of the rooms linked to a room by state events of the given
+/// type, whose state key is the id of the linked room.
+async fn get_space_links(
+    room: &Room,
+    event_type: StateEventType,
+) -> Vec<OwnedRoomId> {
+    let Ok(events) = room.get_state_events(event_type).await else {
+        return vec![];
+    };
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let (room_id, content) = match event {
+                RawAnySyncOrStrippedState::Sync(raw) => (
+                    raw.get_field::<OwnedRoomId>("state_key"),
+                    raw.get_field::<SpaceLinkContent>("content"),
+                ),
+                RawAnySyncOrStrippedState::Stripped(raw) => (
+                    raw.get_field::<OwnedRoomId>("state_key"),
+                    raw.get_field::<SpaceLinkContent>("content"),
+                ),
+            };
+            let room_id = room_id.ok()??;
+            (!content.ok()??.via.is_empty()).then_some(room_id)
+        })
+        .collect()
+}
+
+/// Lists the spaces a room declares itself part of, according to its
+/// `m.space.parent` state events.
+pub async fn get_space_parents(room: &Room) -> Vec<OwnedRoomId> {
+    get_space_links(room, StateEventType::SpaceParent).await
+}
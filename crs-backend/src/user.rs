@@ -5,16 +5,57 @@ use alloc::sync::Arc;
 use core::convert::Infallible;
 use core::time::Duration;
 use std::thread;
+use std::time::Instant;
 
 use matrix_sdk::config::SyncSettings;
 use matrix_sdk::event_handler::EventHandlerHandle;
-use matrix_sdk::ruma::api::client::room::create_room::v3::Request as CreateRoomRequest;
-use matrix_sdk::ruma::events::room::message::SyncRoomMessageEvent;
-use matrix_sdk::{Client, ClientBuildError, Error, Room};
+use matrix_sdk::ruma::api::client::room::Visibility;
+use matrix_sdk::ruma::api::client::room::create_room::v3::{
+    Request as CreateRoomRequest, RoomPreset
+};
+use matrix_sdk::ruma::events::InitialStateEvent;
+use matrix_sdk::ruma::events::room::encryption::RoomEncryptionEventContent;
+use matrix_sdk::ruma::events::room::message::{
+    RoomMessageEventContent, SyncRoomMessageEvent
+};
+use matrix_sdk::ruma::{OwnedUserId, UserId};
+use matrix_sdk::{Client, ClientBuildError, Error, Room, RoomState};
 use tokio::task::{JoinError, JoinHandle};
 
+use crate::bridge::provisioning::{ProvisioningClient, RemoteLogin};
+use crate::bridge::{BridgeState, KnownBridge, get_bridge_info};
+use crate::changes::watch_changes;
+use crate::contacts::{
+    Identity, MetaContact, bridge_identities, get_meta_contacts, set_meta_contacts
+};
+use crate::directory::{
+    PublicRoomsPage, parse_room_address, search_public_rooms
+};
 use crate::room::DisplayRoom;
 
+/// Delay to wait for a bridge to create the portal of a new chat
+const PORTAL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Options to create a room with
+#[derive(Default)]
+pub struct RoomOptions {
+    /// Alias of the room, either the local part (`general`) or the full
+    /// alias (`#general:server`). The alias is created on the homeserver of
+    /// the user.
+    pub alias:     Option<String>,
+    /// Whether the messages are encrypted from the creation of the room
+    pub encrypted: bool,
+    /// User ids of the users to invite in the room
+    pub invites:   Vec<String>,
+    /// Name of the room
+    pub name:      Option<String>,
+    /// Whether anyone can join the room, and find it in the room directory
+    /// of the homeserver
+    pub public:    bool,
+    /// Topic of the room
+    pub topic:     Option<String>,
+}
+
 /// Connected user to the homeserver
 pub struct User {
     /// Client to communicate with the homeserver
@@ -24,23 +65,89 @@ pub struct User {
 }
 
 impl User {
-    /// Create a new room and invite a user to this room
+    /// Lists the contacts of the user on the network of a bridge, from its
+    /// provisioning API, including the ones without a portal yet.
+    ///
+    /// # Errors
+    ///
+    /// - When the user isn't logged in
+    /// - When the bridge couldn't be reached or refused the request
+    pub async fn bridge_contacts(
+        &self,
+        bridge: &KnownBridge,
+    ) -> Result<Vec<Identity>, Error> {
+        let contacts = self
+            .provisioning(bridge.as_provisioning_url())?
+            .contacts(None)
+            .await?;
+        Ok(bridge_identities(bridge.as_protocol_id(), &contacts))
+    }
+
+    /// Opens a direct chat with a user.
+    ///
+    /// The existing direct chat with this user is reused if there is one,
+    /// according to the `m.direct` account data. Otherwise, a room is created
+    /// with the `trusted_private_chat` preset, the user is invited to it, and
+    /// the room is added to the `m.direct` account data.
     ///
     /// # Errors
     ///
+    /// - When the user id is invalid
     /// - When the room creation failed
-    /// - When the invination failed
-    pub async fn create_room_with_name(
+    pub async fn create_dm(&self, user_id: &str) -> Result<Room, Error> {
+        let user_id = UserId::parse(user_id.trim())?;
+        if let Some(room) = self.client.get_dm_room(&user_id) {
+            return Ok(room);
+        }
+        self.client.create_dm(&user_id).await
+    }
+
+    /// Create a new room with the given options, and invite the given users
+    /// to this room
+    ///
+    /// # Errors
+    ///
+    /// - When one of the user ids to invite is invalid
+    /// - When the room creation failed, e.g. if the alias is already taken
+    pub async fn create_room(
         &self,
-        name: Option<String>,
+        options: RoomOptions,
     ) -> Result<Room, Error> {
         let mut req = CreateRoomRequest::new();
-        req.name = name;
+        req.invite = options
+            .invites
+            .iter()
+            .map(|user_id| UserId::parse(user_id.trim()))
+            .collect::<Result<Vec<OwnedUserId>, _>>()?;
+        req.name = options.name;
+        req.topic = options.topic;
+        req.room_alias_name = options.alias.map(|alias| {
+            let alias = alias.trim().trim_start_matches('#');
+            alias.split_once(':').map_or(alias, |(local, _)| local).to_owned()
+        });
+        if options.public {
+            req.preset = Some(RoomPreset::PublicChat);
+            req.visibility = Visibility::Public;
+        } else {
+            req.preset = Some(RoomPreset::PrivateChat);
+            req.visibility = Visibility::Private;
+        }
+        if options.encrypted {
+            req.initial_state = vec![
+                InitialStateEvent::new(
+                    RoomEncryptionEventContent::with_recommended_defaults(),
+                )
+                .to_raw_any(),
+            ];
+        }
         self.client.create_room(req).await
     }
 
     /// Enable synchronisation with homeserver
     ///
+    /// The changes of the state of the rooms brought by the syncs are
+    /// recorded, to only fetch the changed parts when loading the rooms.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -55,6 +162,7 @@ impl User {
     /// ```
     #[must_use]
     pub fn enable_sync(&self) -> JoinHandle<Result<Infallible, Error>> {
+        watch_changes(&self.client);
         tokio::spawn({
             let client = self.client.clone();
             async move {
@@ -66,6 +174,38 @@ impl User {
         })
     }
 
+    /// Finds the portal of a bridge to the chat with a contact, given their
+    /// phone number or remote id, among the rooms joined or to which the user
+    /// was invited.
+    async fn find_portal(
+        &self,
+        bridge: &KnownBridge,
+        remote_id: &str,
+    ) -> Option<Room> {
+        for room in self.client.rooms() {
+            if room.state() != RoomState::Left
+                && let Some(info) = get_bridge_info(&room).await
+                && bridge.is_portal_of(&info, remote_id)
+            {
+                return Some(room);
+            }
+        }
+        None
+    }
+
+    /// Joins a room given its address: `#alias:server`, `!id:server` or a
+    /// `matrix.to` link.
+    ///
+    /// # Errors
+    ///
+    /// - When the address is invalid
+    /// - When the join rules of the room don't allow the user to join it
+    /// - For regular connection errors
+    pub async fn join_room(&self, address: &str) -> Result<Room, Error> {
+        let (room, via) = parse_room_address(address)?;
+        self.client.join_room_by_id_or_alias(&room, &via).await
+    }
+
     /// List all the rooms visible by the users
     ///
     /// A room is visible if the user joined, was invited or left the room.
@@ -80,6 +220,9 @@ impl User {
 
     /// Loads the rooms concurrently and give the room when ready
     ///
+    /// Only the parts of the state of the rooms that changed since they were
+    /// last loaded are fetched, cf. [`DisplayRoom::load_changes`].
+    ///
     /// # Errors
     ///
     /// Returns an error when one of the tokio tasks panicked.
@@ -99,7 +242,7 @@ impl User {
         for room in rooms {
             let callback = on_room_load.clone();
             let handle = tokio::spawn(async move {
-                callback(DisplayRoom::new(Arc::new(room)).await);
+                callback(DisplayRoom::load_changes(Arc::new(room)).await);
             });
             futures.push(handle);
         }
@@ -137,6 +280,16 @@ impl User {
         Ok(())
     }
 
+    /// Fetches the meta-contacts of the user, linking several identities of
+    /// the same person.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account data couldn't be fetched or is invalid.
+    pub async fn meta_contacts(&self) -> Result<Vec<MetaContact>, Error> {
+        get_meta_contacts(&self.client).await
+    }
+
     /// Create a new client to the homeserver
     ///
     /// # Errors
@@ -157,6 +310,167 @@ impl User {
         )
     }
 
+    /// Opens the direct chat with the bot of a bridge, on the homeserver of
+    /// the user, to log into the bridge.
+    ///
+    /// # Errors
+    ///
+    /// - When the user isn't logged in
+    /// - When the direct chat couldn't be created
+    pub async fn open_bridge_bot(
+        &self,
+        bridge: &KnownBridge,
+    ) -> Result<Room, Error> {
+        let own_user_id = self.client.user_id().ok_or_else(|| {
+            Error::UnknownError("You must be logged in".into())
+        })?;
+        let bot = bridge.bot_user_id(own_user_id.server_name())?;
+        self.create_dm(bot.as_str()).await
+    }
+
+    /// Polls the state of a bridge from its provisioning API, i.e., the state
+    /// of the logins of the user, and stores it as the latest state of the
+    /// bridge.
+    ///
+    /// The bridge is connected if all the logins are, and logged out if the
+    /// user has none.
+    ///
+    /// # Errors
+    ///
+    /// - When the user isn't logged in
+    /// - When the bridge couldn't be reached or refused the request
+    pub async fn poll_bridge_state(
+        &self,
+        bridge: &KnownBridge,
+    ) -> Result<BridgeState, Error> {
+        let whoami =
+            self.provisioning(bridge.as_provisioning_url())?.whoami().await?;
+        let logins = whoami.as_logins();
+        let state = if logins.is_empty() {
+            BridgeState::LoggedOut
+        } else {
+            logins
+                .iter()
+                .map(RemoteLogin::state)
+                .find(|state| *state != BridgeState::Connected)
+                .unwrap_or(BridgeState::Connected)
+        };
+        bridge.set_polled_state(state.clone());
+        Ok(state)
+    }
+
+    /// Creates a client for the provisioning API of the bridge at the given
+    /// address, authenticated with the access token of the user.
+    ///
+    /// # Errors
+    ///
+    /// - When the user isn't logged in
+    /// - When the address isn't a valid base URL
+    pub fn provisioning(
+        &self,
+        base_url: &str,
+    ) -> Result<ProvisioningClient, Error> {
+        let (Some(user_id), Some(access_token)) =
+            (self.client.user_id(), self.client.access_token())
+        else {
+            return Err(Error::UnknownError("You must be logged in".into()));
+        };
+        ProvisioningClient::new(base_url, user_id.as_str(), &access_token)
+    }
+
+    /// Searches the public room directory of the homeserver.
+    ///
+    /// The `since` token is the `next_batch` or `prev_batch` of another page,
+    /// to paginate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory couldn't be fetched.
+    pub async fn public_rooms(
+        &self,
+        search: &str,
+        since: Option<&str>,
+    ) -> Result<PublicRoomsPage, Error> {
+        search_public_rooms(&self.client, search, since).await
+    }
+
+    /// Saves the meta-contacts of the user in their account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account data couldn't be saved.
+    pub async fn save_meta_contacts(
+        &self,
+        meta_contacts: &[MetaContact],
+    ) -> Result<(), Error> {
+        set_meta_contacts(&self.client, meta_contacts).await
+    }
+
+    /// Starts a chat with a contact on the network of a bridge, given their
+    /// phone number or remote id.
+    ///
+    /// The existing portal is returned if there is one, and its invitation is
+    /// accepted. Otherwise, the start chat command is sent to the bot of the
+    /// bridge and `None` is returned straight away: the portal is created
+    /// later by the bridge, and can be awaited with
+    /// [`Self::wait_for_portal`].
+    ///
+    /// # Errors
+    ///
+    /// - When the invitation to the portal couldn't be accepted
+    /// - When the direct chat with the bot couldn't be opened
+    /// - When the command couldn't be sent
+    pub async fn start_bridged_chat(
+        &self,
+        bridge: &KnownBridge,
+        remote_id: &str,
+    ) -> Result<Option<Room>, Error> {
+        if let Some(portal) = self.find_portal(bridge, remote_id).await {
+            if portal.state() == RoomState::Invited {
+                portal.join().await?;
+            }
+            return Ok(Some(portal));
+        }
+        let bot_room = self.open_bridge_bot(bridge).await?;
+        let command = bridge.start_chat_command(remote_id);
+        bot_room.send(RoomMessageEventContent::text_plain(command)).await?;
+        Ok(None)
+    }
+
+    /// Waits for the bridge to create the portal to the chat with a contact,
+    /// after [`Self::start_bridged_chat`], for at most [`PORTAL_TIMEOUT`],
+    /// and accepts its invitation.
+    ///
+    /// # Errors
+    ///
+    /// - When the portal didn't appear in time, e.g. if the contact doesn't
+    ///   exist on the network
+    /// - When the invitation to the portal couldn't be accepted
+    pub async fn wait_for_portal(
+        &self,
+        bridge: &KnownBridge,
+        remote_id: &str,
+    ) -> Result<Room, Error> {
+        let deadline = Instant::now().checked_add(PORTAL_TIMEOUT);
+        while deadline.is_some_and(|deadline| Instant::now() < deadline) {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if let Some(portal) = self.find_portal(bridge, remote_id).await {
+                if portal.state() == RoomState::Invited {
+                    portal.join().await?;
+                }
+                return Ok(portal);
+            }
+        }
+        Err(Error::UnknownError(
+            format!(
+                "The {} bridge didn't open a chat with {remote_id}, see the \
+                 answer of its bot",
+                bridge.as_name()
+            )
+            .into(),
+        ))
+    }
+
     /// Wait until the client can see a room.
     ///
     /// A room is visible if the user joined, was invited or left the room.
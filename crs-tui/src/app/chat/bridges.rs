@@ -0,0 +1,252 @@
+//! Bridges the user is logged into, shared by the room list and the chat
+//! panel: their state, and the exchanges with their bot.
+//!
+//! A bridge is configured if the user has a direct chat with its bot. Its
+//! state is polled from its provisioning API at startup and periodically, so
+//! that it is known even when no messages are bridged, without sending
+//! messages to its bot.
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::time::Duration;
+use std::sync::Mutex;
+
+use crs_backend::bridge::{BridgeState, KNOWN_BRIDGES, KnownBridge};
+use crs_backend::contacts::Identity;
+use crs_backend::message::QrCode;
+use crs_backend::room::DisplayRoom;
+use crs_backend::user::User;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Wrap};
+
+use crate::ui::qr::qr_lines;
+use crate::utils::safe_unlock;
+
+/// Time to wait for a bridge to list the contacts of the user
+const CONTACTS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Markers displayed before the rooms and the contacts bridged from the most
+/// common networks, by protocol id as advertised by their bridge, with their
+/// colour.
+///
+/// The rooms and contacts of the other networks are marked with the first
+/// letters of the protocol id.
+const NETWORK_MARKERS: [(&str, &str, Color); 9] = [
+    ("discord", "DC", Color::Magenta),
+    ("facebook", "FB", Color::LightBlue),
+    ("gmessages", "SM", Color::LightGreen),
+    ("instagram", "IG", Color::LightMagenta),
+    ("irc", "IR", Color::Gray),
+    ("signal", "SG", Color::Blue),
+    ("slack", "SL", Color::LightRed),
+    ("telegram", "TG", Color::Cyan),
+    ("whatsapp", "WA", Color::Green),
+];
+
+/// Marker displayed before the state of a bridge
+pub const STATE_MARKER: char = '\u{25cf}';
+
+/// Delay between two polls of the state of the bridges
+const POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Time to wait for a bridge to answer with its state
+const POLL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Lists the contacts of the user on the networks of the configured bridges,
+/// from their provisioning API, waiting at most [`CONTACTS_TIMEOUT`] for each
+/// bridge.
+///
+/// Returns the contacts, and the errors of the bridges whose contacts
+/// couldn't be listed.
+pub async fn bridge_contacts(
+    user: &User,
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+) -> (Vec<Identity>, Vec<String>) {
+    let mut contacts = vec![];
+    let mut errors = vec![];
+    for bridge in &KNOWN_BRIDGES {
+        if find_bot_room(rooms, bridge).is_none() {
+            continue;
+        }
+        let name = bridge.as_name();
+        let listed = tokio::time::timeout(
+            CONTACTS_TIMEOUT,
+            user.bridge_contacts(bridge),
+        )
+        .await;
+        match listed {
+            Ok(Ok(identities)) => contacts.extend(identities),
+            Ok(Err(err)) => errors.push(format!("{name}: {err}")),
+            Err(_) => errors.push(format!("{name}: no answer")),
+        }
+    }
+    (contacts, errors)
+}
+
+/// Lists the known bridges, with their state if the user has a direct chat
+/// with their bot, and `None` otherwise.
+pub fn bridge_states(
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+) -> Vec<(&'static KnownBridge, Option<BridgeState>)> {
+    KNOWN_BRIDGES
+        .iter()
+        .map(|bridge| {
+            let state = find_bot_room(rooms, bridge)
+                .map(|room| bridge.health(&safe_unlock(&room)));
+            (bridge, state)
+        })
+        .collect()
+}
+
+/// Returns the first bridge the user is logged into, or the first known
+/// bridge if there are none
+pub fn default_bridge(
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+) -> &'static KnownBridge {
+    KNOWN_BRIDGES
+        .iter()
+        .find(|bridge| find_bot_room(rooms, bridge).is_some())
+        .unwrap_or(&KNOWN_BRIDGES[0])
+}
+
+/// Draws the messages exchanged with a bot, keeping the last ones visible.
+///
+/// The QR codes are rendered to be scanned, the expired ones being replaced
+/// by a note.
+pub fn draw_transcript(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    messages: Vec<(String, String, Option<QrCode>)>,
+) {
+    let sender_style = Style::new().add_modifier(Modifier::BOLD);
+    let lines = messages
+        .into_iter()
+        .flat_map(|(sender, body, qr_code)| {
+            let mut lines = vec![Line::styled(sender, sender_style)];
+            match qr_code {
+                Some(qr_code) => lines.extend(qr_lines(&qr_code)),
+                None => lines.extend(
+                    body.lines().map(|line| Line::from(line.to_owned())),
+                ),
+            }
+            lines
+        })
+        .collect::<Vec<_>>();
+    let hidden = lines.len().saturating_sub(usize::from(area.height));
+    frame.render_widget(
+        Paragraph::new(lines.into_iter().skip(hidden).collect::<Vec<_>>())
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Finds the direct chat with the bot of a bridge, if the user has one
+pub fn find_bot_room(
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+    bridge: &KnownBridge,
+) -> Option<Arc<Mutex<DisplayRoom>>> {
+    safe_unlock(rooms)
+        .iter()
+        .find(|room| bridge.is_bot_room(&safe_unlock(room)))
+        .cloned()
+}
+
+/// Returns the sender, the body and the QR code of the messages of a room,
+/// from the event with the given id on, e.g. since a command was sent to a
+/// bot.
+///
+/// Nothing is returned until the event is received in the timeline.
+pub fn messages_since(
+    room: &Mutex<DisplayRoom>,
+    event_id: &str,
+) -> Vec<(String, String, Option<QrCode>)> {
+    safe_unlock(room).as_messages().map_or_else(
+        |_| vec![],
+        |messages| {
+            messages
+                .iter()
+                .skip_while(|message| message.as_event_id() != Some(event_id))
+                .map(|message| {
+                    (
+                        message.as_sender().to_owned(),
+                        message.as_body().to_owned(),
+                        message.qr_code().cloned(),
+                    )
+                })
+                .collect()
+        },
+    )
+}
+
+/// Returns the marker of a network, with its colour, from its protocol id
+pub fn network_marker(protocol_id: &str) -> (String, Color) {
+    NETWORK_MARKERS
+        .iter()
+        .find(|(id, ..)| protocol_id.eq_ignore_ascii_case(id))
+        .map_or_else(
+            || {
+                let marker = protocol_id.chars().take(2).collect::<String>();
+                (marker.to_uppercase(), Color::Gray)
+            },
+            |(_, marker, colour)| ((*marker).to_owned(), *colour),
+        )
+}
+
+/// Sends the ping command to the bots of all the configured bridges, for
+/// them to answer with the state of their bridge.
+///
+/// # Errors
+///
+/// Returns the errors of the bridges whose bot couldn't be pinged.
+pub async fn ping_bridges(
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+) -> Result<(), String> {
+    let mut errors = vec![];
+    for bridge in &KNOWN_BRIDGES {
+        let Some(room) = find_bot_room(rooms, bridge) else { continue };
+        let room_handle = safe_unlock(&room).as_room();
+        if let Err(err) = room_handle.send_plain(bridge.as_ping_command()).await
+        {
+            errors.push(format!("{}: {err}", bridge.as_name()));
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
+
+/// Polls the state of the known bridges in the background from their
+/// provisioning API, once at startup and then every [`POLL_INTERVAL`],
+/// waiting at most [`POLL_TIMEOUT`] for each bridge.
+///
+/// All the known bridges are polled, as the direct chats with their bots may
+/// not be loaded yet at startup. The errors are ignored, the state of the
+/// bridges being then found from their reports and from the answers of their
+/// bot.
+pub fn poll_bridges_periodically(user: Arc<User>) {
+    let _handle = tokio::spawn(async move {
+        loop {
+            for bridge in &KNOWN_BRIDGES {
+                let _polled: Result<Result<BridgeState, _>, _> =
+                    tokio::time::timeout(
+                        POLL_TIMEOUT,
+                        user.poll_bridge_state(bridge),
+                    )
+                    .await;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Returns the label describing the state of a bridge, with its colour
+pub const fn state_label(state: &BridgeState) -> (&'static str, Color) {
+    match state {
+        BridgeState::Connected => ("connected", Color::Green),
+        BridgeState::Error(_) => ("error", Color::Red),
+        BridgeState::LoggedOut => ("logged out", Color::Red),
+        BridgeState::TransientDisconnect => ("reconnecting", Color::Yellow),
+        BridgeState::Unknown => ("unknown", Color::DarkGray),
+    }
+}
@@ -0,0 +1,79 @@
+//! Read-only view of a room that was left, with its last messages
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::widgets::{Paragraph, Wrap};
+
+use crate::app::chat::current_room::discussion::Discussion;
+use crate::ui::component::Component;
+use crate::ui::widgets::InstructionsBuilder;
+use crate::utils::safe_unlock;
+
+/// Room that was left, displayed without the message prompt
+///
+/// An event returns `Some(())` when the user asks to rejoin the room. This is
+/// only possible if the join rules of the room allow it.
+pub struct ArchivedRoom {
+    /// Whether the user is allowed to join the room again
+    can_rejoin: bool,
+    /// Discussion used to display the messages
+    discussion: Discussion,
+}
+
+impl ArchivedRoom {
+    /// Returns the underlying [`DisplayRoom`]
+    pub fn into_room(self) -> Arc<Mutex<DisplayRoom>> {
+        self.discussion.into_room()
+    }
+
+    /// Opens the archive of the given room
+    pub fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
+        let can_rejoin = safe_unlock(&room).can_rejoin();
+        Self { can_rejoin, discussion: Discussion::new(room) }
+    }
+}
+
+impl Component for ArchivedRoom {
+    type ResponseData = Infallible;
+    type UpdateState = ();
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+        self.discussion.draw_messages(frame, layout[0]);
+
+        let builder =
+            InstructionsBuilder::default().text("You left this room.");
+        let instructions = if self.can_rejoin {
+            builder.text("Press").key("Enter").text("to rejoin it, or")
+        } else {
+            builder.text("Its history is read-only. Press")
+        }
+        .key("C-x")
+        .text("to forget it.")
+        .build();
+
+        frame.render_widget(
+            Paragraph::new(instructions.line)
+                .wrap(Wrap { trim: true })
+                .centered(),
+            layout[1],
+        );
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        (self.can_rejoin && event.as_key_press_event()?.code.is_enter())
+            .then_some(())
+    }
+}
@@ -0,0 +1,257 @@
+//! Command palette to manage a bridge, by sending text commands to its bot
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use core::ptr;
+use std::sync::Mutex;
+
+use crs_backend::bridge::{BridgeCommand, KNOWN_BRIDGES, KnownBridge};
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::app::chat::bridges::{
+    default_bridge, draw_transcript, find_bot_room, messages_since
+};
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Status;
+use crate::ui::prompt::candidates::Candidates;
+use crate::ui::widgets::{
+    InstructionsBuilder, fully_centred_content, saturating_cast
+};
+use crate::utils::safe_unlock;
+
+/// Maximum number of commands proposed at once
+const MAX_CANDIDATES: u16 = 6;
+
+/// Palette with the commands understood by the bot of a bridge.
+///
+/// The commands are proposed while typing their name, with a hint of their
+/// arguments, and are sent to the direct chat with the bot. The answers of
+/// the bot are displayed above the palette.
+pub struct BridgeCommands {
+    /// Bridge whose bot receives the commands
+    bridge:        &'static KnownBridge,
+    /// Commands of the bot matching the name being typed
+    candidates:    Candidates<&'static BridgeCommand>,
+    /// Command line being typed
+    command:       Input<'static>,
+    /// Event id of the last command sent to the bot, once a command was sent
+    command_event: Option<String>,
+    /// Direct chat with the bot, if the user has one
+    room:          Option<Arc<Mutex<DisplayRoom>>>,
+    /// Rooms of the user, to find the direct chats with the bots
+    rooms:         Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Status of the last command
+    status:        Status,
+}
+
+impl BridgeCommands {
+    /// Completes the command line with the selected command, while its
+    /// name is being typed.
+    ///
+    /// Returns `false` if no command is selected, if it was already typed, or
+    /// if the arguments are being typed.
+    fn complete(&mut self) -> bool {
+        let Some(selected) = self.candidates.selected().copied() else {
+            return false;
+        };
+        let is_typed =
+            self.typed_command().is_some_and(|typed| ptr::eq(typed, selected));
+        if is_typed || self.command.as_value().contains(char::is_whitespace) {
+            return false;
+        }
+        self.command.set_value(format!("{} ", selected.as_name()));
+        self.refresh_candidates();
+        true
+    }
+
+    /// Opens the palette for the first bridge the user is logged into, or
+    /// the first known bridge if there are none.
+    pub fn new(rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>) -> Self {
+        let bridge = default_bridge(&rooms);
+        let mut this = Self {
+            bridge,
+            candidates: Candidates::new(vec![]),
+            command: Input::new().with_active().with_title(" Command "),
+            command_event: None,
+            room: None,
+            rooms,
+            status: Status::None,
+        };
+        this.select_bridge(bridge);
+        this
+    }
+
+    /// Proposes the commands matching the name being typed
+    fn refresh_candidates(&mut self) {
+        let name =
+            self.command.as_value().split_whitespace().next().unwrap_or("");
+        self.candidates.update_matching(name);
+    }
+
+    /// Switches to another bridge, and finds the direct chat with its bot
+    fn select_bridge(&mut self, bridge: &'static KnownBridge) {
+        self.bridge = bridge;
+        self.room = find_bot_room(&self.rooms, bridge);
+        self.candidates =
+            Candidates::new(bridge.as_commands().iter().collect());
+        self.command_event = None;
+        self.command.take_value();
+        self.refresh_candidates();
+    }
+
+    /// Switches to the next bridge the user is logged into, going back to
+    /// the first one after the last one
+    fn select_next_bridge(&mut self) {
+        let configured = KNOWN_BRIDGES
+            .iter()
+            .filter(|bridge| find_bot_room(&self.rooms, bridge).is_some())
+            .collect::<Vec<_>>();
+        let position = configured
+            .iter()
+            .position(|bridge| ptr::eq(*bridge, self.bridge))
+            .unwrap_or(0);
+        if let Some(next) =
+            configured.iter().cycle().nth(position.saturating_add(1))
+        {
+            self.select_bridge(next);
+        }
+    }
+
+    /// Sends the command line to the bot, and starts following its answers
+    async fn send_command(&mut self) {
+        let Some(room) = &self.room else { return };
+        if self.command.is_empty() {
+            return;
+        }
+        let room_handle = safe_unlock(room).as_room();
+        match room_handle.send_plain(self.command.as_value().trim()).await {
+            Ok(event_id) => {
+                self.command_event = Some(event_id);
+                self.command.take_value();
+                self.refresh_candidates();
+                self.status = Status::None;
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Returns the command whose name was typed, if any
+    fn typed_command(&self) -> Option<&'static BridgeCommand> {
+        let name = self.command.as_value().split_whitespace().next()?;
+        self.bridge
+            .as_commands()
+            .iter()
+            .find(|command| command.as_name() == name)
+    }
+}
+
+impl Component for BridgeCommands {
+    type ResponseData = Infallible;
+    type UpdateState = Infallible;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let instructions = InstructionsBuilder::default()
+            .text(" Select")
+            .key("Tab")
+            .text("Complete or send")
+            .key("Enter")
+            .text("Next bridge")
+            .key("Down")
+            .build();
+        let block = Block::bordered()
+            .title(format!(" Commands of the {} bot ", self.bridge.as_name()))
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(instructions.line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let Some(room) = &self.room else {
+            let message = format!(
+                "You aren't logged into {}. Press C-b to connect a bridge.",
+                self.bridge.as_name()
+            );
+            let rect = fully_centred_content(
+                saturating_cast(message.len()),
+                inner.width,
+                inner,
+            );
+            frame.render_widget(
+                Paragraph::new(message).wrap(Wrap { trim: true }).centered(),
+                rect,
+            );
+            return;
+        };
+
+        let candidates_height =
+            if self.command.as_value().contains(char::is_whitespace) {
+                0
+            } else {
+                saturating_cast(self.candidates.nb_matching())
+                    .clamp(1, MAX_CANDIDATES)
+            };
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(candidates_height),
+            Constraint::Length(1),
+            Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
+        ])
+        .split(inner);
+
+        if let Some(event_id) = &self.command_event {
+            draw_transcript(frame, layout[0], messages_since(room, event_id));
+        }
+        if candidates_height != 0 {
+            self.candidates.draw(frame, layout[1]);
+        }
+
+        let hint = if let Some((message, colour)) = self.status.as_content() {
+            Line::styled(message.to_owned(), Style::new().fg(colour))
+        } else if let Some(command) = self.typed_command() {
+            let arguments = match command.as_arguments() {
+                "" => "(no arguments)",
+                arguments => arguments,
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", command.as_name()),
+                    Style::new().fg(Color::Green),
+                ),
+                Span::styled(arguments, Style::new().fg(Color::DarkGray)),
+            ])
+        } else {
+            Line::default()
+        };
+        frame.render_widget(hint, layout[2]);
+
+        self.command.draw(frame, layout[3]);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        let key_event = event.as_key_press_event()?;
+        match key_event.code {
+            KeyCode::Down => self.select_next_bridge(),
+            _ if self.room.is_none() => (),
+            KeyCode::Tab | KeyCode::BackTab => {
+                let _: Infallible = self.candidates.on_event(event).await?;
+            }
+            KeyCode::Enter =>
+                if !self.complete() {
+                    self.send_command().await;
+                },
+            _ => {
+                self.command.on_event(event).await;
+                self.refresh_candidates();
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,121 @@
+//! Health panel with the state of the connection of each bridge to its
+//! network
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::bridge::BridgeState;
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::app::chat::bridges::{
+    STATE_MARKER, bridge_states, ping_bridges, state_label
+};
+use crate::ui::component::Component;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::InstructionsBuilder;
+
+/// Panel listing the known bridges with their state.
+///
+/// The state is refreshed with the rooms and with the periodic polls of the
+/// bridges, and the bots can be pinged at once for a fresh answer.
+pub struct BridgeHealth {
+    /// Rooms of the user, to find the direct chats with the bots
+    rooms:  Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Status of the last ping
+    status: Status,
+}
+
+impl BridgeHealth {
+    /// Opens the panel, with the rooms in which the bots are searched
+    pub const fn new(rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>) -> Self {
+        Self { rooms, status: Status::None }
+    }
+}
+
+impl Component for BridgeHealth {
+    type ResponseData = Infallible;
+    type UpdateState = Infallible;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let instructions = InstructionsBuilder::default()
+            .text(" Ping the bots")
+            .key("Enter")
+            .text("Connect a bridge")
+            .key("C-b")
+            .build();
+        let block = Block::bordered()
+            .title(" Bridges ")
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(instructions.line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+        let name_style = Style::new().add_modifier(Modifier::BOLD);
+        let mut lines = vec![];
+        for (bridge, state) in bridge_states(&self.rooms) {
+            let mut line = vec![Span::styled(
+                format!("{} ", bridge.as_name()),
+                name_style,
+            )];
+            match &state {
+                Some(state) => {
+                    let (label, colour) = state_label(state);
+                    line.push(Span::styled(
+                        format!("{STATE_MARKER} {label}"),
+                        Style::new().fg(colour),
+                    ));
+                }
+                None => line.push(Span::styled(
+                    "not connected",
+                    Style::new().fg(Color::DarkGray),
+                )),
+            }
+            lines.push(Line::from(line));
+            if let Some(BridgeState::Error(message)) = state {
+                lines.push(Line::styled(
+                    format!("  {message}"),
+                    Style::new().fg(Color::Red),
+                ));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }),
+            layout[0],
+        );
+
+        if let Some((message, colour)) = self.status.as_content() {
+            frame.render_widget(
+                Paragraph::new(message)
+                    .style(Style::new().fg(colour))
+                    .wrap(Wrap { trim: true })
+                    .centered(),
+                layout[1],
+            );
+        }
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        if event.as_key_press_event()?.code.is_enter() {
+            self.status = match ping_bridges(&self.rooms).await {
+                Ok(()) => Status::None,
+                Err(err) => Status::Error(err),
+            };
+        }
+        None
+    }
+}
@@ -0,0 +1,317 @@
+//! Wizard to log into a bridge, by talking to its bot in a direct chat
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::ptr;
+use std::sync::Mutex;
+
+use crs_backend::bridge::{KNOWN_BRIDGES, KnownBridge, LoginProgress};
+use crs_backend::member::Membership;
+use crs_backend::message::QrCode;
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Text;
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Wrap};
+
+use crate::app::chat::bridges::{draw_transcript, messages_since};
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::{InstructionsBuilder, saturating_cast};
+use crate::utils::safe_unlock;
+
+/// Request of the wizard to the chat panel
+pub enum BridgeLoginRequest {
+    /// Close the wizard and open the direct chat with the bot
+    Close(Arc<Mutex<DisplayRoom>>),
+    /// Open the direct chat with the bot of the bridge, then give it to the
+    /// wizard with [`Component::update`].
+    Connect(&'static KnownBridge),
+}
+
+/// Wizard to log into a bridge.
+///
+/// The user picks a bridge, the direct chat with its bot is opened, then the
+/// login command is sent to the bot. The answers of the bot are displayed,
+/// and the user can reply to its questions, until the bot reports that the
+/// login succeeded.
+pub struct BridgeLogin {
+    /// Bridge selected by the user
+    bridge:        &'static KnownBridge,
+    /// Event id of the login command, once it was sent
+    command_event: Option<String>,
+    /// Input to answer the questions of the bot
+    reply:         Input<'static>,
+    /// Direct chat with the bot, once it is opened
+    room:          Option<Arc<Mutex<DisplayRoom>>>,
+    /// Status of the last request
+    status:        Status,
+}
+
+impl BridgeLogin {
+    /// Returns the sender, the body and the QR code of the messages sent in
+    /// the chat with the bot since the login command, the command included
+    fn answers(&self) -> Vec<(String, String, Option<QrCode>)> {
+        let (Some(room), Some(event_id)) = (&self.room, &self.command_event)
+        else {
+            return vec![];
+        };
+        messages_since(room, event_id)
+    }
+
+    /// Checks if the bot joined the direct chat, and can thus receive the
+    /// commands
+    fn bot_joined(&self, room: &DisplayRoom) -> bool {
+        let server_name = room.own_user_id().server_name();
+        room.members().and_then(Result::ok).is_some_and(|members| {
+            members.iter().any(|member| {
+                self.bridge.is_bot(member.as_user_id(), server_name)
+                    && member.membership() == Membership::Joined
+            })
+        })
+    }
+
+    /// Draws the list of the bridges the user can log into
+    fn draw_bridges(&self, frame: &mut Frame<'_>, area: Rect) {
+        let items = KNOWN_BRIDGES.iter().map(|bridge| {
+            if ptr::eq(bridge, self.bridge) {
+                ListItem::new(format!(">{}", bridge.as_name())).style(
+                    Style::new().fg(Color::Green).add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ListItem::new(format!(" {}", bridge.as_name()))
+            }
+        });
+        frame.render_widget(List::new(items), area);
+    }
+
+    /// Creates the wizard, with the first known bridge selected
+    pub fn new() -> Self {
+        Self {
+            bridge:        &KNOWN_BRIDGES[0],
+            command_event: None,
+            reply:         Input::new()
+                .with_active()
+                .with_title(" Answer to the bot "),
+            room:          None,
+            status:        Status::None,
+        }
+    }
+
+    /// Selects the next known bridge, going back to the first one after the
+    /// last one
+    fn select_next(&mut self) {
+        let position = KNOWN_BRIDGES
+            .iter()
+            .position(|bridge| ptr::eq(bridge, self.bridge))
+            .unwrap_or(0);
+        self.bridge = KNOWN_BRIDGES
+            .iter()
+            .cycle()
+            .nth(position.saturating_add(1))
+            .unwrap_or(self.bridge);
+    }
+
+    /// Sends the login command to the bot, and starts following its answers
+    async fn send_command(&mut self) {
+        let Some(room) = &self.room else { return };
+        let room_handle = safe_unlock(room).as_room();
+        match room_handle.send_plain(self.bridge.as_login_command()).await {
+            Ok(event_id) => {
+                self.command_event = Some(event_id);
+                self.status = Status::None;
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Sends the answer typed by the user to the bot
+    async fn send_reply(&mut self) {
+        let Some(room) = &self.room else { return };
+        if self.reply.is_empty() {
+            return;
+        }
+        let room_handle = safe_unlock(room).as_room();
+        match room_handle.send_plain(self.reply.as_value().trim()).await {
+            Ok(_) => {
+                self.reply.take_value();
+                self.status = Status::None;
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Returns the current step of the login
+    fn step(&self) -> Step {
+        let Some(room) = &self.room else { return Step::PickBridge };
+        if self.command_event.is_some() {
+            let answers = self.answers();
+            Step::Answering(self.bridge.login_progress(
+                answers.iter().map(|(_, body, _)| body.as_str()),
+            ))
+        } else if self.bot_joined(&safe_unlock(room)) {
+            Step::ReadyToSend
+        } else {
+            Step::WaitingForBot
+        }
+    }
+}
+
+impl Component for BridgeLogin {
+    type ResponseData = Arc<Mutex<DisplayRoom>>;
+    type UpdateState = BridgeLoginRequest;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let step = self.step();
+        let name = self.bridge.as_name();
+        let instructions = match step {
+            Step::PickBridge => InstructionsBuilder::default()
+                .text(" Select")
+                .key("Tab")
+                .text("Open the chat with its bot")
+                .key("Enter"),
+            Step::WaitingForBot => InstructionsBuilder::default()
+                .text(" Waiting for the bot to join the chat... "),
+            Step::ReadyToSend | Step::Answering(LoginProgress::Failed) =>
+                InstructionsBuilder::default()
+                    .text(" Send")
+                    .key(self.bridge.as_login_command())
+                    .text("to the bot")
+                    .key("Enter"),
+            Step::Answering(LoginProgress::InProgress) =>
+                InstructionsBuilder::default()
+                    .text(" Answer the bot")
+                    .key("Enter"),
+            Step::Answering(LoginProgress::LoggedIn) =>
+                InstructionsBuilder::default()
+                    .text(" Open the chat with the bot")
+                    .key("Enter"),
+        }
+        .build();
+
+        let block = Block::bordered()
+            .title(" Connect a bridge ")
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(instructions.line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let reply_height = if step == Step::Answering(LoginProgress::InProgress)
+        {
+            Input::HEIGHT_WITHOUT_LABEL
+        } else {
+            0
+        };
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Length(reply_height),
+        ])
+        .split(inner);
+
+        let (message, colour) = match step {
+            Step::PickBridge => {
+                self.draw_bridges(frame, layout[0]);
+                ("Choose the network to connect to.".to_owned(), Color::Gray)
+            }
+            Step::WaitingForBot => (
+                format!(
+                    "The chat with the bot of {name} is open. It will be \
+                     ready once the bot joins it."
+                ),
+                Color::Gray,
+            ),
+            Step::ReadyToSend => (
+                format!("The bot of {name} is ready to log you in."),
+                Color::Gray,
+            ),
+            Step::Answering(progress) => {
+                draw_transcript(frame, layout[0], self.answers());
+                match progress {
+                    LoginProgress::Failed => (
+                        "The login failed, you can try again.".to_owned(),
+                        Color::Red,
+                    ),
+                    LoginProgress::InProgress => (
+                        "Follow the instructions of the bot.".to_owned(),
+                        Color::Gray,
+                    ),
+                    LoginProgress::LoggedIn =>
+                        (format!("You are logged into {name}!"), Color::Green),
+                }
+            }
+        };
+        let (message, colour) = self
+            .status
+            .as_content()
+            .map_or((message, colour), |(content, status_colour)| {
+                (content.to_owned(), status_colour)
+            });
+        let message_height =
+            saturating_cast(message.len()).div_ceil(inner.width.max(1));
+        let message_area =
+            Rect { height: message_height.min(layout[1].height), ..layout[1] };
+        frame.render_widget(
+            Paragraph::new(Text::from(message).style(Style::new().fg(colour)))
+                .wrap(Wrap { trim: true })
+                .centered(),
+            message_area,
+        );
+
+        if reply_height != 0 {
+            self.reply.draw(frame, layout[2]);
+        }
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        if matches!(self.status, Status::Submitting) {
+            return None;
+        }
+        let code = event.as_key_press_event()?.code;
+        match (self.step(), code) {
+            (Step::PickBridge, KeyCode::Tab) => self.select_next(),
+            (Step::PickBridge, KeyCode::Enter) => {
+                self.status = Status::Submitting;
+                return Some(BridgeLoginRequest::Connect(self.bridge));
+            }
+            (
+                Step::ReadyToSend | Step::Answering(LoginProgress::Failed),
+                KeyCode::Enter,
+            ) => self.send_command().await,
+            (Step::Answering(LoginProgress::LoggedIn), KeyCode::Enter) =>
+                return self
+                    .room
+                    .as_ref()
+                    .map(|room| BridgeLoginRequest::Close(Arc::clone(room))),
+            (Step::Answering(LoginProgress::InProgress), KeyCode::Enter) =>
+                self.send_reply().await,
+            (Step::Answering(LoginProgress::InProgress), _) => {
+                self.reply.on_event(event).await;
+            }
+            _ => (),
+        }
+        None
+    }
+
+    fn update(&mut self, response_data: Self::ResponseData) {
+        self.room = Some(response_data);
+        self.status = Status::None;
+    }
+}
+
+/// Step of the login, computed from the direct chat with the bot
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// The login command was sent, and the bot is answering
+    Answering(LoginProgress),
+    /// The user is choosing the bridge to log into
+    PickBridge,
+    /// The bot joined the direct chat, the login command can be sent
+    ReadyToSend,
+    /// The direct chat was opened, but the bot didn't join it yet
+    WaitingForBot,
+}
@@ -0,0 +1,129 @@
+//! Moderation commands, typed in the message prompt (e.g. `/ban @a:b spam`)
+//! or chosen from the profile of a member.
+//!
+//! Only the names of the known commands are intercepted, the other messages
+//! starting with `/` are sent as they are. A message starting with `//` is
+//! sent with a single `/`, e.g. `//ban` sends `/ban`.
+
+use crs_backend::room::RoomWrap;
+
+/// Moderation action on a member of a room
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Bans the member from the room, with an optional reason
+    Ban,
+    /// Kicks the member out of the room, with an optional reason
+    Kick,
+    /// Changes the power level of the member
+    Op,
+    /// Lifts the ban of the member, with an optional reason
+    Unban,
+}
+
+impl Action {
+    /// Actions in the order they are proposed in the profile of a member
+    pub const ALL: [Self; 4] = [Self::Kick, Self::Ban, Self::Unban, Self::Op];
+
+    /// Returns the description of the argument of the action
+    pub const fn argument_hint(self) -> &'static str {
+        match self {
+            Self::Ban | Self::Kick | Self::Unban => "Reason (optional)",
+            Self::Op => "Power level",
+        }
+    }
+
+    /// Returns the name of the action, as typed after the `/`
+    pub const fn as_name(self) -> &'static str {
+        match self {
+            Self::Ban => "ban",
+            Self::Kick => "kick",
+            Self::Op => "op",
+            Self::Unban => "unban",
+        }
+    }
+
+    /// Finds the action with the given name
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_name() == name)
+    }
+}
+
+/// Moderation command, with its target and its argument
+pub struct Command {
+    /// Action to do on the member
+    action:   Action,
+    /// Argument of the action: the reason, or the power level for
+    /// [`Action::Op`]
+    argument: Option<String>,
+    /// User id of the targeted member
+    user_id:  String,
+}
+
+impl Command {
+    /// Creates a command on a member, with the argument typed by the user
+    pub fn new(action: Action, user_id: String, argument: &str) -> Self {
+        let argument = argument.trim();
+        Self {
+            action,
+            argument: (!argument.is_empty()).then(|| argument.to_owned()),
+            user_id,
+        }
+    }
+
+    /// Parses a message typed in the message prompt.
+    ///
+    /// # Returns
+    ///
+    /// - `None` if the message isn't a command, i.e., if it doesn't start with
+    ///   `/` followed by the name of a known command
+    /// - An error message if the user id is missing
+    pub fn parse(message: &str) -> Option<Result<Self, String>> {
+        let command = message.trim().strip_prefix('/')?;
+        let (name, arguments) =
+            command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let action = Action::from_name(name)?;
+        let arguments = arguments.trim_start();
+        let (user_id, argument) = arguments
+            .split_once(char::is_whitespace)
+            .unwrap_or((arguments, ""));
+        if user_id.is_empty() {
+            return Some(Err(format!(
+                "Usage: /{name} <user id> <{}>",
+                action.argument_hint().to_lowercase()
+            )));
+        }
+        Some(Ok(Self::new(action, user_id.to_owned(), argument)))
+    }
+
+    /// Sends the command to the room
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the error if the power level is invalid, if the
+    /// logged in user isn't allowed to do this action, or for connection
+    /// errors.
+    pub async fn run(&self, room: &RoomWrap) -> Result<(), String> {
+        let reason = self.argument.as_deref();
+        let result = match self.action {
+            Action::Ban => room.ban_user(&self.user_id, reason).await,
+            Action::Kick => room.kick_user(&self.user_id, reason).await,
+            Action::Op => {
+                let level = reason
+                    .and_then(|level| level.parse().ok())
+                    .ok_or_else(|| "Invalid power level".to_owned())?;
+                room.set_power_level(&self.user_id, level).await
+            }
+            Action::Unban => room.unban_user(&self.user_id, reason).await,
+        };
+        result.map_err(|err| err.to_string())
+    }
+}
+
+/// Removes the `/` escaping a message that starts with `//`, so that it is
+/// sent starting with a single `/` instead of being parsed as a command.
+pub fn unescape(message: String) -> String {
+    if let Some(escaped) = message.trim_start().strip_prefix("//") {
+        return format!("/{escaped}");
+    }
+    message
+}
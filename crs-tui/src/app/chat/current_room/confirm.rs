@@ -0,0 +1,105 @@
+//! Popup to confirm an action that can't be undone on a room
+
+use core::convert::Infallible;
+
+use ratatui::Frame;
+use ratatui::crossterm::event::Event;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+use crate::ui::component::Component;
+use crate::ui::widgets::{InstructionsBuilder, linear_centre};
+
+/// Action on a room that needs to be confirmed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RoomAction {
+    /// Decline the pending invitation to the room
+    Decline,
+    /// Forget the room that was left, with its history
+    Forget,
+    /// Decline the pending invitation, and ignore the user who sent it
+    Ignore,
+    /// Leave the room
+    Leave,
+}
+
+impl RoomAction {
+    /// Returns the question asked to the user to confirm the action
+    const fn as_question(self) -> &'static str {
+        match self {
+            Self::Decline => "Do you really want to decline this invitation?",
+            Self::Forget =>
+                "You left this room. Do you also want to forget it and its \
+                 history?",
+            Self::Ignore =>
+                "Do you really want to decline this invitation and ignore its \
+                 sender? You won't receive their messages and invitations \
+                 anymore.",
+            Self::Leave => "Do you really want to leave this room?",
+        }
+    }
+}
+
+/// Popup to confirm an action on a room
+///
+/// An event returns `Some(true)` if the action is confirmed, and `Some(false)`
+/// if it is cancelled.
+pub struct ConfirmPopup {
+    /// Action to confirm
+    action: RoomAction,
+}
+
+impl ConfirmPopup {
+    /// Returns the action to confirm
+    pub const fn as_action(&self) -> RoomAction {
+        self.action
+    }
+
+    /// Creates a popup to confirm the given action
+    pub const fn new(action: RoomAction) -> Self {
+        Self { action }
+    }
+}
+
+impl Component for ConfirmPopup {
+    type ResponseData = Infallible;
+    type UpdateState = bool;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let centre =
+            linear_centre(Constraint::Length(6), Direction::Vertical, area);
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(centre);
+
+        let question = Paragraph::new(self.action.as_question())
+            .wrap(Wrap { trim: true })
+            .centered();
+        frame.render_widget(question, layout[0]);
+
+        let confirm = InstructionsBuilder::default()
+            .text("Press")
+            .key("Enter")
+            .text("to confirm, or any other key to cancel.")
+            .build();
+
+        let button_rect = linear_centre(
+            Constraint::Length(confirm.width.saturating_add(2)),
+            Direction::Horizontal,
+            layout[1],
+        );
+
+        let button = Paragraph::new(confirm.line)
+            .centered()
+            .block(Block::new().borders(Borders::ALL));
+
+        frame.render_widget(button, button_rect);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        Some(event.as_key_press_event()?.code.is_enter())
+    }
+}
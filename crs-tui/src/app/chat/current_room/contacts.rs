@@ -0,0 +1,474 @@
+//! Contact book gathering the people the user chats with on all the
+//! networks, to open the chat with them on the chosen network, and to link
+//! the identities of the same person into a meta-contact.
+//!
+//! The contacts listed by the bridges are included, and the chat with the
+//! ones without a portal is started on demand. They are loaded in the
+//! background with the meta-contacts, as the bridges may take a while to
+//! answer.
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::bridge::{KNOWN_BRIDGES, KnownBridge};
+use crs_backend::contacts::{
+    Contact, Identity, MetaContact, contact_book, link_contacts, unlink_contact
+};
+use crs_backend::room::DisplayRoom;
+use crs_backend::user::User;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem};
+
+use crate::app::chat::bridges::{bridge_contacts, network_marker};
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::{Instructions, InstructionsBuilder};
+use crate::utils::safe_unlock;
+
+/// Marker displayed for the identities of the Matrix users, with its colour
+const MATRIX_MARKER: (&str, Color) = ("MX", Color::White);
+
+/// Contacts loaded in the background: the meta-contacts or the error that
+/// prevented to fetch them, the identities listed by the bridges, and the
+/// errors of the bridges that couldn't list them
+type LoadedContacts =
+    (Result<Vec<MetaContact>, String>, Vec<Identity>, Vec<String>);
+
+/// Request of the contact book to the chat panel
+pub enum ContactRequest {
+    /// Open the chat with the selected contact
+    Open(Arc<Mutex<DisplayRoom>>),
+    /// Start a chat with the selected contact on the network of the bridge,
+    /// given their remote id, as they have no portal yet
+    StartChat(&'static KnownBridge, String),
+}
+
+/// Screen listing the contacts of the user on all the networks.
+///
+/// A contact is selected with its network, and an event returns the chat
+/// with them on this network, or the request to start it. Two contacts can be
+/// linked into a meta-contact, saved in the account data of the user, once
+/// the meta-contacts were loaded.
+pub struct ContactBook {
+    /// Identities of the contacts listed by the bridges
+    bridge_contacts: Vec<Identity>,
+    /// Contacts of the user, sorted by name
+    contacts:        Vec<Contact>,
+    /// Contacts loaded by the background task, until they are displayed
+    loaded:          Arc<Mutex<Option<LoadedContacts>>>,
+    /// Index of the contact marked to be linked with another one, if any
+    marked:          Option<usize>,
+    /// Meta-contacts of the user, linking several identities, `None` until
+    /// they are loaded, or if they couldn't be
+    meta_contacts:   Option<Vec<MetaContact>>,
+    /// Index of the identity of the selected contact, i.e., the network on
+    /// which to open the chat
+    network_index:   usize,
+    /// Rooms of the user, to find the chats with the contacts
+    rooms:           Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Input to search the contacts
+    search:          Input<'static>,
+    /// Index of the selected contact, if any
+    selected:        Option<usize>,
+    /// Status of the last change of the meta-contacts
+    status:          Status,
+    /// User to save the meta-contacts in their account data
+    user:            Arc<User>,
+}
+
+impl ContactBook {
+    /// Displays the meta-contacts and the contacts listed by the bridges,
+    /// once they were loaded by the background task.
+    ///
+    /// The errors of the meta-contacts and of the bridges that couldn't list
+    /// their contacts are displayed.
+    pub fn apply_loaded_contacts(&mut self) {
+        let Some((meta_contacts, bridge_contacts, bridge_errors)) =
+            safe_unlock(&self.loaded).take()
+        else {
+            return;
+        };
+        let mut errors = vec![];
+        match meta_contacts {
+            Ok(meta_contacts) => self.meta_contacts = Some(meta_contacts),
+            Err(err) => errors.push(format!("Meta-contacts not loaded: {err}")),
+        }
+        if !bridge_errors.is_empty() {
+            errors.push(format!(
+                "Contacts of the bridges not listed: {}",
+                bridge_errors.join(", ")
+            ));
+        }
+        if !errors.is_empty() {
+            self.status = Status::Error(errors.join(". "));
+        }
+        self.bridge_contacts = bridge_contacts;
+        self.refresh_contacts();
+    }
+
+    /// Draws the contacts matching the search
+    fn draw_contacts(&self, frame: &mut Frame<'_>, area: Rect) {
+        let items = self
+            .matching()
+            .map(|index| self.contact_item(index))
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            frame
+                .render_widget(Text::from("No contact found").centered(), area);
+            return;
+        }
+        frame.render_widget(List::new(items), area);
+    }
+
+    /// Returns the item of the list displaying a contact, with the markers of
+    /// their networks.
+    ///
+    /// The network on which the chat will be opened is highlighted for the
+    /// selected contact, with the user id of their identity on it, and
+    /// whether the chat must be started.
+    fn contact_item(&self, index: usize) -> ListItem<'_> {
+        let Some(contact) = self.contacts.get(index) else {
+            return ListItem::new("");
+        };
+        let is_selected = self.selected == Some(index);
+        let style = if is_selected {
+            Style::new().fg(Color::Green).add_modifier(Modifier::BOLD)
+        } else {
+            Style::new()
+        };
+        let mut title = vec![
+            Span::styled(if is_selected { ">" } else { " " }, style),
+            Span::styled(
+                if self.marked == Some(index) { "+" } else { " " },
+                Style::new().fg(Color::Yellow),
+            ),
+            Span::styled(contact.as_name(), style),
+        ];
+        if contact.is_linked() {
+            title.push(Span::styled(
+                " (linked)",
+                Style::new().fg(Color::DarkGray),
+            ));
+        }
+        for (identity_index, identity) in
+            contact.as_identities().iter().enumerate()
+        {
+            let (marker, colour) = identity_marker(identity);
+            let mut marker_style = Style::new().fg(colour);
+            if is_selected && identity_index == self.network_index {
+                marker_style = marker_style.add_modifier(Modifier::REVERSED);
+            }
+            title.push(Span::raw(" "));
+            title.push(Span::styled(format!("[{marker}]"), marker_style));
+        }
+        let mut lines = vec![Line::from(title)];
+        if is_selected
+            && let Some(identity) =
+                contact.as_identities().get(self.network_index)
+        {
+            let new_chat = if identity.as_room_id().is_none() {
+                " (new chat)"
+            } else {
+                ""
+            };
+            lines.push(Line::styled(
+                format!("    {}{new_chat}", identity.as_user_id()),
+                Style::new().add_modifier(Modifier::DIM),
+            ));
+        }
+        ListItem::new(Text::from(lines))
+    }
+
+    /// Instructions to display in the screen's footer
+    fn instructions() -> Instructions<'static> {
+        InstructionsBuilder::default()
+            .text(" Select")
+            .key("Tab")
+            .text("Network")
+            .key("Left/Right")
+            .text("Open")
+            .key("Enter")
+            .text("Link")
+            .key("C-a")
+            .text("Unlink")
+            .key("C-z")
+            .build()
+    }
+
+    /// Marks the selected contact to be linked, or links it with the marked
+    /// one, and saves the meta-contacts.
+    async fn link_selected(&mut self) {
+        let Some(selected) =
+            self.selected.filter(|_| self.meta_contacts.is_some())
+        else {
+            return;
+        };
+        let Some(marked) = self.marked else {
+            self.marked = Some(selected);
+            return;
+        };
+        if marked == selected {
+            self.marked = None;
+            return;
+        }
+        if let (Some(meta_contacts), Some(first), Some(second)) = (
+            &mut self.meta_contacts,
+            self.contacts.get(marked),
+            self.contacts.get(selected),
+        ) {
+            link_contacts(meta_contacts, first, second);
+            self.save().await;
+        }
+    }
+
+    /// Returns the indices of the contacts matching the search
+    fn matching(&self) -> impl Iterator<Item = usize> {
+        let search = self.search.as_value().trim().to_lowercase();
+        self.contacts
+            .iter()
+            .enumerate()
+            .filter(move |(_, contact)| contact.matches(&search))
+            .map(|(index, _)| index)
+    }
+
+    /// Opens the contact book with the contacts found in the chats of the
+    /// user, and loads the meta-contacts and the contacts listed by the
+    /// bridges in the background.
+    ///
+    /// They are displayed by [`Self::apply_loaded_contacts`].
+    pub fn new(
+        user: Arc<User>,
+        rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    ) -> Self {
+        let loaded = Arc::default();
+        let task_loaded = Arc::clone(&loaded);
+        let task_rooms = Arc::clone(&rooms);
+        let task_user = Arc::clone(&user);
+        let _handle = tokio::spawn(async move {
+            let meta_contacts =
+                task_user.meta_contacts().await.map_err(|err| err.to_string());
+            let (contacts, errors) =
+                bridge_contacts(&task_user, &task_rooms).await;
+            *safe_unlock(&task_loaded) =
+                Some((meta_contacts, contacts, errors));
+        });
+        let mut this = Self {
+            bridge_contacts: vec![],
+            contacts: vec![],
+            loaded,
+            marked: None,
+            meta_contacts: None,
+            network_index: 0,
+            rooms,
+            search: Input::new().with_active().with_title(" Search "),
+            selected: None,
+            status: Status::None,
+            user,
+        };
+        this.refresh_contacts();
+        this
+    }
+
+    /// Builds the contact book again, from the chats of the user, the
+    /// contacts listed by the bridges and the meta-contacts, and clears the
+    /// selection.
+    fn refresh_contacts(&mut self) {
+        let identities = safe_unlock(&self.rooms)
+            .iter()
+            .filter_map(|room| safe_unlock(room).contact_identity())
+            .chain(self.bridge_contacts.iter().cloned())
+            .collect();
+        self.contacts = contact_book(
+            identities,
+            self.meta_contacts.as_deref().unwrap_or_default(),
+        );
+        self.marked = None;
+        self.network_index = 0;
+        self.selected = None;
+    }
+
+    /// Saves the meta-contacts in the account data of the user, and displays
+    /// the contact book with them.
+    async fn save(&mut self) {
+        let Some(meta_contacts) = &self.meta_contacts else { return };
+        self.status = match self.user.save_meta_contacts(meta_contacts).await {
+            Ok(()) => Status::None,
+            Err(err) => Status::Error(err.to_string()),
+        };
+        self.refresh_contacts();
+    }
+
+    /// Selects the previous or the next contact matching the search.
+    ///
+    /// No contact is selected before the first one and after the last one.
+    fn select(&mut self, previous: bool) {
+        let matching = self.matching().collect::<Vec<_>>();
+        let position = self
+            .selected
+            .and_then(|selected| matching.iter().position(|&i| i == selected));
+        let new_position = match (position, previous) {
+            (None, true) => matching.len().checked_sub(1),
+            (None, false) => Some(0),
+            (Some(position), true) => position.checked_sub(1),
+            (Some(position), false) => position.checked_add(1),
+        };
+        self.selected =
+            new_position.and_then(|position| matching.get(position).copied());
+        self.network_index = 0;
+    }
+
+    /// Selects the previous or the next network of the selected contact,
+    /// going back to the first one after the last one.
+    fn select_network(&mut self, previous: bool) {
+        let nb_identities = self
+            .selected
+            .and_then(|selected| self.contacts.get(selected))
+            .map_or(0, |contact| contact.as_identities().len());
+        if nb_identities == 0 {
+            return;
+        }
+        self.network_index = if previous {
+            self.network_index
+                .checked_sub(1)
+                .unwrap_or_else(|| nb_identities.saturating_sub(1))
+        } else {
+            self.network_index
+                .saturating_add(1)
+                .checked_rem(nb_identities)
+                .unwrap_or(0)
+        };
+    }
+
+    /// Returns the chat with the selected contact on the selected network,
+    /// or the request to start it if the bridge listed a contact without a
+    /// portal
+    fn selected_request(&self) -> Option<ContactRequest> {
+        let contact = self.contacts.get(self.selected?)?;
+        let identity = contact.as_identities().get(self.network_index)?;
+        let room = identity.as_room_id().and_then(|room_id| {
+            safe_unlock(&self.rooms)
+                .iter()
+                .find(|room| safe_unlock(room).id() == room_id)
+                .cloned()
+        });
+        if let Some(room) = room {
+            return Some(ContactRequest::Open(room));
+        }
+        let network = identity.as_network()?;
+        let bridge = KNOWN_BRIDGES.iter().find(|bridge| {
+            bridge.as_protocol_id().eq_ignore_ascii_case(network)
+        })?;
+        Some(ContactRequest::StartChat(
+            bridge,
+            identity.as_remote_id()?.to_owned(),
+        ))
+    }
+
+    /// Unlinks the identities of the selected contact, and saves the
+    /// meta-contacts.
+    async fn unlink_selected(&mut self) {
+        let Some(contact) =
+            self.selected.and_then(|selected| self.contacts.get(selected))
+        else {
+            return;
+        };
+        if contact.is_linked()
+            && let Some(meta_contacts) = &mut self.meta_contacts
+        {
+            unlink_contact(meta_contacts, contact);
+            self.save().await;
+        }
+    }
+}
+
+impl Component for ContactBook {
+    type ResponseData = Infallible;
+    type UpdateState = ContactRequest;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let block = Block::bordered()
+            .title(" Contacts ")
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(Self::instructions().line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(inner);
+
+        self.search.draw(frame, layout[0]);
+        if let Some((content, colour)) = self.status.as_content() {
+            frame.render_widget(
+                Text::from(content).style(Style::new().fg(colour)).centered(),
+                layout[1],
+            );
+        } else if self.meta_contacts.is_none() {
+            frame.render_widget(
+                Text::from("Loading the contacts...")
+                    .style(Style::new().fg(Color::DarkGray))
+                    .centered(),
+                layout[1],
+            );
+        } else if let Some(marked) =
+            self.marked.and_then(|marked| self.contacts.get(marked))
+        {
+            let hint = format!(
+                "Select the contact to link with {}, and press C-a",
+                marked.as_name()
+            );
+            frame.render_widget(
+                Text::from(hint)
+                    .style(Style::new().fg(Color::Yellow))
+                    .centered(),
+                layout[1],
+            );
+        }
+        self.draw_contacts(frame, layout[2]);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        let key_event = event.as_key_press_event()?;
+        if key_event.modifiers & KeyModifiers::CONTROL == KeyModifiers::CONTROL
+        {
+            if key_event.code.is_char('a') {
+                self.link_selected().await;
+            } else if key_event.code.is_char('z') {
+                self.unlink_selected().await;
+            }
+            return None;
+        }
+        match key_event.code {
+            KeyCode::Tab | KeyCode::Down => self.select(false),
+            KeyCode::BackTab | KeyCode::Up => self.select(true),
+            KeyCode::Left => self.select_network(true),
+            KeyCode::Right => self.select_network(false),
+            KeyCode::Enter => return self.selected_request(),
+            _ => {
+                self.search.on_event(event).await;
+                self.selected = None;
+                self.network_index = 0;
+            }
+        }
+        None
+    }
+}
+
+/// Returns the marker of the network of an identity, with its colour
+fn identity_marker(identity: &Identity) -> (String, Color) {
+    identity.as_network().map_or_else(
+        || (MATRIX_MARKER.0.to_owned(), MATRIX_MARKER.1),
+        network_marker,
+    )
+}
@@ -0,0 +1,256 @@
+//! Screen to join a room by its address, or to find it in the public room
+//! directory of the homeserver or in the hierarchy of a space
+
+extern crate alloc;
+use alloc::sync::Arc;
+use std::sync::Mutex;
+
+use crs_backend::directory::{PublicRoom, PublicRoomsPage, is_room_address};
+use crs_backend::room::DisplayRoom;
+use crs_backend::user::User;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem};
+
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::{Instructions, InstructionsBuilder};
+use crate::utils::{UNKNOWN_NAME, safe_unlock};
+
+/// Title of the search input
+const SEARCH_TITLE: &str = " Search, or #alias:server, !id:server, link ";
+
+/// Screen to join a room by its address, or to browse the public room
+/// directory, or the rooms of a space if one is given.
+///
+/// An event returns the address of the room to join.
+pub struct RoomDirectory {
+    /// Page of the directory currently displayed, once a search was done
+    page:     Option<PublicRoomsPage>,
+    /// Input to type the search or the address of the room
+    search:   Input<'static>,
+    /// Search the current page is a result of
+    searched: String,
+    /// Index of the selected room in the page, if any
+    selected: Option<usize>,
+    /// Space whose rooms are listed instead of the public rooms, if any
+    space:    Option<Arc<Mutex<DisplayRoom>>>,
+    /// Status of the last search or join request
+    status:   Status,
+    /// User to query the directory of their homeserver
+    user:     Arc<User>,
+}
+
+impl RoomDirectory {
+    /// Draws the rooms of the current page
+    fn draw_page(&self, frame: &mut Frame<'_>, area: Rect) {
+        let Some(page) = &self.page else {
+            frame.render_widget(
+                Text::from(if self.space.is_some() {
+                    "Press Enter to list the rooms of the space"
+                } else {
+                    "Press Enter to list the public rooms"
+                })
+                .centered(),
+                area,
+            );
+            return;
+        };
+        if page.rooms.is_empty() {
+            frame.render_widget(Text::from("No room found").centered(), area);
+            return;
+        }
+        let items = page.rooms.iter().enumerate().map(|(index, room)| {
+            let is_selected = self.selected == Some(index);
+            room_item(room, is_selected)
+        });
+        frame.render_widget(List::new(items), area);
+    }
+
+    /// Fetches a page of the directory, or of the hierarchy of the space, for
+    /// the given search
+    async fn fetch_page(&mut self, search: String, since: Option<&str>) {
+        let page = if let Some(space) = &self.space {
+            let space_handle = safe_unlock(space).as_room();
+            space_handle.space_hierarchy(&search, since).await
+        } else {
+            self.user.public_rooms(&search, since).await
+        };
+        match page {
+            Ok(page) => {
+                self.page = Some(page);
+                self.searched = search;
+                self.selected = None;
+                self.status = Status::None;
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Instructions to display in the screen's footer
+    fn instructions() -> Instructions<'static> {
+        InstructionsBuilder::default()
+            .text(" Select")
+            .key("Tab")
+            .text("Search/Join")
+            .key("Enter")
+            .text("Page")
+            .key("PgUp/PgDn")
+            .build()
+    }
+
+    /// Creates the screen, listing the public rooms of the homeserver of the
+    /// user, or the rooms of the space, once the first search is done
+    pub const fn new(
+        user: Arc<User>,
+        space: Option<Arc<Mutex<DisplayRoom>>>,
+    ) -> Self {
+        Self {
+            page: None,
+            search: Input::new().with_active().with_title(SEARCH_TITLE),
+            searched: String::new(),
+            selected: None,
+            space,
+            status: Status::None,
+            user,
+        }
+    }
+
+    /// Selects the previous or the next room of the page.
+    ///
+    /// No room is selected before the first one and after the last one.
+    fn select(&mut self, previous: bool) {
+        let len = self.page.as_ref().map_or(0, |page| page.rooms.len());
+        let last = len.saturating_sub(1);
+        self.selected = match (self.selected, previous) {
+            _ if len == 0 => None,
+            (None, true) => Some(last),
+            (None, false) => Some(0),
+            (Some(0), true) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index < last =>
+                Some(index.saturating_add(1)),
+            (Some(_), false) => None,
+        };
+    }
+
+    /// Returns the id of the room that is currently selected
+    fn selected_room_id(&self) -> Option<String> {
+        let page = self.page.as_ref()?;
+        Some(page.rooms.get(self.selected?)?.room_id.clone())
+    }
+}
+
+impl Component for RoomDirectory {
+    type ResponseData = Status;
+    type UpdateState = String;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let title = self.space.as_ref().map_or_else(
+            || " Join a room ".to_owned(),
+            |space| {
+                let name = safe_unlock(space)
+                    .as_name()
+                    .unwrap_or_else(|| UNKNOWN_NAME.clone());
+                format!(" Join a room of {name} ")
+            },
+        );
+        let block = Block::bordered()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(Self::instructions().line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(inner);
+
+        self.search.draw(frame, layout[0]);
+        if let Some((content, colour)) = self.status.as_content() {
+            frame.render_widget(
+                Text::from(content).style(Style::new().fg(colour)).centered(),
+                layout[1],
+            );
+        }
+        self.draw_page(frame, layout[2]);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        if matches!(self.status, Status::Submitting) {
+            return None;
+        }
+        match event.as_key_press_event()?.code {
+            KeyCode::Tab => self.select(false),
+            KeyCode::BackTab => self.select(true),
+            KeyCode::PageDown => {
+                let next = self.page.as_ref()?.next_batch.clone()?;
+                self.fetch_page(self.searched.clone(), Some(&next)).await;
+            }
+            KeyCode::PageUp => {
+                let prev = self.page.as_ref()?.prev_batch.clone()?;
+                self.fetch_page(self.searched.clone(), Some(&prev)).await;
+            }
+            KeyCode::Enter => {
+                if is_room_address(self.search.as_value()) {
+                    return Some(self.search.as_value().trim().to_owned());
+                }
+                if let Some(room_id) = self.selected_room_id() {
+                    return Some(room_id);
+                }
+                self.fetch_page(self.search.as_value().to_owned(), None).await;
+            }
+            _ => {
+                self.search.on_event(event).await;
+            }
+        }
+        None
+    }
+
+    fn update(&mut self, response_data: Self::ResponseData) {
+        self.status = response_data;
+    }
+}
+
+/// Returns the item of the list displaying a public room
+fn room_item(room: &PublicRoom, is_selected: bool) -> ListItem<'_> {
+    let name =
+        room.name.as_deref().or(room.alias.as_deref()).unwrap_or(&room.room_id);
+    let style = if is_selected {
+        Style::new().fg(Color::Green).add_modifier(Modifier::BOLD)
+    } else {
+        Style::new()
+    };
+    let mut title = vec![
+        Span::styled(if is_selected { ">" } else { " " }, style),
+        Span::styled(name, style),
+        Span::styled(
+            format!(" ({} members)", room.member_count),
+            Style::new().fg(Color::DarkGray),
+        ),
+    ];
+    if let Some(alias) = &room.alias
+        && room.name.is_some()
+    {
+        title.push(Span::styled(
+            format!(" {alias}"),
+            Style::new().fg(Color::DarkGray),
+        ));
+    }
+    let mut lines = vec![Line::from(title)];
+    if let Some(topic) = &room.topic {
+        lines.push(Line::styled(
+            format!("  {topic}"),
+            Style::new().add_modifier(Modifier::DIM),
+        ));
+    }
+    ListItem::new(Text::from(lines))
+}
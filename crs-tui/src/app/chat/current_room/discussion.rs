@@ -5,37 +5,270 @@ extern crate alloc;
 use alloc::sync::Arc;
 use core::convert::Infallible;
 use core::mem::take;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
+use crs_backend::bridge::UserKind;
+use crs_backend::member::DisplayMember;
+use crs_backend::message::DisplayMessage;
 use crs_backend::room::DisplayRoom;
 use ratatui::Frame;
-use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{List, ListItem};
+use ratatui::widgets::{Block, List, ListItem};
 
+use crate::app::chat::bridges::network_marker;
+use crate::app::chat::current_room::commands::{Command, unescape};
+use crate::ui::code::{self, CodeBlock, Segment};
 use crate::ui::component::Component;
+use crate::ui::html;
 use crate::ui::input::Input;
-use crate::utils::safe_unlock;
+use crate::ui::prompt::candidates::Candidates;
+use crate::ui::qr::qr_lines;
+use crate::ui::widgets::saturating_cast;
+use crate::utils::{copy_to_clipboard, safe_unlock};
+
+/// Separator displayed before the language of a code block
+const CODE_HEADER: &str = "\u{2500}\u{2500} ";
+
+/// Maximum number of members displayed in the mention list
+const MAX_MENTION_CANDIDATES: usize = 5;
+
+/// Marker displayed before the messages that mention the user
+const MENTION_MARKER: &str = "@ ";
+
+/// Title of the list of members that can be mentioned
+const MENTION_TITLE: &str = " Mention (Tab: select, Enter: insert) ";
+
+/// Title of the message prompt when the message will be sent as Markdown
+const MARKDOWN_TITLE: &str = " Markdown (C-r: send raw) ";
+
+/// Title of the message prompt when the message will be sent as raw text
+const RAW_TITLE: &str = " Raw text (C-r: send Markdown) ";
 
 /// Discussion panel, with the all the messages and the input to send messages
 pub struct Discussion {
+    /// Error of the last message, moderation command or copy, if it failed
+    command_error:      Option<String>,
+    /// Whether the messages of the bridge bots are hidden
+    hide_bots:          bool,
+    /// Members proposed to complete the mention being typed, if any
+    mention_candidates: Option<Candidates<DisplayMember>>,
+    /// Members inserted as mentions in the message being written
+    mentions:           Vec<DisplayMember>,
     /// Message prompt to write the messages
-    message_prompt: Input<'static>,
+    message_prompt:     Input<'static>,
+    /// Segments of the messages, rendered and highlighted once, by event id,
+    /// with the body they were rendered from
+    rendered:           Mutex<HashMap<String, (String, Arc<[Rendered]>)>>,
     /// Room currently being displayed
-    room:           Arc<Mutex<DisplayRoom>>,
+    room:               Arc<Mutex<DisplayRoom>>,
+    /// Index of the code block selected to be copied.
+    ///
+    /// If `None`, the last code block of the discussion is selected.
+    selected_block:     Option<usize>,
+    /// Whether the next message is sent as raw text instead of Markdown
+    send_raw:           bool,
 }
 
 impl Discussion {
+    /// Returns the room currently being displayed
+    pub const fn as_room(&self) -> &Arc<Mutex<DisplayRoom>> {
+        &self.room
+    }
+
+    /// Returns the raw contents of the code blocks of the discussion
+    fn code_blocks(&self) -> Vec<String> {
+        let room = safe_unlock(&self.room);
+        let Ok(messages) = room.as_messages() else { return vec![] };
+        let shown = messages
+            .iter()
+            .filter(|message| self.is_shown(message))
+            .collect::<Vec<_>>();
+        self.segments(&shown)
+            .iter()
+            .flat_map(|segments| segments.iter())
+            .filter_map(|segment| match segment {
+                Rendered::Code(block, _) => Some(block.as_content().to_owned()),
+                Rendered::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Copies the selected code block to the clipboard, or displays the
+    /// error if it couldn't be copied
+    fn copy_selected_block(&mut self) {
+        let blocks = self.code_blocks();
+        let selected =
+            self.selected_block.unwrap_or(blocks.len().saturating_sub(1));
+        if let Some(block) = blocks.get(selected) {
+            self.command_error = copy_to_clipboard(block)
+                .err()
+                .map(|err| format!("Couldn't copy the code block: {err}"));
+        }
+    }
+
+    /// Replaces the mention being typed by the selected member.
+    ///
+    /// Returns `false` if no member is selected.
+    fn complete_mention(&mut self) -> bool {
+        let Some(member) = self
+            .mention_candidates
+            .as_ref()
+            .and_then(Candidates::selected)
+            .cloned()
+        else {
+            return false;
+        };
+        let value = self.message_prompt.as_value();
+        if let Some((start, _)) = mention_query(value)
+            && let Some(before) = value.get(..start)
+        {
+            let completed = format!("{before}{} ", member.mention_text());
+            self.message_prompt.set_value(completed);
+        }
+        self.mentions.push(member);
+        self.mention_candidates = None;
+        true
+    }
+
+    /// Draws the list of members that can be mentioned
+    fn draw_mention_candidates(
+        candidates: &Candidates<DisplayMember>,
+        frame: &mut Frame<'_>,
+        area: Rect,
+    ) {
+        let block = Block::bordered()
+            .title(MENTION_TITLE)
+            .title_style(Style::new().fg(Color::Yellow));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        candidates.draw(frame, inner);
+    }
+
+    /// Draws the messages of the discussion, with their code blocks
+    #[expect(
+        clippy::unwrap_used,
+        reason = "channel can't become erroneous on update"
+    )]
+    pub fn draw_messages(&self, frame: &mut Frame<'_>, area: Rect) {
+        let width = usize::from(area.width);
+
+        let room = safe_unlock(&self.room);
+        let messages = room
+            .as_messages()
+            .unwrap()
+            .iter()
+            .filter(|message| self.is_shown(message))
+            .collect::<Vec<_>>();
+
+        let segments = self.segments(&messages);
+        let nb_blocks = segments
+            .iter()
+            .flat_map(|segments| segments.iter())
+            .filter(|segment| matches!(segment, Rendered::Code(..)))
+            .count();
+        let selected_block =
+            self.selected_block.unwrap_or(nb_blocks.saturating_sub(1));
+        let mut block_index = 0_usize;
+
+        let list = messages.iter().zip(segments).map(|(message, segments)| {
+            let mut body = vec![];
+            for segment in segments.iter() {
+                match segment {
+                    Rendered::Text(lines) => body.extend(lines.iter().cloned()),
+                    Rendered::Code(block, highlighted) => {
+                        let is_selected = block_index == selected_block;
+                        body.push(code_header(block, is_selected));
+                        for line in highlighted {
+                            body.extend(code::wrap(line.clone(), width));
+                        }
+                        block_index = block_index.saturating_add(1);
+                    }
+                }
+            }
+            if body.is_empty() {
+                body.push(Line::default());
+            }
+            let first_body_line = take(&mut body[0]);
+
+            let mut header = vec![];
+            if message.mentions_me() {
+                header.push(Span::styled(
+                    MENTION_MARKER,
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ));
+            }
+            header.extend(sender_spans(message));
+            body[0] = Line::from(header);
+            body[0].extend(first_body_line);
+
+            ListItem::from(Text::from(body))
+        });
+
+        frame.render_widget(List::new(list), area);
+        drop(room);
+    }
+
+    /// Draws the number of hidden messages of the bridge bots, if the room
+    /// has any, with the key to show or hide them
+    fn draw_bots_hint(&self, frame: &mut Frame<'_>, area: Rect) {
+        let nb_bot_messages = self.nb_bot_messages();
+        if nb_bot_messages == 0 {
+            return;
+        }
+        let hint = if self.hide_bots {
+            format!("{nb_bot_messages} bridge bot messages hidden (C-a: show)")
+        } else {
+            "Bridge bot messages shown (C-a: hide)".to_owned()
+        };
+        frame.render_widget(
+            Text::from(hint).style(Style::new().fg(Color::DarkGray)).centered(),
+            area,
+        );
+    }
+
     /// Returns the underlying [`DisplayRoom`]
     pub fn into_room(self) -> Arc<Mutex<DisplayRoom>> {
         self.room
     }
 
+    /// Checks if a message is displayed, i.e., if it wasn't sent by a bridge
+    /// bot while their messages are hidden
+    const fn is_shown(&self, message: &DisplayMessage) -> bool {
+        !self.hide_bots
+            || !matches!(message.sender_kind(), UserKind::BridgeBot(_))
+    }
+
+    /// Returns the number of messages sent by the bridge bots in the room
+    fn nb_bot_messages(&self) -> usize {
+        safe_unlock(&self.room).as_messages().map_or(0, |messages| {
+            messages
+                .iter()
+                .filter(|message| {
+                    matches!(message.sender_kind(), UserKind::BridgeBot(_))
+                })
+                .count()
+        })
+    }
+
     /// Open a new conversation for the given room
-    pub const fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
-        Self { room, message_prompt: Input::new().with_active() }
+    pub fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
+        Self {
+            room,
+            command_error: None,
+            hide_bots: false,
+            mention_candidates: None,
+            mentions: vec![],
+            message_prompt: Input::new()
+                .with_active()
+                .with_title(MARKDOWN_TITLE),
+            rendered: Mutex::default(),
+            selected_block: None,
+            send_raw: false,
+        }
     }
 
     /// Checks if the current room is the same that the provided one, by
@@ -43,56 +276,300 @@ impl Discussion {
     pub fn room_is(&self, other: &DisplayRoom) -> bool {
         safe_unlock(&self.room).id() == other.id()
     }
+
+    /// Opens, updates or closes the list of members to mention, depending on
+    /// whether a mention is being typed.
+    async fn refresh_mention_candidates(&mut self) {
+        let Some((_, query)) = mention_query(self.message_prompt.as_value())
+        else {
+            self.mention_candidates = None;
+            return;
+        };
+        let query = query.to_owned();
+        if self.mention_candidates.is_none() {
+            let room = safe_unlock(&self.room).as_room();
+            if let Ok(members) = room.joined_members().await {
+                self.mention_candidates =
+                    Some(Candidates::new(members).with_ignore_case());
+            }
+        }
+        if let Some(candidates) = &mut self.mention_candidates {
+            candidates.update_matching(&query);
+        }
+    }
+
+    /// Returns the segments of the given messages, rendered and highlighted
+    /// once, then cached by event id and body.
+    ///
+    /// Only the given messages are kept in the cache. The messages without
+    /// an event id and the QR codes, which can expire, are rendered again
+    /// every time.
+    fn segments(&self, messages: &[&DisplayMessage]) -> Vec<Arc<[Rendered]>> {
+        let mut cache = safe_unlock(&self.rendered);
+        let mut previous = take(&mut *cache);
+        messages
+            .iter()
+            .map(|message| {
+                let Some(event_id) = message
+                    .as_event_id()
+                    .filter(|_| message.qr_code().is_none())
+                else {
+                    return render_segments(message);
+                };
+                let (key, (body, segments)) = previous
+                    .remove_entry(event_id)
+                    .filter(|(_, (body, _))| body == message.as_body())
+                    .unwrap_or_else(|| {
+                        let body = message.as_body().to_owned();
+                        (event_id.to_owned(), (body, render_segments(message)))
+                    });
+                cache.insert(key, (body, Arc::clone(&segments)));
+                segments
+            })
+            .collect()
+    }
+
+    /// Selects the previous or the next code block of the discussion
+    fn select_block(&mut self, previous: bool, nb_blocks: usize) {
+        let last = nb_blocks.saturating_sub(1);
+        let current = self.selected_block.unwrap_or(last);
+        self.selected_block = if previous {
+            Some(current.saturating_sub(1))
+        } else if current.saturating_add(1) < last {
+            Some(current.saturating_add(1))
+        } else {
+            None
+        };
+    }
+
+    /// Chooses whether the next message is sent as raw text or as Markdown
+    const fn set_send_raw(&mut self, send_raw: bool) {
+        self.send_raw = send_raw;
+        self.message_prompt.set_title(Some(if send_raw {
+            RAW_TITLE
+        } else {
+            MARKDOWN_TITLE
+        }));
+    }
 }
 
 impl Component for Discussion {
     type ResponseData = Infallible;
     type UpdateState = Infallible;
 
-    #[expect(
-        clippy::unwrap_used,
-        reason = "channel can't become erroneous on update"
-    )]
     fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let mention_height =
+            self.mention_candidates.as_ref().map_or(0, |candidates| {
+                saturating_cast(
+                    candidates.nb_matching().clamp(1, MAX_MENTION_CANDIDATES),
+                )
+                .saturating_add(2)
+            });
+
+        let error_height = u16::from(self.command_error.is_some());
+        let bots_hint_height = u16::from(self.nb_bot_messages() > 0);
+
         let layout = Layout::new(Direction::Vertical, [
             Constraint::Fill(1),
+            Constraint::Length(bots_hint_height),
+            Constraint::Length(mention_height),
+            Constraint::Length(error_height),
             Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
         ])
         .split(area);
 
-        let room = safe_unlock(&self.room);
-        let messages = room.as_messages().unwrap();
+        self.draw_messages(frame, layout[0]);
+        self.draw_bots_hint(frame, layout[1]);
 
-        let list = messages.iter().map(|message| {
-            let mut body = tui_markdown::from_str(message.as_body()).lines;
-            let first_body_line = take(&mut body[0]);
-
-            let sender = Span::from(format!("{}: ", message.as_sender()))
-                .patch_style(Style::default().fg(Color::Red));
-
-            body[0] = Line::from(sender);
-            body[0].extend(first_body_line);
-
-            ListItem::from(Text::from(body))
-        });
+        if let Some(candidates) = &self.mention_candidates {
+            Self::draw_mention_candidates(candidates, frame, layout[2]);
+        }
 
-        frame.render_widget(List::new(list), layout[0]);
-        drop(room);
+        if let Some(error) = &self.command_error {
+            frame.render_widget(
+                Text::from(error.as_str()).style(Style::new().fg(Color::Red)),
+                layout[3],
+            );
+        }
 
-        self.message_prompt.draw(frame, layout[1]);
+        self.message_prompt.draw(frame, layout[4]);
     }
 
-    #[expect(clippy::unwrap_used, reason = "not planned by trait")] // TODO: handle it
     async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
         let key_event = event.as_key_press_event()?;
 
+        if key_event.modifiers & KeyModifiers::CONTROL == KeyModifiers::CONTROL
+        {
+            match key_event.code {
+                KeyCode::Char('a') => {
+                    self.hide_bots = !self.hide_bots;
+                    self.selected_block = None;
+                }
+                KeyCode::Char('r') => self.set_send_raw(!self.send_raw),
+                KeyCode::Char('y') => self.copy_selected_block(),
+                KeyCode::Up =>
+                    self.select_block(true, self.code_blocks().len()),
+                KeyCode::Down =>
+                    self.select_block(false, self.code_blocks().len()),
+                _ => (),
+            }
+            return None;
+        }
+
+        if let Some(candidates) = &mut self.mention_candidates
+            && (key_event.code.is_tab() || key_event.code.is_back_tab())
+        {
+            let _: Infallible = candidates.on_event(event).await?;
+            return None;
+        }
+
         if key_event.code.is_enter() {
+            if self.complete_mention() {
+                return None;
+            }
+            self.mention_candidates = None;
+            self.command_error = None;
             let message = self.message_prompt.take_value();
+            let mentions = take(&mut self.mentions);
             let room = safe_unlock(&self.room).as_room();
-            room.send_plain(&message).await.unwrap();
+            let result = if self.send_raw {
+                room.send_plain(&message)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            } else if let Some(command) = Command::parse(&message) {
+                match command {
+                    Ok(command) => command.run(&room).await,
+                    Err(err) => Err(err),
+                }
+            } else {
+                room.send_markdown(&unescape(message.clone()), &mentions)
+                    .await
+                    .map_err(|err| err.to_string())
+            };
+            if let Err(err) = result {
+                self.command_error = Some(err);
+                self.message_prompt.set_value(message);
+                self.mentions = mentions;
+            } else {
+                self.set_send_raw(false);
+            }
             return None;
         }
 
-        self.message_prompt.on_event(event).await
+        self.message_prompt.on_event(event).await;
+        self.refresh_mention_candidates().await;
+        None
+    }
+}
+
+/// Segment of the body of a message, rendered once to be drawn at every frame
+enum Rendered {
+    /// Block of code, with its highlighted lines
+    Code(CodeBlock, Vec<Line<'static>>),
+    /// Formatted lines of text
+    Text(Vec<Line<'static>>),
+}
+
+/// Returns the line displayed before a code block, with its language
+fn code_header(block: &CodeBlock, is_selected: bool) -> Line<'static> {
+    let language = block.as_language().unwrap_or("code");
+    let header = format!("{CODE_HEADER}{language} ");
+    if is_selected {
+        let style = Style::new().fg(Color::Green);
+        Line::from(vec![
+            Span::styled(header, style),
+            Span::styled("(C-y: copy)", style),
+        ])
+    } else {
+        Line::from(Span::styled(header, Style::new().fg(Color::DarkGray)))
+    }
+}
+
+/// Finds the mention being typed at the end of the input, i.e., an `@` at the
+/// start of the input or after a whitespace, followed by no whitespace.
+///
+/// Returns the position of the `@` and the text typed after it.
+fn mention_query(input: &str) -> Option<(usize, &str)> {
+    let start = input.rfind('@')?;
+    let query = input.get(start.saturating_add(1)..)?;
+    let is_word_start =
+        input.get(..start)?.chars().next_back().is_none_or(char::is_whitespace);
+    (is_word_start && !query.contains(char::is_whitespace))
+        .then_some((start, query))
+}
+
+/// Splits the body of a message into text and code blocks
+///
+/// The QR codes are rendered instead of the body of their image. The HTML
+/// body is used if it can be rendered, and the plain body is interpreted as
+/// Markdown otherwise.
+fn message_segments(message: &DisplayMessage) -> Vec<Segment<'_>> {
+    if let Some(qr_code) = message.qr_code() {
+        return vec![Segment::Text(qr_lines(qr_code))];
+    }
+    match message.as_html().and_then(html::render) {
+        Some(segments) => segments,
+        None => code::parse_markdown(message.as_body()),
+    }
+}
+
+/// Returns a line owning its text, to be cached
+fn owned_line(line: Line<'_>) -> Line<'static> {
+    Line {
+        alignment: line.alignment,
+        spans:     line
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect(),
+        style:     line.style,
+    }
+}
+
+/// Renders the segments of a message, highlighting its code blocks
+fn render_segments(message: &DisplayMessage) -> Arc<[Rendered]> {
+    message_segments(message)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Code(block) => {
+                let highlighted = block.highlight();
+                Rendered::Code(block, highlighted)
+            }
+            Segment::Text(lines) =>
+                Rendered::Text(lines.into_iter().map(owned_line).collect()),
+        })
+        .collect()
+}
+
+/// Returns the spans displaying the sender of a message.
+///
+/// The ghosts and the bots of the bridges are labelled with the marker of
+/// their network, and the messages of the logged in user are highlighted,
+/// including the ones sent from the bridged network with double puppeting.
+fn sender_spans(message: &DisplayMessage) -> Vec<Span<'_>> {
+    let kind = message.sender_kind();
+    let mut spans = vec![];
+    if let Some(network) = kind.network() {
+        let (marker, colour) = network_marker(network);
+        spans.push(Span::styled(
+            format!("[{marker}] "),
+            Style::new().fg(colour),
+        ));
+    }
+    let colour = match kind {
+        _ if message.mentions_me() => Color::Yellow,
+        UserKind::Own => Color::Green,
+        UserKind::BridgeBot(_) => Color::DarkGray,
+        UserKind::Ghost(_) | UserKind::Matrix => Color::Red,
+    };
+    spans.push(Span::styled(message.as_sender(), Style::new().fg(colour)));
+    if let Some(source) = message.puppet_source() {
+        spans.push(Span::styled(
+            format!(" (via {source})"),
+            Style::new().fg(Color::DarkGray),
+        ));
     }
+    spans.push(Span::styled(": ", Style::new().fg(colour)));
+    spans
 }
@@ -0,0 +1,300 @@
+//! Unified inbox merging the unread messages of all the rooms and networks
+//! into one chronological feed, to triage them without opening each room.
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Paragraph};
+
+use crate::app::chat::bridges::network_marker;
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::{Instructions, InstructionsBuilder};
+use crate::utils::{UNKNOWN_NAME, safe_unlock};
+
+/// Unread message of a room, displayed in the inbox
+struct InboxEntry {
+    /// Body of the message
+    body:      String,
+    /// Id of the event of the message, to reply to it and to mark it as read
+    event_id:  String,
+    /// Whether the message was marked as read from the inbox
+    is_read:   bool,
+    /// Identifier of the network the room is bridged from, if any
+    network:   Option<String>,
+    /// Room of the message
+    room:      Arc<Mutex<DisplayRoom>>,
+    /// Name of the room of the message
+    room_name: Arc<str>,
+    /// Name of the sender of the message
+    sender:    String,
+    /// When the message was sent, in milliseconds since the Unix epoch
+    timestamp: u64,
+}
+
+impl InboxEntry {
+    /// Returns the line of the inbox displaying the message, labelled with its
+    /// room and its network
+    fn line(&self, is_selected: bool) -> Line<'_> {
+        let style = if self.is_read {
+            Style::new().add_modifier(Modifier::DIM)
+        } else {
+            Style::new()
+        };
+        let mut spans = vec![Span::styled(
+            if is_selected { ">" } else { " " },
+            Style::new().fg(Color::Green).add_modifier(Modifier::BOLD),
+        )];
+        if let Some(network) = &self.network {
+            let (marker, colour) = network_marker(network);
+            spans.push(Span::styled(format!("[{marker}] "), style.fg(colour)));
+        }
+        spans.extend([
+            Span::styled(format!("{} ", self.room_name), style.fg(Color::Cyan)),
+            Span::styled(
+                format!("{}: ", self.sender),
+                style.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(self.body.lines().next().unwrap_or_default(), style),
+        ]);
+        Line::from(spans)
+    }
+}
+
+/// Inbox listing the unread messages of all the rooms, sorted by date.
+///
+/// The messages are marked as read when the selection moves past them, or
+/// when replying to them. An event returns the room of the selected message,
+/// to open it.
+pub struct Inbox {
+    /// Unread messages, and the ones read since the inbox was opened
+    entries:  Vec<InboxEntry>,
+    /// Input to reply to the selected message
+    reply:    Input<'static>,
+    /// Rooms of the user, to find their unread messages
+    rooms:    Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Index of the selected message, if any
+    selected: Option<usize>,
+    /// Status of the last reply or read receipt
+    status:   Status,
+}
+
+impl Inbox {
+    /// Adds the new unread messages of the rooms to the inbox, keeping the
+    /// ones already listed, and sorts them by date.
+    fn collect_unread(&mut self) {
+        let selected_event = self
+            .selected
+            .and_then(|index| self.entries.get(index))
+            .map(|entry| entry.event_id.clone());
+        let rooms = safe_unlock(&self.rooms).clone();
+        for room in rooms {
+            let room_locked = safe_unlock(&room);
+            if room_locked.is_space()
+                || room_locked.is_left()
+                || room_locked.has_invitation()
+            {
+                continue;
+            }
+            let room_name =
+                room_locked.as_name().unwrap_or_else(|| UNKNOWN_NAME.clone());
+            let network = room_locked
+                .bridge()
+                .map(|bridge| bridge.as_protocol_id().to_owned());
+            for message in room_locked.unread_messages() {
+                let Some(event_id) = message.as_event_id() else { continue };
+                if self.entries.iter().any(|entry| entry.event_id == event_id) {
+                    continue;
+                }
+                self.entries.push(InboxEntry {
+                    body:      message.as_body().to_owned(),
+                    event_id:  event_id.to_owned(),
+                    is_read:   false,
+                    network:   network.clone(),
+                    room:      Arc::clone(&room),
+                    room_name: Arc::clone(&room_name),
+                    sender:    message.as_sender().to_owned(),
+                    timestamp: message.timestamp().unwrap_or(0),
+                });
+            }
+        }
+        self.entries.sort_by_key(|entry| entry.timestamp);
+        self.selected = selected_event.and_then(|event_id| {
+            self.entries.iter().position(|entry| entry.event_id == event_id)
+        });
+    }
+
+    /// Instructions to display in the screen's footer
+    fn instructions() -> Instructions<'static> {
+        InstructionsBuilder::default()
+            .text(" Select")
+            .key("Up/Down")
+            .text("Reply, or open the room")
+            .key("Enter")
+            .text("Mark all as read")
+            .key("C-a")
+            .build()
+    }
+
+    /// Marks the room of a message as read up to this message, which marks
+    /// the previous messages of the room as read too.
+    async fn mark_read(&mut self, index: usize) {
+        let Some(entry) = self.entries.get(index) else { return };
+        if entry.is_read {
+            return;
+        }
+        let room = Arc::clone(&entry.room);
+        let event_id = entry.event_id.clone();
+        let room_handle = safe_unlock(&room).as_room();
+        match room_handle.mark_as_read(&event_id).await {
+            Ok(()) => {
+                safe_unlock(&room).set_read_receipt(&event_id);
+                for entry in
+                    self.entries.iter_mut().take(index.saturating_add(1))
+                {
+                    if Arc::ptr_eq(&entry.room, &room) {
+                        entry.is_read = true;
+                    }
+                }
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Opens the inbox, with the unread messages of the given rooms
+    pub fn new(rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>) -> Self {
+        let mut this = Self {
+            entries: vec![],
+            reply: Input::new().with_active().with_title(" Reply "),
+            rooms,
+            selected: None,
+            status: Status::None,
+        };
+        this.collect_unread();
+        this
+    }
+
+    /// Sends the reply to the selected message, and marks its room as read
+    async fn send_reply(&mut self, index: usize) {
+        let Some(entry) = self.entries.get(index) else { return };
+        let room_handle = safe_unlock(&entry.room).as_room();
+        match room_handle
+            .send_reply(self.reply.as_value().trim(), &entry.event_id)
+            .await
+        {
+            Ok(()) => {
+                self.reply.take_value();
+                self.status = Status::None;
+                self.mark_read(index).await;
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+}
+
+impl Component for Inbox {
+    type ResponseData = Infallible;
+    type UpdateState = Arc<Mutex<DisplayRoom>>;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let nb_unread =
+            self.entries.iter().filter(|entry| !entry.is_read).count();
+        let block = Block::bordered()
+            .title(format!(" Inbox ({nb_unread} unread) "))
+            .title_alignment(Alignment::Center)
+            .title_style(Style::new().fg(Color::Yellow))
+            .title_bottom(Self::instructions().line);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
+        ])
+        .split(inner);
+
+        if self.entries.is_empty() {
+            frame.render_widget(
+                Text::from("No unread message").centered(),
+                layout[0],
+            );
+        } else {
+            let hidden = self.selected.map_or(0, |selected| {
+                selected.saturating_sub(
+                    usize::from(layout[0].height).saturating_sub(1),
+                )
+            });
+            let lines = self
+                .entries
+                .iter()
+                .enumerate()
+                .skip(hidden)
+                .map(|(index, entry)| entry.line(self.selected == Some(index)))
+                .collect::<Vec<_>>();
+            frame.render_widget(Paragraph::new(lines), layout[0]);
+        }
+
+        if let Some((content, colour)) = self.status.as_content() {
+            frame.render_widget(
+                Text::from(content).style(Style::new().fg(colour)).centered(),
+                layout[1],
+            );
+        }
+        self.reply.draw(frame, layout[2]);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        self.collect_unread();
+        let key_event = event.as_key_press_event()?;
+        if key_event.modifiers & KeyModifiers::CONTROL == KeyModifiers::CONTROL
+        {
+            if key_event.code.is_char('a') {
+                for index in (0..self.entries.len()).rev() {
+                    self.mark_read(index).await;
+                }
+            }
+            return None;
+        }
+        match key_event.code {
+            KeyCode::Down | KeyCode::Tab => {
+                if let Some(index) = self.selected {
+                    self.mark_read(index).await;
+                }
+                self.selected = match self.selected {
+                    None if !self.entries.is_empty() => Some(0),
+                    Some(index)
+                        if index.saturating_add(1) < self.entries.len() =>
+                        Some(index.saturating_add(1)),
+                    selected => selected,
+                };
+            }
+            KeyCode::Up | KeyCode::BackTab =>
+                self.selected = self
+                    .selected
+                    .map(|index| index.saturating_sub(1))
+                    .or_else(|| self.entries.len().checked_sub(1)),
+            KeyCode::Enter => {
+                let index = self.selected?;
+                if self.reply.is_empty() {
+                    self.mark_read(index).await;
+                    return Some(Arc::clone(&self.entries.get(index)?.room));
+                }
+                self.send_reply(index).await;
+            }
+            _ => {
+                self.reply.on_event(event).await;
+            }
+        }
+        None
+    }
+}
@@ -3,56 +3,197 @@
 extern crate alloc;
 use alloc::sync::Arc;
 use core::convert::Infallible;
+use core::mem::take;
+use core::time::Duration;
 use std::sync::Mutex;
 
+use crs_backend::directory::{DirectoryUser, is_user_id};
 use crs_backend::room::DisplayRoom;
 use ratatui::Frame;
-use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::{Event, KeyCode};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
-use ratatui::text::Text;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Paragraph, Wrap};
+use tokio::task::JoinHandle;
 
 use crate::ui::component::Component;
 use crate::ui::input::Input;
-use crate::ui::widgets::grid_centre;
+use crate::ui::prompt::candidates::Candidates;
+use crate::ui::widgets::{InstructionsBuilder, grid_centre, saturating_cast};
 use crate::utils::safe_unlock;
 
-/// Popup widget to invite a member to the current room
+/// Maximum number of users of the directory displayed
+const MAX_CANDIDATES: u16 = 5;
+
+/// Minimum length of the search before querying the user directory
+const MIN_SEARCH_LENGTH: usize = 2;
+
+/// Time without typing before querying the user directory
+const SEARCH_DELAY: Duration = Duration::from_millis(300);
+
+/// Search sent to the user directory, with the users found or the error
+type SearchResults = (String, Result<Vec<DirectoryUser>, String>);
+
+/// Popup widget to invite members to the current room
+///
+/// The users are searched in the user directory of the homeserver in the
+/// background once the user stops typing, and several users can be added before
+/// sending the invitations. An event returns `Some(())` once all the
+/// invitations were sent.
 pub struct InviteMemberPopup {
+    /// Users of the directory matching the search
+    candidates:  Option<Candidates<DirectoryUser>>,
     /// Invitation error, if exists
-    error:  Option<String>,
-    /// Name of the person to add to the current room
-    person: Input<'static>,
+    error:       Option<String>,
+    /// Results of the last query of the user directory, sent by the
+    /// background task
+    found:       Arc<Mutex<Option<SearchResults>>>,
+    /// User ids of the users to invite
+    invites:     Vec<String>,
+    /// Name or user id of the person to add to the current room
+    person:      Input<'static>,
     /// Room to which people need to be added
-    room:   Arc<Mutex<DisplayRoom>>,
+    room:        Arc<Mutex<DisplayRoom>>,
+    /// Background query of the user directory, waiting for the user to stop
+    /// typing
+    search_task: Option<JoinHandle<()>>,
+    /// Search of the last query of the user directory, not to query it again
+    /// when the text didn't change
+    searched:    String,
 }
 
 impl InviteMemberPopup {
+    /// Adds the selected candidate, or the typed user id, to the users to
+    /// invite.
+    fn add_invite(&mut self) {
+        let user_id =
+            match self.candidates.as_ref().and_then(Candidates::selected) {
+                Some(user) => user.as_user_id().to_string(),
+                None => self.person.as_value().trim().to_owned(),
+            };
+        if !is_user_id(&user_id) {
+            self.error = Some(format!(
+                "Invalid user id {user_id:?}, expected @user:server"
+            ));
+            return;
+        }
+        if !self.invites.contains(&user_id) {
+            self.invites.push(user_id);
+        }
+        self.person.take_value();
+        self.refresh_candidates();
+        self.error = None;
+    }
+
+    /// Displays the users found in the user directory by the background
+    /// task, if they were found for the current search
+    pub fn apply_search_results(&mut self) {
+        let Some((search, users)) = safe_unlock(&self.found).take() else {
+            return;
+        };
+        if search != self.searched {
+            return;
+        }
+        match users {
+            Ok(users) => {
+                let mut candidates = Candidates::new(users).with_ignore_case();
+                candidates.update_matching("");
+                self.candidates = Some(candidates);
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
     /// Returns the underlying room of an invitation popup
     pub fn into_room(self) -> Arc<Mutex<DisplayRoom>> {
         self.room
     }
 
     /// Create the [`InviteMemberPopup`] component
-    pub const fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
+    pub fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
         Self {
+            candidates: None,
             error: None,
+            found: Arc::default(),
+            invites: vec![],
             person: Input::new()
                 .with_active()
-                .with_label("Name of the person to add"),
+                .with_label("Name or user id of the person to add"),
             room,
+            search_task: None,
+            searched: String::new(),
+        }
+    }
+
+    /// Queries the user directory with the search being typed, if it
+    /// changed, after [`SEARCH_DELAY`] without typing.
+    ///
+    /// The query is sent in the background, the previous one being
+    /// cancelled, and its results are displayed with
+    /// [`Self::apply_search_results`].
+    fn refresh_candidates(&mut self) {
+        let search = self.person.as_value().trim();
+        if search == self.searched {
+            return;
+        }
+        search.clone_into(&mut self.searched);
+        if let Some(task) = self.search_task.take() {
+            task.abort();
+        }
+        self.candidates = None;
+        if self.searched.len() < MIN_SEARCH_LENGTH {
+            return;
+        }
+        let search = self.searched.clone();
+        let room = safe_unlock(&self.room).as_room();
+        let found = Arc::clone(&self.found);
+        self.search_task = Some(tokio::spawn(async move {
+            tokio::time::sleep(SEARCH_DELAY).await;
+            let users =
+                room.search_users(&search).await.map_err(|err| err.to_string());
+            *safe_unlock(&found) = Some((search, users));
+        }));
+    }
+
+    /// Sends the invitations to all the users that were added.
+    ///
+    /// The users that couldn't be invited are kept, to try again.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all the invitations were sent.
+    async fn send_invites(&mut self) -> bool {
+        let room = safe_unlock(&self.room).as_room();
+        let mut errors = vec![];
+        let mut failed = vec![];
+        for user_id in take(&mut self.invites) {
+            if let Err(err) = room.invite_user(&user_id).await {
+                errors.push(format!("{user_id}: {err}"));
+                failed.push(user_id);
+            }
+        }
+        self.invites = failed;
+        if errors.is_empty() {
+            true
+        } else {
+            self.error = Some(errors.join("\n"));
+            false
         }
     }
 }
 
 impl Component for InviteMemberPopup {
     type ResponseData = Infallible;
-    type UpdateState = Infallible;
+    type UpdateState = ();
 
     #[expect(clippy::arithmetic_side_effects, reason = "width >= 50")]
     fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
-        let height = Input::HEIGHT_WITH_LABEL + 2;
-        let width = (area.width - 2).min(50);
+        let candidates_height = self.candidates.as_ref().map_or(0, |list| {
+            saturating_cast(list.nb_matching()).clamp(1, MAX_CANDIDATES)
+        });
+        let height = Input::HEIGHT_WITH_LABEL + candidates_height + 5;
+        let width = (area.width - 2).min(60);
 
         let centre = grid_centre(
             Constraint::Length(width),
@@ -62,31 +203,73 @@ impl Component for InviteMemberPopup {
 
         let layout = Layout::new(Direction::Vertical, [
             Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(candidates_height),
+            Constraint::Length(1),
+            Constraint::Length(2),
             Constraint::Length(2),
         ])
         .split(centre);
 
         self.person.draw(frame, layout[0]);
 
+        if let Some(candidates) = &self.candidates {
+            candidates.draw(frame, layout[1]);
+        }
+
+        let invites = if self.invites.is_empty() {
+            Line::styled(
+                "Nobody to invite yet",
+                Style::new().fg(Color::DarkGray),
+            )
+        } else {
+            Line::from(vec![
+                Span::styled("To invite: ", Style::new().fg(Color::DarkGray)),
+                Span::from(self.invites.join(", ")),
+            ])
+        };
+        frame.render_widget(invites, layout[2]);
+
         if let Some(err) = &self.error {
             frame.render_widget(
                 Text::from(err.as_str()).style(Style::new().fg(Color::Red)),
-                layout[1],
+                layout[3],
             );
         }
+
+        let instructions = InstructionsBuilder::default()
+            .text("Select")
+            .key("Tab")
+            .text("Add")
+            .key("Enter")
+            .text("Send with an empty input")
+            .build();
+        frame.render_widget(
+            Paragraph::new(instructions.line).wrap(Wrap { trim: true }),
+            layout[4],
+        );
     }
 
     async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
-        if let Some(key_event) = event.as_key_press_event()
-            && key_event.code.is_enter()
-        {
-            let room = safe_unlock(&self.room).as_room();
-            if let Err(err) = room.invite_user(&self.person.take_value()).await
-            {
-                self.error = Some(err.to_string());
+        let key_event = event.as_key_press_event()?;
+        match key_event.code {
+            KeyCode::Tab | KeyCode::BackTab => {
+                if let Some(candidates) = &mut self.candidates {
+                    let _: Infallible = candidates.on_event(event).await?;
+                }
+            }
+            KeyCode::Enter if self.person.is_empty() =>
+                if !self.invites.is_empty() && self.send_invites().await {
+                    return Some(());
+                },
+            KeyCode::Enter => self.add_invite(),
+            KeyCode::Backspace if self.person.is_empty() => {
+                self.invites.pop();
+            }
+            _ => {
+                self.person.on_event(event).await;
+                self.refresh_candidates();
             }
         }
-
-        self.person.on_event(event).await
+        None
     }
 }
@@ -2,44 +2,130 @@
 
 use core::convert::Infallible;
 
+use crs_backend::invitation::Invitation;
 use ratatui::Frame;
-use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::{Event, KeyCode};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 
+use crate::app::chat::current_room::profile::field_line;
 use crate::ui::component::Component;
-use crate::ui::widgets::{InstructionsBuilder, linear_centre};
+use crate::ui::widgets::{InstructionsBuilder, linear_centre, saturating_cast};
 
-/// Represents the action of accepting an invitation
-pub struct AcceptInvitation;
+/// Answer given to an invitation
+pub enum InvitationAnswer {
+    /// Accept the invitation and join the room
+    Accept,
+    /// Decline the invitation
+    Decline,
+    /// Decline the invitation and ignore the user who sent it
+    Ignore,
+}
+
+/// Invitation popup to a channel, with a preview of the room
+pub struct InvitationToRoomPopup {
+    /// Preview of the room, if it could be loaded
+    invitation: Option<Invitation>,
+}
+
+impl InvitationToRoomPopup {
+    /// Checks if the user who sent the invitation is known, and thus can be
+    /// ignored
+    fn can_ignore(&self) -> bool {
+        self.invitation
+            .as_ref()
+            .is_some_and(|invitation| invitation.inviter.is_some())
+    }
+
+    /// Returns the lines describing the invitation
+    fn preview(&self) -> Vec<Line<'_>> {
+        let Some(invitation) = &self.invitation else {
+            return vec![Line::from(
+                "You have a pending invitation for this room.",
+            )];
+        };
+
+        let mut lines = vec![];
+        if let Some(inviter) = &invitation.inviter {
+            lines.push(Line::from(format!(
+                "{} ({}) invited you to",
+                inviter.as_name(),
+                inviter.as_user_id()
+            )));
+        } else {
+            lines.push(Line::from("You were invited to"));
+        }
+        lines.push(Line::styled(
+            invitation.name.as_deref().unwrap_or("an unnamed room"),
+            Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+        if let Some(topic) = &invitation.topic {
+            lines.push(Line::from(topic.as_str()));
+        }
+        lines.push(Line::default());
+        lines.push(field_line(
+            "Type",
+            if invitation.is_direct { "Direct message" } else { "Room" },
+        ));
+        lines.push(field_line(
+            "Encryption",
+            if invitation.encrypted { "Enabled" } else { "Disabled" },
+        ));
+        lines.push(field_line("Members", invitation.member_count.to_string()));
+        if let Some(network) = &invitation.network {
+            lines.push(field_line("Bridged from", network.as_str()));
+        }
+        if let Some(avatar_url) = &invitation.avatar_url {
+            lines.push(field_line("Avatar", avatar_url.as_str()));
+        }
+        lines
+    }
 
-/// Invitation popup to a channel
-pub struct InvitationToRoomPopup;
+    /// Creates the popup for an invitation, with its preview if it could be
+    /// loaded
+    pub const fn new(invitation: Option<Invitation>) -> Self {
+        Self { invitation }
+    }
+}
 
 impl Component for InvitationToRoomPopup {
     type ResponseData = Infallible;
-    type UpdateState = AcceptInvitation;
+    type UpdateState = InvitationAnswer;
 
     fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
-        let centre =
-            linear_centre(Constraint::Length(4), Direction::Vertical, area);
+        let preview = self.preview();
+        let preview_height = saturating_cast(preview.len());
+
+        let centre = linear_centre(
+            Constraint::Length(preview_height.saturating_add(4)),
+            Direction::Vertical,
+            area,
+        );
 
         let layout = Layout::new(Direction::Vertical, [
-            Constraint::Length(1),
+            Constraint::Length(preview_height.saturating_add(1)),
             Constraint::Length(3),
         ])
         .split(centre);
 
         let message =
-            Paragraph::new("You have a pending invitation for this room.")
-                .centered();
+            Paragraph::new(preview).wrap(Wrap { trim: true }).centered();
         frame.render_widget(message, layout[0]);
 
-        let accept = InstructionsBuilder::default()
+        let builder = InstructionsBuilder::default()
             .text("Press")
             .key("Enter")
-            .text("to accept and join.")
-            .build();
+            .text("to accept and join,")
+            .key("d")
+            .text("to decline");
+        let accept = if self.can_ignore() {
+            builder.text(", or").key("i").text("to ignore the inviter.")
+        } else {
+            builder.text(".")
+        }
+        .build();
 
         let button_rect = linear_centre(
             Constraint::Length(accept.width.saturating_add(2)),
@@ -55,12 +141,12 @@ impl Component for InvitationToRoomPopup {
     }
 
     async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
-        if let Some(key_event) = event.as_key_press_event()
-            && key_event.code.is_enter()
-        {
-            Some(AcceptInvitation)
-        } else {
-            None
+        match event.as_key_press_event()?.code {
+            KeyCode::Enter => Some(InvitationAnswer::Accept),
+            KeyCode::Char('d') => Some(InvitationAnswer::Decline),
+            KeyCode::Char('i') if self.can_ignore() =>
+                Some(InvitationAnswer::Ignore),
+            _ => None,
         }
     }
 }
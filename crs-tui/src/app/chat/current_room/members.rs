@@ -0,0 +1,255 @@
+//! Side panel with the members of the current room
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::bridge::UserKind;
+use crs_backend::member::{DisplayMember, Membership, Presence};
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::app::chat::bridges::network_marker;
+use crate::app::chat::current_room::profile::{MemberProfile, membership_name};
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::widgets::InstructionsBuilder;
+use crate::utils::safe_unlock;
+
+/// Width of the member panel, borders included
+pub const MEMBER_LIST_WIDTH: u16 = 36;
+
+/// Title of the member panel
+const TITLE: &str = " Members (C-l: close) ";
+
+/// Side panel listing the members of a room, grouped by role
+pub struct MemberList {
+    /// Index of the selected member, among the ones that match the search
+    cursor:  Option<usize>,
+    /// Profile of the selected member, if it is opened
+    profile: Option<MemberProfile>,
+    /// Room whose members are listed
+    room:    Arc<Mutex<DisplayRoom>>,
+    /// Input to search a member by name or user id
+    search:  Input<'static>,
+}
+
+impl MemberList {
+    /// Draws the list of the members that match the search
+    fn draw_list(&self, frame: &mut Frame<'_>, area: Rect) {
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Length(Input::HEIGHT_WITHOUT_LABEL),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+        self.search.draw(frame, layout[0]);
+
+        let room = safe_unlock(&self.room);
+        let members = match room.members() {
+            Some(Ok(members)) => members,
+            None => {
+                frame.render_widget(
+                    Text::from("Loading the members...")
+                        .style(Style::new().fg(Color::DarkGray))
+                        .centered(),
+                    layout[1],
+                );
+                return;
+            }
+            Some(Err(err)) => {
+                let error = Paragraph::new(err.to_string())
+                    .style(Style::new().fg(Color::Red))
+                    .wrap(Wrap { trim: true });
+                frame.render_widget(error, layout[1]);
+                return;
+            }
+        };
+
+        let search = self.search.as_value().to_lowercase();
+        let mut lines = vec![];
+        let mut last_role = None;
+        for (index, member) in members
+            .iter()
+            .filter(|member| matches_search(member, &search))
+            .enumerate()
+        {
+            let role = member.role();
+            if last_role != Some(role) {
+                lines.push(Line::styled(
+                    role.as_name(),
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ));
+                last_role = Some(role);
+            }
+            let is_selected = self.cursor == Some(index);
+            lines.push(member_line(member, is_selected));
+        }
+        drop(room);
+
+        if lines.is_empty() {
+            let empty = Text::from("No matching member")
+                .style(Style::new().fg(Color::Red))
+                .centered();
+            frame.render_widget(empty, layout[1]);
+        } else {
+            frame.render_widget(Paragraph::new(lines), layout[1]);
+        }
+
+        let instructions = InstructionsBuilder::default()
+            .key("Tab")
+            .text("select")
+            .key("Enter")
+            .text("profile")
+            .build();
+        frame.render_widget(
+            Paragraph::new(instructions.line).wrap(Wrap { trim: true }),
+            layout[2],
+        );
+    }
+
+    /// Returns the members that match the search
+    fn matching(&self) -> Vec<DisplayMember> {
+        let search = self.search.as_value().to_lowercase();
+        safe_unlock(&self.room).members().and_then(Result::ok).map_or_else(
+            Vec::new,
+            |members| {
+                members
+                    .iter()
+                    .filter(|member| matches_search(member, &search))
+                    .cloned()
+                    .collect()
+            },
+        )
+    }
+
+    /// Moves the cursor to the previous or the next member, cycling at the
+    /// ends of the list.
+    fn move_cursor(&mut self, previous: bool) {
+        let nb_matching = self.matching().len();
+        if nb_matching == 0 {
+            self.cursor = None;
+            return;
+        }
+        let last = nb_matching.saturating_sub(1);
+        self.cursor = Some(match (self.cursor, previous) {
+            (None | Some(0), true) => last,
+            (Some(cursor), true) => cursor.saturating_sub(1),
+            (Some(cursor), false) if cursor < last => cursor.saturating_add(1),
+            (None | Some(_), false) => 0,
+        });
+    }
+
+    /// Creates the member panel of a room, loading its members in the
+    /// background
+    pub fn new(room: Arc<Mutex<DisplayRoom>>) -> Self {
+        let room_wrap = safe_unlock(&room).as_room();
+        let loaded_room = Arc::clone(&room);
+        let _handle = tokio::spawn(async move {
+            let members = room_wrap.members().await;
+            safe_unlock(&loaded_room).set_members(members);
+        });
+        Self {
+            cursor: None,
+            profile: None,
+            room,
+            search: Input::new().with_active().with_title(" Search "),
+        }
+    }
+}
+
+impl Component for MemberList {
+    type ResponseData = Infallible;
+    type UpdateState = Infallible;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let block = Block::bordered()
+            .title(TITLE)
+            .title_style(Style::new().fg(Color::Yellow));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if let Some(profile) = &self.profile {
+            profile.draw(frame, inner);
+        } else {
+            self.draw_list(frame, inner);
+        }
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        if let Some(profile) = &mut self.profile {
+            profile.on_event(event).await?;
+            self.profile = None;
+            return None;
+        }
+
+        match event.as_key_press_event()?.code {
+            KeyCode::Tab => self.move_cursor(false),
+            KeyCode::BackTab => self.move_cursor(true),
+            KeyCode::Enter =>
+                self.profile = self
+                    .cursor
+                    .and_then(|cursor| self.matching().get(cursor).cloned())
+                    .map(|member| {
+                        MemberProfile::new(member, Arc::clone(&self.room))
+                    }),
+            _ => {
+                self.search.on_event(event).await;
+                self.cursor = None;
+            }
+        }
+        None
+    }
+}
+
+/// Checks if the name or the user id of a member contains the lowercase search
+fn matches_search(member: &DisplayMember, search: &str) -> bool {
+    member.as_name().to_lowercase().contains(search)
+        || member.as_user_id().as_str().to_lowercase().contains(search)
+}
+
+/// Returns the line displaying a member in the list, with their presence,
+/// the network of the ghosts and the bots of the bridges, and their membership
+/// if they didn't join the room.
+fn member_line(member: &DisplayMember, is_selected: bool) -> Line<'static> {
+    let presence_colour = match member.presence() {
+        Some(Presence::Online) => Color::Green,
+        Some(Presence::Unavailable) => Color::Yellow,
+        Some(Presence::Offline) | None => Color::DarkGray,
+    };
+    let name = match member.membership() {
+        Membership::Joined => member.as_name().to_owned(),
+        Membership::Banned | Membership::Invited => format!(
+            "{} ({})",
+            member.as_name(),
+            membership_name(member.membership()).to_lowercase()
+        ),
+    };
+    let (marker, name_style) = if is_selected {
+        ("*", Style::new().fg(Color::Green))
+    } else {
+        (" ", Style::new())
+    };
+    let mut spans = vec![
+        Span::styled(marker, name_style),
+        Span::styled("\u{25cf} ", Style::new().fg(presence_colour)),
+    ];
+    let kind = member.kind();
+    if let Some(network) = kind.network() {
+        let (label, colour) = network_marker(network);
+        spans
+            .push(Span::styled(format!("[{label}] "), Style::new().fg(colour)));
+    }
+    spans.push(Span::styled(name, name_style));
+    if matches!(kind, UserKind::BridgeBot(_)) {
+        spans.push(Span::styled(" (bot)", Style::new().fg(Color::DarkGray)));
+    }
+    Line::from(spans)
+}
@@ -1,9 +1,21 @@
 //! Current display in the chat panel
 
+mod archived;
+mod bridge_commands;
+mod bridge_health;
+mod bridge_login;
+mod commands;
+mod confirm;
+mod contacts;
+mod directory;
 mod discussion;
+mod inbox;
 mod invite_member;
 mod invited_not_joined;
+mod members;
+mod profile;
 mod prompts;
+mod settings;
 
 extern crate alloc;
 use alloc::sync::Arc;
@@ -11,22 +23,37 @@ use core::convert::Infallible;
 use core::mem::take;
 use std::sync::Mutex;
 
+use crs_backend::bridge::KnownBridge;
 use crs_backend::room::DisplayRoom;
+use crs_backend::user::{RoomOptions, User};
 use ratatui::Frame;
-use ratatui::crossterm::event::{Event, KeyModifiers};
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::text::Text;
 use ratatui::widgets::{Paragraph, Wrap};
 
+use crate::app::chat::current_room::archived::ArchivedRoom;
+use crate::app::chat::current_room::bridge_commands::BridgeCommands;
+use crate::app::chat::current_room::bridge_health::BridgeHealth;
+use crate::app::chat::current_room::bridge_login::{
+    BridgeLogin, BridgeLoginRequest
+};
+use crate::app::chat::current_room::confirm::{ConfirmPopup, RoomAction};
+use crate::app::chat::current_room::contacts::{ContactBook, ContactRequest};
+use crate::app::chat::current_room::directory::RoomDirectory;
 use crate::app::chat::current_room::discussion::Discussion;
+use crate::app::chat::current_room::inbox::Inbox;
 use crate::app::chat::current_room::invite_member::InviteMemberPopup;
 use crate::app::chat::current_room::invited_not_joined::{
-    AcceptInvitation, InvitationToRoomPopup
+    InvitationAnswer, InvitationToRoomPopup
 };
+use crate::app::chat::current_room::members::{MEMBER_LIST_WIDTH, MemberList};
+use crate::app::chat::current_room::prompts::bridged_chat::StartBridgedChat;
+use crate::app::chat::current_room::prompts::create_dm::CreateDm;
 use crate::app::chat::current_room::prompts::create_room::CreateRoom;
-pub use crate::app::chat::current_room::prompts::create_room::CreateRoomAction;
 use crate::app::chat::current_room::prompts::search_room::RoomSearch;
+use crate::app::chat::current_room::settings::RoomSettingsForm;
 use crate::ui::component::Component;
 use crate::ui::prompt::Status;
 use crate::ui::widgets::{
@@ -40,26 +67,61 @@ use crate::utils::{UNKNOWN_NAME, safe_unlock};
 pub struct CurrentRoom {
     /// Content currently displayed on the screen
     child:     CurrentRoomChild,
+    /// Side panel with the members of the room, if it is opened
+    members:   Option<MemberList>,
     /// Name of the room that is currently selected
     room_name: Arc<str>,
 }
 
 impl CurrentRoom {
-    /// Accept invitation and display the new status.
-    async fn accept_invitation(&mut self, room: Arc<Mutex<DisplayRoom>>) {
+    /// Applies the results sent by the background tasks of the open popup,
+    /// e.g. the users found by the invitation popup
+    pub fn apply_pending_update(&mut self) {
+        match &mut self.child {
+            CurrentRoomChild::Contacts(contacts, _) =>
+                contacts.apply_loaded_contacts(),
+            CurrentRoomChild::Invite(invite) => invite.apply_search_results(),
+            _ => (),
+        }
+    }
+
+    /// Does a confirmed action on a room.
+    ///
+    /// Once a room is left, the user is offered to forget it.
+    async fn apply_room_action(
+        &mut self,
+        action: RoomAction,
+        room: Arc<Mutex<DisplayRoom>>,
+    ) {
         let room_handle = safe_unlock(&room).as_room();
-        match room_handle.accept_invitation().await {
+        let result = match action {
+            RoomAction::Decline => room_handle.decline_invitation().await,
+            RoomAction::Forget => room_handle.forget().await,
+            RoomAction::Ignore => room_handle.ignore_inviter().await,
+            RoomAction::Leave => room_handle.leave().await,
+        };
+        match result {
             Err(err) => {
                 self.child =
                     CurrentRoomChild::Error(err.to_string(), Some(room));
             }
-            Ok(new_room) => {
-                *safe_unlock(&room) = new_room;
-                self.select_new_room(room);
+            Ok(()) if action == RoomAction::Leave => {
+                self.child = CurrentRoomChild::Confirm(
+                    ConfirmPopup::new(RoomAction::Forget),
+                    room,
+                );
             }
+            Ok(()) => self.close_room(),
         }
     }
 
+    /// Closes the current room, e.g. after it was left.
+    fn close_room(&mut self) {
+        self.child = CurrentRoomChild::None;
+        self.members = None;
+        self.room_name = Arc::default();
+    }
+
     /// Draws the error at the centre of the chat panel
     #[expect(clippy::arithmetic_side_effects, reason = "width >= 20")]
     fn draw_error(err_msg: &str, frame: &mut Frame<'_>, area: Rect) {
@@ -85,18 +147,164 @@ impl CurrentRoom {
         frame.render_widget(room_name_widget, area);
     }
 
+    /// Checks if the popup to start a bridged chat is open, e.g. waiting for
+    /// the portal to be created
+    pub const fn is_starting_bridged_chat(&self) -> bool {
+        matches!(self.child, CurrentRoomChild::BridgedChat(..))
+    }
+
+    /// Joins a room, to accept an invitation or to rejoin a room that was left,
+    /// and displays the new status.
+    async fn join_room(&mut self, room: Arc<Mutex<DisplayRoom>>) {
+        let room_handle = safe_unlock(&room).as_room();
+        match room_handle.join().await {
+            Err(err) => {
+                self.child =
+                    CurrentRoomChild::Error(err.to_string(), Some(room));
+            }
+            Ok(new_room) => {
+                *safe_unlock(&room) = new_room;
+                self.select_new_room(room);
+            }
+        }
+    }
+
+    /// Handles the answer to a confirmation popup
+    async fn on_confirmation(&mut self, action: RoomAction, confirmed: bool) {
+        let Some(room) = self.child.take_room() else { return };
+        if confirmed {
+            self.apply_room_action(action, room).await;
+        } else {
+            self.select_new_room(room);
+        }
+    }
+
+    /// Handles the shortcuts pressed with the control key.
+    ///
+    /// Returns `true` if the event was consumed, and `false` if it must be
+    /// forwarded to the child, e.g. the discussion or the contact book.
+    async fn on_control_key(&mut self, code: KeyCode) -> bool {
+        if code.is_char('b') {
+            let old_room = self.child.take_room();
+            self.child =
+                CurrentRoomChild::BridgeLogin(BridgeLogin::new(), old_room);
+            return true;
+        }
+
+        if code.is_char('n') {
+            let old_room = self.child.take_room();
+            self.child =
+                CurrentRoomChild::CreateRoom(CreateRoom::new(), old_room);
+            return true;
+        }
+
+        if code.is_char('d') {
+            let old_room = self.child.take_room();
+            self.child = CurrentRoomChild::CreateDm(CreateDm::new(), old_room);
+            return true;
+        }
+
+        #[expect(clippy::unwrap_used, reason = "checked w/ has_room")]
+        if code.is_char('j') && self.child.has_room() {
+            let room = self.child.take_room().unwrap();
+            self.child = CurrentRoomChild::Invite(InviteMemberPopup::new(room));
+            return true;
+        }
+
+        #[expect(clippy::unwrap_used, reason = "checked w/ is_discussion")]
+        if code.is_char('s') && self.child.is_discussion() {
+            let room = self.child.take_room().unwrap();
+            self.open_settings(room).await;
+            return true;
+        }
+
+        #[expect(clippy::unwrap_used, reason = "checked w/ is_discussion")]
+        if code.is_char('x') && self.child.is_discussion() {
+            let room = self.child.take_room().unwrap();
+            self.child = CurrentRoomChild::Confirm(
+                ConfirmPopup::new(RoomAction::Leave),
+                room,
+            );
+            return true;
+        }
+
+        #[expect(clippy::unwrap_used, reason = "checked w/ archived")]
+        if code.is_char('x')
+            && let CurrentRoomChild::Archived(_) = self.child
+        {
+            let room = self.child.take_room().unwrap();
+            self.child = CurrentRoomChild::Confirm(
+                ConfirmPopup::new(RoomAction::Forget),
+                room,
+            );
+            return true;
+        }
+
+        if code.is_char('l') {
+            self.members = match (&self.members, &self.child) {
+                (None, CurrentRoomChild::Discussion(discussion)) =>
+                    Some(MemberList::new(Arc::clone(discussion.as_room()))),
+                _ => None,
+            };
+            return true;
+        }
+
+        !self.child.handles_control_keys()
+    }
+
+    /// Opens the chat chosen in the contact book, or starts it with the
+    /// popup to start a bridged chat, returning the request to send the
+    /// command to the bot of the bridge.
+    fn open_contact(&mut self, request: ContactRequest) -> Option<RoomRequest> {
+        match request {
+            ContactRequest::Open(room) => {
+                self.select_new_room(room);
+                None
+            }
+            ContactRequest::StartChat(bridge, remote_id) => {
+                let mut bridged_chat =
+                    StartBridgedChat::with_remote_id(bridge, remote_id.clone());
+                bridged_chat.update(Status::Submitting);
+                let old_room = self.child.take_room();
+                self.child =
+                    CurrentRoomChild::BridgedChat(bridged_chat, old_room);
+                Some(RoomRequest::BridgedChat(bridge, remote_id))
+            }
+        }
+    }
+
+    /// Loads the settings of the current room and opens the form to edit
+    /// them.
+    async fn open_settings(&mut self, room: Arc<Mutex<DisplayRoom>>) {
+        let room_handle = safe_unlock(&room).as_room();
+        self.child = match room_handle.settings().await {
+            Ok(settings) => CurrentRoomChild::Settings(RoomSettingsForm::new(
+                room, settings,
+            )),
+            Err(err) => CurrentRoomChild::Error(err.to_string(), Some(room)),
+        };
+    }
+
     /// Open a new room in the discussion panel
     fn select_new_room(&mut self, room: Arc<Mutex<DisplayRoom>>) {
+        self.members = None;
         let room_handle = safe_unlock(&room);
         self.room_name =
             room_handle.as_name().unwrap_or_else(|| UNKNOWN_NAME.clone());
         if room_handle.has_invitation() {
+            let invitation = room_handle.invitation().cloned();
             drop(room_handle);
-            self.child = CurrentRoomChild::Invited(InvitationToRoomPopup, room);
+            self.child = CurrentRoomChild::Invited(
+                InvitationToRoomPopup::new(invitation),
+                room,
+            );
         } else if let Err(err) = room_handle.as_messages() {
             let err_msg = err.to_string();
             drop(room_handle);
             self.child = CurrentRoomChild::Error(err_msg, Some(room));
+        } else if room_handle.is_left() {
+            drop(room_handle);
+            self.child = CurrentRoomChild::Archived(ArchivedRoom::new(room));
         } else {
             drop(room_handle);
             self.child = CurrentRoomChild::Discussion(Discussion::new(room));
@@ -106,7 +314,7 @@ impl CurrentRoom {
 
 impl Component for CurrentRoom {
     type ResponseData = UpdateCurrentRoomPanel;
-    type UpdateState = CreateRoomAction;
+    type UpdateState = RoomRequest;
 
     fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
         let constraints: &[Constraint] = if self.child.is_discussion() {
@@ -119,66 +327,160 @@ impl Component for CurrentRoom {
         self.draw_room_name(frame, layout[0]);
 
         match &self.child {
+            CurrentRoomChild::Archived(child) => child.draw(frame, layout[1]),
+            CurrentRoomChild::BridgeCommands(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::BridgeHealth(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::BridgeLogin(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::BridgedChat(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::Contacts(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::CreateDm(child, _) =>
+                child.draw(frame, layout[1]),
             CurrentRoomChild::CreateRoom(child, _) => {
                 child.draw(frame, layout[1]);
             }
-            CurrentRoomChild::Discussion(child) => child.draw(frame, layout[1]),
+            CurrentRoomChild::Directory(child, _) =>
+                child.draw(frame, layout[1]),
+            CurrentRoomChild::Inbox(child, _) => child.draw(frame, layout[1]),
+            CurrentRoomChild::Discussion(child) => {
+                if let Some(members) = &self.members {
+                    let panels = Layout::new(Direction::Horizontal, [
+                        Constraint::Fill(1),
+                        Constraint::Length(MEMBER_LIST_WIDTH),
+                    ])
+                    .split(layout[1]);
+                    child.draw(frame, panels[0]);
+                    members.draw(frame, panels[1]);
+                } else {
+                    child.draw(frame, layout[1]);
+                }
+            }
             CurrentRoomChild::Error(err_msg, _) => {
                 Self::draw_error(err_msg, frame, layout[1]);
             }
             CurrentRoomChild::Invite(invite) => invite.draw(frame, layout[1]),
+            CurrentRoomChild::Confirm(child, _) => child.draw(frame, layout[1]),
             CurrentRoomChild::Invited(child, _) => child.draw(frame, layout[1]),
             CurrentRoomChild::None => NoRoomSelected.draw(frame, layout[1]),
             CurrentRoomChild::Search(child, _) => child.draw(frame, layout[1]),
+            CurrentRoomChild::Settings(form) => form.draw(frame, layout[1]),
         }
     }
 
     #[expect(clippy::unreachable, reason = "just checked monothread data")]
     async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        let is_control = event.as_key_press_event().is_some_and(|key_event| {
+            key_event.modifiers & KeyModifiers::CONTROL == KeyModifiers::CONTROL
+        });
+
         if let Some(key_event) = event.as_key_press_event()
-            && key_event.modifiers & KeyModifiers::CONTROL
-                == KeyModifiers::CONTROL
+            && is_control
+            && self.on_control_key(key_event.code).await
         {
-            if key_event.code.is_char('n') {
-                let old_room = self.child.take_room();
-                self.child =
-                    CurrentRoomChild::CreateRoom(CreateRoom::new(), old_room);
-                return None;
+            return None;
+        }
+
+        match &mut self.child {
+            CurrentRoomChild::Archived(archived) => {
+                archived.on_event(event).await?;
+                let room = self.child.take_room()?;
+                self.join_room(room).await;
             }
 
-            #[expect(clippy::unwrap_used, reason = "checked w/ has_room")]
-            if key_event.code.is_char('j') && self.child.has_room() {
-                let room = self.child.take_room().unwrap();
-                self.child =
-                    CurrentRoomChild::Invite(InviteMemberPopup::new(room));
+            CurrentRoomChild::BridgeCommands(commands, _) => {
+                let _: Infallible = commands.on_event(event).await?;
             }
 
-            return None;
-        }
+            CurrentRoomChild::BridgeHealth(health, _) => {
+                let _: Infallible = health.on_event(event).await?;
+            }
+
+            CurrentRoomChild::BridgeLogin(login, _) =>
+                match login.on_event(event).await? {
+                    BridgeLoginRequest::Close(room) =>
+                        self.select_new_room(room),
+                    BridgeLoginRequest::Connect(bridge) =>
+                        return Some(RoomRequest::Bridge(bridge)),
+                },
+
+            CurrentRoomChild::BridgedChat(bridged_chat, _) => {
+                let remote_id = bridged_chat.on_event(event).await?;
+                bridged_chat.update(Status::Submitting);
+                return Some(RoomRequest::BridgedChat(
+                    bridged_chat.as_bridge(),
+                    remote_id,
+                ));
+            }
+
+            CurrentRoomChild::Contacts(contacts, _) => {
+                let request = contacts.on_event(event).await?;
+                return self.open_contact(request);
+            }
+
+            CurrentRoomChild::CreateDm(create_dm, _) => {
+                let user_id = create_dm.on_event(event).await?;
+                create_dm.update(Status::Submitting);
+                return Some(RoomRequest::Direct(user_id));
+            }
 
-        match &mut self.child {
             CurrentRoomChild::CreateRoom(create_room, _) => {
-                let name = create_room.on_event(event).await?;
+                let options = create_room.on_event(event).await?;
                 create_room.update(Status::Submitting);
-                return Some(CreateRoomAction(name));
+                return Some(RoomRequest::Create(options));
+            }
+
+            CurrentRoomChild::Directory(directory, _) => {
+                let address = directory.on_event(event).await?;
+                directory.update(Status::Submitting);
+                return Some(RoomRequest::Join(address));
             }
 
             CurrentRoomChild::Discussion(discussion) => {
-                discussion.on_event(event).await;
+                if let Some(members) = &mut self.members
+                    && !is_control
+                {
+                    let _: Infallible = members.on_event(event).await?;
+                } else {
+                    discussion.on_event(event).await;
+                }
+            }
+
+            CurrentRoomChild::Inbox(inbox, _) => {
+                let room = inbox.on_event(event).await?;
+                self.select_new_room(room);
             }
 
             CurrentRoomChild::Invite(invite_member) => {
-                let _: Infallible = invite_member.on_event(event).await?;
+                invite_member.on_event(event).await?;
+                let room = self.child.take_room()?;
+                self.select_new_room(room);
+            }
+
+            CurrentRoomChild::Confirm(popup, _) => {
+                let confirmed = popup.on_event(event).await?;
+                let action = popup.as_action();
+                self.on_confirmation(action, confirmed).await;
             }
 
             CurrentRoomChild::Invited(invitation_popup, _) => {
-                let _: AcceptInvitation =
-                    invitation_popup.on_event(event).await?;
-                match take(&mut self.child) {
-                    CurrentRoomChild::Invited(_, room) => {
-                        self.accept_invitation(room).await;
-                    }
-                    _ => unreachable!(),
+                let answer = invitation_popup.on_event(event).await?;
+                let room = self.child.take_room()?;
+                match answer {
+                    InvitationAnswer::Accept => self.join_room(room).await,
+                    InvitationAnswer::Decline =>
+                        self.child = CurrentRoomChild::Confirm(
+                            ConfirmPopup::new(RoomAction::Decline),
+                            room,
+                        ),
+                    InvitationAnswer::Ignore =>
+                        self.child = CurrentRoomChild::Confirm(
+                            ConfirmPopup::new(RoomAction::Ignore),
+                            room,
+                        ),
                 }
             }
 
@@ -196,6 +498,12 @@ impl Component for CurrentRoom {
                 }
             }
 
+            CurrentRoomChild::Settings(form) => {
+                form.on_event(event).await?;
+                let room = self.child.take_room()?;
+                self.select_new_room(room);
+            }
+
             CurrentRoomChild::None | CurrentRoomChild::Error(..) => (),
         }
         None
@@ -203,11 +511,65 @@ impl Component for CurrentRoom {
 
     fn update(&mut self, response_data: Self::ResponseData) {
         match response_data {
+            UpdateCurrentRoomPanel::BridgeCommands(room_list) => {
+                let old_room = self.child.take_room();
+                self.child = CurrentRoomChild::BridgeCommands(
+                    BridgeCommands::new(room_list),
+                    old_room,
+                );
+            }
+
+            UpdateCurrentRoomPanel::BridgeHealth(room_list) => {
+                let old_room = self.child.take_room();
+                self.child = CurrentRoomChild::BridgeHealth(
+                    BridgeHealth::new(room_list),
+                    old_room,
+                );
+            }
+
+            UpdateCurrentRoomPanel::BridgeBot(room) =>
+                if let CurrentRoomChild::BridgeLogin(login, _) = &mut self.child
+                {
+                    login.update(room);
+                } else {
+                    self.select_new_room(room);
+                },
+
+            UpdateCurrentRoomPanel::BridgedChat(bridge) => {
+                let old_room = self.child.take_room();
+                self.child = CurrentRoomChild::BridgedChat(
+                    StartBridgedChat::new(bridge),
+                    old_room,
+                );
+            }
+
+            UpdateCurrentRoomPanel::Contacts(user, room_list) => {
+                let old_room = self.child.take_room();
+                self.child = CurrentRoomChild::Contacts(
+                    ContactBook::new(user, room_list),
+                    old_room,
+                );
+            }
+
+            UpdateCurrentRoomPanel::Directory(user, space) => {
+                let old_room = self.child.take_room();
+                self.child = CurrentRoomChild::Directory(
+                    RoomDirectory::new(user, space),
+                    old_room,
+                );
+            }
+
             UpdateCurrentRoomPanel::Error(error) => {
                 let old_room = self.child.take_room();
                 self.child = CurrentRoomChild::Error(error, old_room);
             }
 
+            UpdateCurrentRoomPanel::Inbox(room_list) => {
+                let old_room = self.child.take_room();
+                self.child =
+                    CurrentRoomChild::Inbox(Inbox::new(room_list), old_room);
+            }
+
             UpdateCurrentRoomPanel::NewRoom(new_room) => {
                 self.select_new_room(new_room);
             }
@@ -226,12 +588,33 @@ impl Component for CurrentRoom {
 /// Type of the content displayed in the chat panel
 #[derive(Default)]
 enum CurrentRoomChild {
+    /// A room that was left, displayed read-only
+    Archived(ArchivedRoom),
+    /// Command palette to manage a bridge through its bot
+    BridgeCommands(BridgeCommands, Option<Arc<Mutex<DisplayRoom>>>),
+    /// Panel with the state of the bridges
+    BridgeHealth(BridgeHealth, Option<Arc<Mutex<DisplayRoom>>>),
+    /// Wizard to log into a bridge
+    BridgeLogin(BridgeLogin, Option<Arc<Mutex<DisplayRoom>>>),
+    /// A popup to start a chat with a contact on a bridged network
+    BridgedChat(StartBridgedChat, Option<Arc<Mutex<DisplayRoom>>>),
+    /// A popup to confirm an action on the room
+    Confirm(ConfirmPopup, Arc<Mutex<DisplayRoom>>),
+    /// Contact book, to open the chat with a contact on one of their networks
+    Contacts(ContactBook, Option<Arc<Mutex<DisplayRoom>>>),
+    /// A popup to open a direct chat with a user
+    CreateDm(CreateDm, Option<Arc<Mutex<DisplayRoom>>>),
     /// A popup to create a new room
     CreateRoom(CreateRoom, Option<Arc<Mutex<DisplayRoom>>>),
+    /// Screen to join a room by its address or from the public room
+    /// directory
+    Directory(RoomDirectory, Option<Arc<Mutex<DisplayRoom>>>),
     /// A valid room discussion is open and running
     Discussion(Discussion),
     /// An error occurred and needs to be displayed
     Error(String, Option<Arc<Mutex<DisplayRoom>>>),
+    /// Inbox with the unread messages of all the rooms
+    Inbox(Inbox, Option<Arc<Mutex<DisplayRoom>>>),
     /// Invite some people to a room
     Invite(InviteMemberPopup),
     /// The current roomed hasn't been joined by the user yet, but it has a
@@ -245,20 +628,48 @@ enum CurrentRoomChild {
     /// This member stores the old room that the user had before entering search
     /// mode.
     Search(RoomSearch, Option<Arc<Mutex<DisplayRoom>>>),
+    /// Form to edit the settings of the room
+    Settings(RoomSettingsForm),
 }
 
 impl CurrentRoomChild {
     /// Checks if a room is currently specified
     const fn has_room(&self) -> bool {
         match self {
-            Self::Discussion(_) | Self::Invite(_) | Self::Invited(..) => true,
+            Self::Archived(_)
+            | Self::Confirm(..)
+            | Self::Discussion(_)
+            | Self::Invite(_)
+            | Self::Invited(..)
+            | Self::Settings(_) => true,
             Self::None => false,
             Self::Error(_, room)
+            | Self::BridgeCommands(_, room)
+            | Self::BridgeHealth(_, room)
+            | Self::BridgeLogin(_, room)
+            | Self::BridgedChat(_, room)
+            | Self::Contacts(_, room)
+            | Self::CreateDm(_, room)
             | Self::CreateRoom(_, room)
+            | Self::Directory(_, room)
+            | Self::Inbox(_, room)
             | Self::Search(_, room) => room.is_some(),
         }
     }
 
+    /// Checks if the child has its own shortcuts pressed with the control
+    /// key, the ones that aren't handled by the current room being forwarded
+    /// to it.
+    const fn handles_control_keys(&self) -> bool {
+        matches!(
+            self,
+            Self::Contacts(..)
+                | Self::Discussion(_)
+                | Self::Inbox(..)
+                | Self::Settings(_)
+        )
+    }
+
     /// Checks if current state is a discussion, meaning the client can interact
     /// with the room without issues.
     const fn is_discussion(&self) -> bool {
@@ -278,12 +689,22 @@ impl CurrentRoomChild {
     /// exists.
     fn take_room(&mut self) -> Option<Arc<Mutex<DisplayRoom>>> {
         match take(self) {
+            Self::Archived(archived) => Some(archived.into_room()),
             Self::Discussion(discussion) => Some(discussion.into_room()),
             Self::Invite(invite) => Some(invite.into_room()),
-            Self::Invited(_, room) => Some(room),
+            Self::Confirm(_, room) | Self::Invited(_, room) => Some(room),
+            Self::Settings(form) => Some(form.into_room()),
             Self::None => None,
             Self::Error(_, room)
+            | Self::BridgeCommands(_, room)
+            | Self::BridgeHealth(_, room)
+            | Self::BridgeLogin(_, room)
+            | Self::BridgedChat(_, room)
+            | Self::Contacts(_, room)
+            | Self::CreateDm(_, room)
             | Self::CreateRoom(_, room)
+            | Self::Directory(_, room)
+            | Self::Inbox(_, room)
             | Self::Search(_, room) => room,
         }
     }
@@ -306,7 +727,37 @@ impl Component for NoRoomSelected {
             .key("Right")
             .text("to open it here. You can also press")
             .key("C-k")
-            .text("to search a chat by name. Press")
+            .text("to search a chat by name, or")
+            .key("C-o")
+            .text("to join a room,")
+            .key("C-n")
+            .text("to create one,")
+            .key("C-d")
+            .text("to start a direct chat, or")
+            .key("C-b")
+            .text("to log into a bridge. In a chat, press")
+            .key("C-l")
+            .text("to list its members,")
+            .key("C-j")
+            .text("to invite someone,")
+            .key("C-s")
+            .text("to change its settings, or")
+            .key("C-x")
+            .text("to leave it. Press")
+            .key("C-p")
+            .text("to switch between your spaces,")
+            .key("C-w")
+            .text("between the networks of your bridges,")
+            .key("C-t")
+            .text("to check the state of your bridges,")
+            .key("C-e")
+            .text("to send commands to their bots,")
+            .key("C-g")
+            .text("to message a contact on a bridged network,")
+            .key("C-u")
+            .text("to browse your contacts on all networks,")
+            .key("C-f")
+            .text("to triage the unread messages of all rooms, and")
             .key("Esc")
             .text("to exit the TUI.")
             .build();
@@ -321,10 +772,47 @@ impl Component for NoRoomSelected {
     }
 }
 
+/// Request to create, open or join a room, sent to the chat page which
+/// then opens the room.
+pub enum RoomRequest {
+    /// Open the direct chat with the bot of a bridge, to log into it
+    Bridge(&'static KnownBridge),
+    /// Start a chat with a contact on the network of a bridge, given their
+    /// phone number or remote id
+    BridgedChat(&'static KnownBridge, String),
+    /// Create a room with the given options
+    Create(RoomOptions),
+    /// Open a direct chat with the user with the given id, reusing the
+    /// existing one if there is one
+    Direct(String),
+    /// Join a room by its address: alias, id or `matrix.to` link
+    Join(String),
+}
+
 /// Data send to this panel to update it.
 pub enum UpdateCurrentRoomPanel {
+    /// Open the command palette of the bridges, whose bots are searched in
+    /// the rooms
+    BridgeCommands(Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>),
+    /// Open the panel with the state of the bridges, whose bots are searched
+    /// in the rooms
+    BridgeHealth(Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>),
+    /// The direct chat with the bot of a bridge was opened, for the bridge
+    /// login wizard
+    BridgeBot(Arc<Mutex<DisplayRoom>>),
+    /// Open the popup to start a chat on the network of the given bridge
+    BridgedChat(&'static KnownBridge),
+    /// Open the contact book, with the user to load and save their
+    /// meta-contacts and to list the contacts of the bridges, and the rooms
+    /// in which the contacts are found
+    Contacts(Arc<User>, Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>),
+    /// Open the screen to join a room, with the user to query the public
+    /// room directory of their homeserver, or the space to list the rooms of
+    Directory(Arc<User>, Option<Arc<Mutex<DisplayRoom>>>),
     /// Display an error
     Error(String),
+    /// Open the inbox with the unread messages of the given rooms
+    Inbox(Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>),
     /// A new room was selected from the room list menu.
     NewRoom(Arc<Mutex<DisplayRoom>>),
     /// A search was requested on a set of rooms.
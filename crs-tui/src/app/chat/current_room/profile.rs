@@ -0,0 +1,213 @@
+//! Profile of a member, with the moderation actions that can be done on them
+
+extern crate alloc;
+use alloc::borrow::Cow;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::member::{DisplayMember, Membership, Presence, Role};
+use crs_backend::room::DisplayRoom;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+
+use crate::app::chat::current_room::commands::{Action, Command};
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::widgets::InstructionsBuilder;
+use crate::utils::safe_unlock;
+
+/// Profile of a member of a room
+///
+/// An event returns `Some(())` when the profile is closed, to go back to the
+/// list of the members.
+pub struct MemberProfile {
+    /// Index in [`Action::ALL`] of the selected action, if any
+    action:   Option<usize>,
+    /// Argument of the selected action
+    argument: Input<'static>,
+    /// Member whose profile is displayed
+    member:   DisplayMember,
+    /// Room the member belongs to
+    room:     Arc<Mutex<DisplayRoom>>,
+    /// Result of the last action, with the message to display
+    status:   Option<Result<String, String>>,
+}
+
+impl MemberProfile {
+    /// Returns the action that is currently selected
+    fn as_action(&self) -> Option<Action> {
+        self.action.and_then(|index| Action::ALL.get(index).copied())
+    }
+
+    /// Selects the previous or the next action.
+    ///
+    /// No action is selected before the first one and after the last one.
+    fn move_action(&mut self, previous: bool) {
+        let last = Action::ALL.len().saturating_sub(1);
+        self.action = match (self.action, previous) {
+            (None, true) => Some(last),
+            (None, false) => Some(0),
+            (Some(0), true) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index < last =>
+                Some(index.saturating_add(1)),
+            (Some(_), false) => None,
+        };
+        self.argument.set_title(self.as_action().map(Action::argument_hint));
+        self.argument.set_active(self.action.is_some());
+    }
+
+    /// Creates the profile of a member
+    pub const fn new(
+        member: DisplayMember,
+        room: Arc<Mutex<DisplayRoom>>,
+    ) -> Self {
+        Self {
+            action: None,
+            argument: Input::new(),
+            member,
+            room,
+            status: None,
+        }
+    }
+}
+
+impl Component for MemberProfile {
+    type ResponseData = Infallible;
+    type UpdateState = ();
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let member = &self.member;
+        let role = member.power_level().map_or_else(
+            || Role::Creator.as_name().to_owned(),
+            |level| {
+                format!("{} (power level {level})", member.role().as_name())
+            },
+        );
+        let presence = member.presence().map_or("Unknown", presence_name);
+
+        let mut actions =
+            vec![Span::styled("Actions: ", Style::new().fg(Color::DarkGray))];
+        for (index, action) in Action::ALL.into_iter().enumerate() {
+            let style = if self.action == Some(index) {
+                Style::new().fg(Color::Green).add_modifier(Modifier::BOLD)
+            } else {
+                Style::new()
+            };
+            actions.push(Span::styled(action.as_name(), style));
+            actions.push(Span::from(" "));
+        }
+
+        let mut lines = vec![
+            Line::styled(
+                member.as_name().to_owned(),
+                Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            Line::from(member.as_user_id().to_string()),
+            Line::default(),
+            field_line("Membership", membership_name(member.membership())),
+            field_line("Role", &role),
+            field_line("Presence", presence),
+            Line::default(),
+            Line::from(actions),
+        ];
+        match &self.status {
+            Some(Ok(success)) =>
+                lines.push(Line::styled(success, Style::new().fg(Color::Green))),
+            Some(Err(error)) =>
+                lines.push(Line::styled(error, Style::new().fg(Color::Red))),
+            None => (),
+        }
+
+        let instructions = InstructionsBuilder::default()
+            .key("Tab")
+            .text("action")
+            .key("Enter")
+            .text(if self.action.is_some() { "run" } else { "back" })
+            .build();
+
+        let argument_height =
+            if self.action.is_some() { Input::HEIGHT_WITHOUT_LABEL } else { 0 };
+
+        let layout = Layout::new(Direction::Vertical, [
+            Constraint::Fill(1),
+            Constraint::Length(argument_height),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: true }),
+            layout[0],
+        );
+        if self.action.is_some() {
+            self.argument.draw(frame, layout[1]);
+        }
+        frame.render_widget(
+            Paragraph::new(instructions.line).wrap(Wrap { trim: true }),
+            layout[2],
+        );
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        match event.as_key_press_event()?.code {
+            KeyCode::Tab => self.move_action(false),
+            KeyCode::BackTab => self.move_action(true),
+            KeyCode::Enter => {
+                let Some(action) = self.as_action() else {
+                    return Some(());
+                };
+                let command = Command::new(
+                    action,
+                    self.member.as_user_id().to_string(),
+                    &self.argument.take_value(),
+                );
+                let room = safe_unlock(&self.room).as_room();
+                self.status = Some(
+                    command
+                        .run(&room)
+                        .await
+                        .map(|()| format!("Done: {}", action.as_name())),
+                );
+            }
+            _ => {
+                self.argument.on_event(event).await;
+            }
+        }
+        None
+    }
+}
+
+/// Returns a line with the name and the value of a field of a profile
+pub fn field_line<'field, Value: Into<Cow<'field, str>>>(
+    name: &'static str,
+    value: Value,
+) -> Line<'field> {
+    Line::from(vec![
+        Span::styled(format!("{name}: "), Style::new().fg(Color::DarkGray)),
+        Span::raw(value),
+    ])
+}
+
+/// Returns the displayed name of a membership
+pub const fn membership_name(membership: Membership) -> &'static str {
+    match membership {
+        Membership::Banned => "Banned",
+        Membership::Invited => "Invited",
+        Membership::Joined => "Joined",
+    }
+}
+
+/// Returns the displayed name of a presence
+const fn presence_name(presence: Presence) -> &'static str {
+    match presence {
+        Presence::Offline => "Offline",
+        Presence::Online => "Online",
+        Presence::Unavailable => "Away",
+    }
+}
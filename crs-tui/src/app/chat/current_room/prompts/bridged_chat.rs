@@ -0,0 +1,50 @@
+//! UI component to start a chat with a contact on a bridged network
+
+use crs_backend::bridge::KnownBridge;
+
+use crate::derive_component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Prompt;
+
+/// Component to start a chat with a contact on the network of a bridge,
+/// given their phone number or remote id
+pub struct StartBridgedChat(Prompt<String>, &'static KnownBridge);
+
+impl StartBridgedChat {
+    /// Returns the bridge through which the chat is started
+    pub const fn as_bridge(&self) -> &'static KnownBridge {
+        self.1
+    }
+
+    /// Create a new [`StartBridgedChat`] for the given bridge
+    pub const fn new(bridge: &'static KnownBridge) -> Self {
+        Self::with_input(bridge, Input::new())
+    }
+
+    /// Create a new [`StartBridgedChat`] for the given bridge, with the
+    /// given input
+    const fn with_input(
+        bridge: &'static KnownBridge,
+        input: Input<'static>,
+    ) -> Self {
+        Self(
+            Prompt::new(
+                input.with_active(),
+                " Phone number or id of the contact ",
+                vec![],
+            ),
+            bridge,
+        )
+    }
+
+    /// Create a new [`StartBridgedChat`] for the given bridge, filled with
+    /// the remote id of a contact
+    pub fn with_remote_id(
+        bridge: &'static KnownBridge,
+        remote_id: String,
+    ) -> Self {
+        Self::with_input(bridge, Input::new().with_value(remote_id))
+    }
+}
+
+derive_component!(StartBridgedChat, Prompt<String>);
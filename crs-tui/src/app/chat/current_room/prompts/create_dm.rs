@@ -0,0 +1,21 @@
+//! UI component to open a direct chat with a user
+
+use crate::derive_component;
+use crate::ui::input::Input;
+use crate::ui::prompt::Prompt;
+
+/// Component to open a direct chat with a user, given their user id
+pub struct CreateDm(Prompt<String>);
+
+impl CreateDm {
+    /// Create a new [`CreateDm`] with the right titles.
+    pub const fn new() -> Self {
+        Self(Prompt::new(
+            Input::new().with_active(),
+            " User id to message (@user:server) ",
+            vec![],
+        ))
+    }
+}
+
+derive_component!(CreateDm, Prompt<String>);
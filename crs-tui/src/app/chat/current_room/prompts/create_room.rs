@@ -1,24 +1,265 @@
-//! UI component to create room with a given name
+//! Form to create a room with its name, topic, alias, visibility, encryption
+//! and the users to invite
 
-use crate::derive_component;
+use crs_backend::user::RoomOptions;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode};
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Style, Stylize as _};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Paragraph};
+
+use crate::ui::component::Component;
 use crate::ui::input::Input;
-use crate::ui::prompt::Prompt;
+use crate::ui::prompt::Status;
+use crate::ui::widgets::{Instructions, InstructionsBuilder, grid_centre};
+
+/// Height needed to display a border around the popup
+const BORDER_HEIGHT: u16 = 2;
+
+/// Height of the lines of the fields with a set of choices
+const CHOICES_HEIGHT: u16 = 2;
+
+/// Full height to reserve to display the popup
+const POPUP_HEIGHT: u16 = 4 * Input::HEIGHT_WITH_LABEL
+    + CHOICES_HEIGHT
+    + STATUS_HEIGHT
+    + BORDER_HEIGHT;
+
+/// Maximum width of the popup
+const POPUP_WIDTH: u16 = 60;
 
-/// Component to create a room, with a given name
-pub struct CreateRoom(Prompt<String>);
+/// Height reserved for the status message
+const STATUS_HEIGHT: u16 = 2;
+
+/// Field of the form
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// Alias of the room
+    Alias,
+    /// Whether the messages are encrypted
+    Encryption,
+    /// Users to invite
+    Invites,
+    /// Name of the room
+    Name,
+    /// Topic of the room
+    Topic,
+    /// Whether the room is public
+    Visibility,
+}
+
+impl Field {
+    /// Fields in the order they are displayed
+    const ALL: [Self; 6] = [
+        Self::Name,
+        Self::Topic,
+        Self::Alias,
+        Self::Invites,
+        Self::Visibility,
+        Self::Encryption,
+    ];
+}
+
+/// Form to create a room
+///
+/// An event returns the options of the room once the form is submitted.
+pub struct CreateRoom {
+    /// Input for the alias of the room
+    alias:     Input<'static>,
+    /// Field that is currently focused
+    current:   Field,
+    /// Whether the messages will be encrypted
+    encrypted: bool,
+    /// Input for the user ids to invite, separated by spaces or commas
+    invites:   Input<'static>,
+    /// Input for the name of the room
+    name:      Input<'static>,
+    /// Whether the room will be public
+    public:    bool,
+    /// Status of the submission
+    status:    Status,
+    /// Input for the topic of the room
+    topic:     Input<'static>,
+}
 
 impl CreateRoom {
-    /// Create a new [`CreateRoom`] with the right titles.
+    /// Returns the line displaying a field with a set of choices
+    fn draw_choice(
+        &self,
+        field: Field,
+        label: &'static str,
+        value: &'static str,
+    ) -> Line<'static> {
+        let style = if self.current == field {
+            Style::new().fg(Color::Green)
+        } else {
+            Style::new()
+        };
+        Line::from(vec![
+            Span::styled(format!("{label}: "), style),
+            Span::styled(format!("< {value} >"), style.bold()),
+        ])
+    }
+
+    /// Focuses the previous or the next field
+    fn focus(&mut self, previous: bool) {
+        let position = Field::ALL
+            .iter()
+            .position(|field| *field == self.current)
+            .unwrap_or(0);
+        let last = Field::ALL.len().saturating_sub(1);
+        let next = match (previous, position) {
+            (true, 0) => last,
+            (true, _) => position.saturating_sub(1),
+            (false, _) if position == last => 0,
+            (false, _) => position.saturating_add(1),
+        };
+        self.current = Field::ALL.get(next).copied().unwrap_or(Field::Name);
+        self.alias.set_active(self.current == Field::Alias);
+        self.invites.set_active(self.current == Field::Invites);
+        self.name.set_active(self.current == Field::Name);
+        self.topic.set_active(self.current == Field::Topic);
+    }
+
+    /// Returns the input of the focused field, if it is a text field
+    const fn focused_input(&mut self) -> Option<&mut Input<'static>> {
+        match self.current {
+            Field::Alias => Some(&mut self.alias),
+            Field::Invites => Some(&mut self.invites),
+            Field::Name => Some(&mut self.name),
+            Field::Topic => Some(&mut self.topic),
+            Field::Encryption | Field::Visibility => None,
+        }
+    }
+
+    /// Instructions to display in the form's footer
+    fn instructions() -> Instructions<'static> {
+        InstructionsBuilder::default()
+            .text(" Switch")
+            .key("Tab")
+            .text("Choose")
+            .key("Space")
+            .text("Create")
+            .key("Enter")
+            .build()
+    }
+
+    /// Create a new [`CreateRoom`] form, for a private and encrypted room.
     pub const fn new() -> Self {
-        Self(Prompt::new(
-            Input::new().with_active(),
-            " Name of the room to create ",
-            vec![],
-        ))
+        Self {
+            alias:     Input::new().with_label("Alias (#alias:server)"),
+            current:   Field::Name,
+            encrypted: true,
+            invites:   Input::new()
+                .with_label("Invite (user ids, separated by spaces)"),
+            name:      Input::new().with_label("Name").with_active(),
+            public:    false,
+            status:    Status::None,
+            topic:     Input::new().with_label("Topic"),
+        }
+    }
+
+    /// Returns the options filled in the form
+    fn take_options(&mut self) -> RoomOptions {
+        let non_empty = |input: &mut Input<'static>| {
+            let value = input.take_value().trim().to_owned();
+            (!value.is_empty()).then_some(value)
+        };
+        RoomOptions {
+            alias:     non_empty(&mut self.alias),
+            encrypted: self.encrypted,
+            invites:   self
+                .invites
+                .take_value()
+                .split([' ', ','])
+                .filter(|user_id| !user_id.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            name:      non_empty(&mut self.name),
+            public:    self.public,
+            topic:     non_empty(&mut self.topic),
+        }
     }
 }
 
-derive_component!(CreateRoom, Prompt<String>);
+impl Component for CreateRoom {
+    type ResponseData = Status;
+    type UpdateState = RoomOptions;
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let instructions = Self::instructions();
+
+        let popup_area = grid_centre(
+            Constraint::Length(area.width.saturating_sub(2).min(POPUP_WIDTH)),
+            Constraint::Length(POPUP_HEIGHT),
+            area,
+        );
+
+        let title = Line::from(" Create a room ".bold().style(Color::Green));
+        let border = Block::bordered()
+            .title(title.centered())
+            .title_bottom(instructions.line.centered());
+        frame.render_widget(border, popup_area);
+
+        let form = Layout::new(Direction::Vertical, [
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(CHOICES_HEIGHT),
+            Constraint::Length(STATUS_HEIGHT),
+        ])
+        .split(popup_area.inner(Margin::new(2, 1)));
 
-/// Action to request a room creation
-pub struct CreateRoomAction(pub String);
+        self.name.draw(frame, form[0]);
+        self.topic.draw(frame, form[1]);
+        self.alias.draw(frame, form[2]);
+        self.invites.draw(frame, form[3]);
+
+        let choices = vec![
+            self.draw_choice(
+                Field::Visibility,
+                "Visibility",
+                if self.public { "Public" } else { "Private" },
+            ),
+            self.draw_choice(
+                Field::Encryption,
+                "Encryption",
+                if self.encrypted { "Enabled" } else { "Disabled" },
+            ),
+        ];
+        frame.render_widget(Paragraph::new(choices), form[4]);
+
+        if let Some((content, colour)) = self.status.as_content() {
+            let status =
+                Text::from(content).style(Style::new().fg(colour)).centered();
+            frame.render_widget(status, form[5]);
+        }
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        if matches!(self.status, Status::Submitting) {
+            return None;
+        }
+        let key_event = event.as_key_press_event()?;
+        match key_event.code {
+            KeyCode::Tab => self.focus(false),
+            KeyCode::BackTab => self.focus(true),
+            KeyCode::Enter => return Some(self.take_options()),
+            KeyCode::Char(' ') if self.current == Field::Encryption =>
+                self.encrypted = !self.encrypted,
+            KeyCode::Char(' ') if self.current == Field::Visibility =>
+                self.public = !self.public,
+            _ =>
+                if let Some(input) = self.focused_input() {
+                    input.on_event(event).await;
+                },
+        }
+        None
+    }
+
+    fn update(&mut self, response_data: Self::ResponseData) {
+        self.status = response_data;
+    }
+}
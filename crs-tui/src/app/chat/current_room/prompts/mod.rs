@@ -1,4 +1,6 @@
 //! Prompts that can be opened in the discussion panel.
 
+pub mod bridged_chat;
+pub mod create_dm;
 pub mod create_room;
 pub mod search_room;
@@ -0,0 +1,371 @@
+//! Form to edit the settings of a room
+
+extern crate alloc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
+use std::sync::Mutex;
+
+use crs_backend::room::{DisplayRoom, RoomWrap};
+use crs_backend::settings::{
+    HistoryVisibility, JoinRule, RoomSettings, Setting
+};
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Style, Stylize as _};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+
+use crate::ui::component::Component;
+use crate::ui::input::Input;
+use crate::ui::widgets::{Instructions, InstructionsBuilder, grid_centre};
+use crate::utils::safe_unlock;
+
+/// Height needed to display a border around the popup
+const BORDER_HEIGHT: u16 = 2;
+
+/// Height of the lines of the settings with a set of choices
+const CHOICES_HEIGHT: u16 = 4;
+
+/// Height reserved for the error message
+const ERROR_HEIGHT: u16 = 2;
+
+/// Full height to reserve to display the popup
+const POPUP_HEIGHT: u16 = 3 * Input::HEIGHT_WITH_LABEL
+    + CHOICES_HEIGHT
+    + ERROR_HEIGHT
+    + BORDER_HEIGHT;
+
+/// Maximum width of the popup
+const POPUP_WIDTH: u16 = 60;
+
+/// Form to edit the settings of a room
+///
+/// Only the settings the power level of the user allows to change can be
+/// focused. An event returns `Some(())` once the settings were saved, or when
+/// the edition is cancelled, to go back to the discussion.
+pub struct RoomSettingsForm {
+    /// Input for the `mxc://` url of the avatar
+    avatar:             Input<'static>,
+    /// Setting that is currently focused, `None` if none can be edited
+    current:            Option<Setting>,
+    /// Whether the encryption will be enabled
+    encrypted:          bool,
+    /// Error that occurred while saving the settings
+    error:              String,
+    /// Whether guests will be allowed to join
+    guest_access:       bool,
+    /// Who will be able to read the history
+    history_visibility: HistoryVisibility,
+    /// Who will be able to join
+    join_rule:          JoinRule,
+    /// Input for the name of the room
+    name:               Input<'static>,
+    /// Settings of the room before edition
+    original:           RoomSettings,
+    /// Room whose settings are edited
+    room:               Arc<Mutex<DisplayRoom>>,
+    /// Input for the topic of the room
+    topic:              Input<'static>,
+}
+
+impl RoomSettingsForm {
+    /// Changes the value of the focused setting, if it has a set of choices
+    fn cycle_choice(&mut self) {
+        match self.current {
+            Some(Setting::Encryption) if !self.original.is_encrypted() =>
+                self.encrypted = !self.encrypted,
+            Some(Setting::GuestAccess) =>
+                self.guest_access = !self.guest_access,
+            Some(Setting::HistoryVisibility) =>
+                self.history_visibility = next_choice(
+                    &HistoryVisibility::ALL,
+                    self.history_visibility,
+                ),
+            Some(Setting::JoinRule) =>
+                self.join_rule = next_choice(&JoinRule::ALL, self.join_rule),
+            Some(
+                Setting::Avatar
+                | Setting::Encryption
+                | Setting::Name
+                | Setting::Topic,
+            )
+            | None => (),
+        }
+    }
+
+    /// Returns the line displaying a setting with a set of choices
+    fn draw_choice(&self, setting: Setting, value: &str) -> Line<'static> {
+        let style = if !self.original.can_edit(setting) {
+            Style::new().fg(Color::DarkGray)
+        } else if self.current == Some(setting) {
+            Style::new().fg(Color::Green)
+        } else {
+            Style::new()
+        };
+        Line::from(vec![
+            Span::styled(format!("{}: ", setting_name(setting)), style),
+            Span::styled(format!("< {value} >"), style.bold()),
+        ])
+    }
+
+    /// Focuses the previous or the next setting that can be edited
+    fn focus(&mut self, previous: bool) {
+        let editable = Setting::ALL
+            .into_iter()
+            .filter(|setting| self.original.can_edit(*setting))
+            .collect::<Vec<_>>();
+        let Some(position) = self.current.and_then(|current| {
+            editable.iter().position(|setting| *setting == current)
+        }) else {
+            return;
+        };
+        let last = editable.len().saturating_sub(1);
+        let next = match (previous, position) {
+            (true, 0) => last,
+            (true, _) => position.saturating_sub(1),
+            (false, _) if position == last => 0,
+            (false, _) => position.saturating_add(1),
+        };
+        self.current = editable.get(next).copied();
+        self.avatar.set_active(self.current == Some(Setting::Avatar));
+        self.name.set_active(self.current == Some(Setting::Name));
+        self.topic.set_active(self.current == Some(Setting::Topic));
+    }
+
+    /// Instructions to display in the form's footer
+    fn instructions() -> Instructions<'static> {
+        InstructionsBuilder::default()
+            .text(" Switch")
+            .key("Tab")
+            .text("Choose")
+            .key("Space")
+            .text("Save")
+            .key("Enter")
+            .text("Cancel")
+            .key("C-c")
+            .build()
+    }
+
+    /// Returns the underlying room of the form
+    pub fn into_room(self) -> Arc<Mutex<DisplayRoom>> {
+        self.room
+    }
+
+    /// Creates the form with the current settings of a room
+    pub fn new(room: Arc<Mutex<DisplayRoom>>, settings: RoomSettings) -> Self {
+        let current = Setting::ALL
+            .into_iter()
+            .find(|setting| settings.can_edit(*setting));
+        let input = |setting: Setting, value: &str| {
+            let label = if settings.can_edit(setting) {
+                setting_name(setting)
+            } else {
+                locked_setting_name(setting)
+            };
+            let input =
+                Input::default().with_label(label).with_value(value.to_owned());
+            if current == Some(setting) { input.with_active() } else { input }
+        };
+        let avatar = input(Setting::Avatar, settings.as_avatar_url());
+        let name = input(Setting::Name, settings.as_name());
+        let topic = input(Setting::Topic, settings.as_topic());
+        Self {
+            avatar,
+            current,
+            encrypted: settings.is_encrypted(),
+            error: if current.is_none() {
+                "You aren't allowed to change the settings of this room"
+                    .to_owned()
+            } else {
+                String::new()
+            },
+            guest_access: settings.has_guest_access(),
+            history_visibility: settings.as_history_visibility(),
+            join_rule: settings.as_join_rule(),
+            name,
+            original: settings,
+            room,
+            topic,
+        }
+    }
+
+    /// Sends the settings that were changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the settings couldn't be changed. The
+    /// previous ones are still applied.
+    async fn save(&self, room: &RoomWrap) -> Result<(), String> {
+        let original = &self.original;
+        if self.name.as_value() != original.as_name() {
+            room.set_name(self.name.as_value().to_owned())
+                .await
+                .map_err(|err| format!("Name: {err}"))?;
+        }
+        if self.topic.as_value() != original.as_topic() {
+            room.set_topic(self.topic.as_value())
+                .await
+                .map_err(|err| format!("Topic: {err}"))?;
+        }
+        if self.avatar.as_value() != original.as_avatar_url() {
+            room.set_avatar_url(self.avatar.as_value())
+                .await
+                .map_err(|err| format!("Avatar: {err}"))?;
+        }
+        if self.join_rule != original.as_join_rule() {
+            room.set_join_rule(self.join_rule)
+                .await
+                .map_err(|err| format!("Join rule: {err}"))?;
+        }
+        if self.guest_access != original.has_guest_access() {
+            room.set_guest_access(self.guest_access)
+                .await
+                .map_err(|err| format!("Guest access: {err}"))?;
+        }
+        if self.history_visibility != original.as_history_visibility() {
+            room.set_history_visibility(self.history_visibility)
+                .await
+                .map_err(|err| format!("History visibility: {err}"))?;
+        }
+        if self.encrypted && !original.is_encrypted() {
+            room.enable_encryption()
+                .await
+                .map_err(|err| format!("Encryption: {err}"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for RoomSettingsForm {
+    type ResponseData = Infallible;
+    type UpdateState = ();
+
+    fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let instructions = Self::instructions();
+
+        let popup_area = grid_centre(
+            Constraint::Length(area.width.saturating_sub(2).min(POPUP_WIDTH)),
+            Constraint::Length(POPUP_HEIGHT),
+            area,
+        );
+
+        let title = Line::from(" Room settings ".bold().style(Color::Green));
+        let border = Block::bordered()
+            .title(title.centered())
+            .title_bottom(instructions.line.centered());
+        frame.render_widget(border, popup_area);
+
+        let form = Layout::new(Direction::Vertical, [
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(Input::HEIGHT_WITH_LABEL),
+            Constraint::Length(CHOICES_HEIGHT),
+            Constraint::Length(ERROR_HEIGHT),
+        ])
+        .split(popup_area.inner(Margin::new(2, 1)));
+
+        self.name.draw(frame, form[0]);
+        self.topic.draw(frame, form[1]);
+        self.avatar.draw(frame, form[2]);
+
+        let choices = vec![
+            self.draw_choice(Setting::JoinRule, self.join_rule.as_name()),
+            self.draw_choice(
+                Setting::GuestAccess,
+                if self.guest_access { "Allowed" } else { "Forbidden" },
+            ),
+            self.draw_choice(
+                Setting::HistoryVisibility,
+                self.history_visibility.as_name(),
+            ),
+            self.draw_choice(
+                Setting::Encryption,
+                if self.encrypted { "Enabled" } else { "Disabled" },
+            ),
+        ];
+        frame.render_widget(Paragraph::new(choices), form[3]);
+
+        let error = Paragraph::new(self.error.as_str())
+            .wrap(Wrap { trim: true })
+            .style(Style::new().fg(Color::Red))
+            .centered();
+        frame.render_widget(error, form[4]);
+    }
+
+    async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
+        let key_event = event.as_key_press_event()?;
+        if key_event.modifiers & KeyModifiers::CONTROL == KeyModifiers::CONTROL
+        {
+            return key_event.code.is_char('c').then_some(());
+        }
+        match key_event.code {
+            KeyCode::Tab => self.focus(false),
+            KeyCode::BackTab => self.focus(true),
+            KeyCode::Enter => {
+                let room = safe_unlock(&self.room).as_room();
+                match self.save(&room).await {
+                    Ok(()) => return Some(()),
+                    Err(err) => self.error = err,
+                }
+            }
+            _ => match self.current {
+                Some(Setting::Avatar) => {
+                    self.avatar.on_event(event).await;
+                }
+                Some(Setting::Name) => {
+                    self.name.on_event(event).await;
+                }
+                Some(Setting::Topic) => {
+                    self.topic.on_event(event).await;
+                }
+                Some(_) if key_event.code == KeyCode::Char(' ') =>
+                    self.cycle_choice(),
+                Some(_) | None => (),
+            },
+        }
+        None
+    }
+}
+
+/// Returns the label of a setting the user isn't allowed to change
+const fn locked_setting_name(setting: Setting) -> &'static str {
+    match setting {
+        Setting::Avatar => "Avatar url (not allowed)",
+        Setting::Encryption => "Encryption (not allowed)",
+        Setting::GuestAccess => "Guest access (not allowed)",
+        Setting::HistoryVisibility => "History visible by (not allowed)",
+        Setting::JoinRule => "Join rule (not allowed)",
+        Setting::Name => "Name (not allowed)",
+        Setting::Topic => "Topic (not allowed)",
+    }
+}
+
+/// Returns the choice that follows the current one, cycling at the end
+fn next_choice<Choice: Copy + PartialEq>(
+    choices: &[Choice],
+    current: Choice,
+) -> Choice {
+    let position = choices
+        .iter()
+        .position(|choice| *choice == current)
+        .map_or(0, |position| position.saturating_add(1));
+    choices
+        .get(position)
+        .or_else(|| choices.first())
+        .copied()
+        .unwrap_or(current)
+}
+
+/// Returns the label of a setting
+const fn setting_name(setting: Setting) -> &'static str {
+    match setting {
+        Setting::Avatar => "Avatar url (mxc://...)",
+        Setting::Encryption => "Encryption",
+        Setting::GuestAccess => "Guest access",
+        Setting::HistoryVisibility => "History visible by",
+        Setting::JoinRule => "Join rule",
+        Setting::Name => "Name",
+        Setting::Topic => "Topic",
+    }
+}
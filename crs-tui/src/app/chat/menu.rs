@@ -3,16 +3,20 @@
 extern crate alloc;
 use alloc::sync::Arc;
 use core::convert::Infallible;
+use std::collections::HashSet;
 use std::sync::{LazyLock, Mutex};
 
 use crs_backend::room::DisplayRoom;
 use ratatui::Frame;
 use ratatui::crossterm::event::{Event, KeyCode};
 use ratatui::layout::{Constraint, Rect};
-use ratatui::style::{Color, Style};
-use ratatui::text::Text;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, List, ListItem, Paragraph, Wrap};
 
+use crate::app::chat::bridges::{
+    STATE_MARKER, bridge_states, network_marker, state_label
+};
 use crate::ui::component::Component;
 use crate::ui::widgets::{
     Instructions, InstructionsBuilder, fully_centred_content, grid_centre, saturating_cast
@@ -29,6 +33,25 @@ static INSTRUCTIONS: LazyLock<Instructions<'static>> = LazyLock::new(|| {
         .build()
 });
 
+/// Header displayed before the rooms that were left
+const ARCHIVED_HEADER: &str = " Archived";
+
+/// Marker displayed before a space whose rooms are hidden
+const COLLAPSED_MARKER: char = '\u{25b8}';
+
+/// Header displayed before the direct chats
+const DIRECT_HEADER: &str = " Direct messages";
+
+/// Marker displayed before a space whose rooms are displayed
+const EXPANDED_MARKER: char = '\u{25be}';
+
+/// Header displayed before the rooms that don't belong to any space, if the
+/// user joined spaces
+const ORPHANS_HEADER: &str = " Other rooms";
+
+/// Header displayed before the rooms that aren't direct chats
+const ROOMS_HEADER: &str = " Rooms";
+
 /// Minimum width to display the room list
 pub static ROOM_LIST_WIDTH: LazyLock<u16> =
     LazyLock::new(|| INSTRUCTIONS.width.saturating_add(2));
@@ -36,18 +59,28 @@ pub static ROOM_LIST_WIDTH: LazyLock<u16> =
 /// This page renders and gives the user an interface to list the chat and
 /// communicate in those chats.
 pub struct RoomList {
+    /// Rooms that were left by the user, displayed after the other rooms
+    archived:       Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Ids of the spaces whose rooms are hidden
+    collapsed:      HashSet<String>,
     /// Indicates whether the rooms are still loading
     ///
     /// This is used to determine if an empty list of rooms should be
     /// interpreted as "They are not accessible yet" or "There aren't any".
-    is_loading:    bool,
+    is_loading:     bool,
+    /// Protocol id of the network the list is filtered to, if any
+    network_filter: Option<String>,
     /// Rooms visible by the user
-    rooms:         Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
-    /// Room selected on the side bar with the list of chats.
+    rooms:          Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    /// Room selected on the side bar with the list of chats, counting the
+    /// rooms and the spaces of all the sections, in the order they are
+    /// displayed.
     ///
     /// Press enter to open this room in the chat panel, and use arrows to
     /// selected another room.
-    selected_room: usize,
+    selected_room:  usize,
+    /// Id of the space the list is filtered to, if any
+    space_filter:   Option<String>,
 }
 
 impl RoomList {
@@ -86,34 +119,122 @@ impl RoomList {
 
     /// Draws the list of rooms to select which room to open in the conversation
     /// part of the window.
+    ///
+    /// The rooms are displayed by section, with a header before each section.
     fn draw_room_list(&self, frame: &mut Frame<'_>, area: Rect) {
-        let (start, current_index, stop) = self.get_section_delimitations(area);
+        let entries = self.entries();
 
-        let name_list = safe_unlock(&self.rooms)[start..stop]
+        let selected_entry = entries
             .iter()
             .enumerate()
-            .map(|(idx, room)| {
-                let room_locked = safe_unlock(room);
-                let name = room_locked
-                    .as_name()
-                    .unwrap_or_else(|| UNKNOWN_NAME.clone());
-                let (text, colour) = if idx == current_index {
-                    (format!(">{name}"), Color::Green)
-                } else {
-                    (format!(" {name}"), Color::Reset)
-                };
-                drop(room_locked);
-                ListItem::new(text).style(Style::new().fg(colour))
+            .filter(|(_, entry)| entry.is_selectable())
+            .nth(self.selected_room)
+            .map_or(0, |(index, _)| index);
+        let (start, current_index, stop) =
+            get_section_delimitations(area, entries.len(), selected_entry);
+
+        let name_list = entries[start..stop]
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| match entry {
+                Entry::Header(header) => ListItem::new(*header).style(
+                    Style::new()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Entry::Room { name, network, style, .. } => {
+                    let (prefix, style) = if idx == current_index {
+                        (">", style.fg(Color::Green))
+                    } else {
+                        (" ", *style)
+                    };
+                    let mut spans = vec![Span::from(prefix)];
+                    if let Some(network) = network {
+                        let (marker, colour) = network_marker(network);
+                        spans.push(Span::styled(
+                            format!("[{marker}] "),
+                            Style::new().fg(colour),
+                        ));
+                    }
+                    spans.push(Span::from(name.to_string()));
+                    ListItem::new(Line::from(spans)).style(style)
+                }
+                Entry::Space { collapsed, name, .. } => {
+                    let arrow = if *collapsed {
+                        COLLAPSED_MARKER
+                    } else {
+                        EXPANDED_MARKER
+                    };
+                    let style = Style::new().add_modifier(Modifier::BOLD);
+                    if idx == current_index {
+                        ListItem::new(format!(">{arrow} {name}"))
+                            .style(style.fg(Color::Green))
+                    } else {
+                        ListItem::new(format!(" {arrow} {name}")).style(style)
+                    }
+                }
             })
             .collect::<Vec<_>>();
 
-        let block = if area.width >= *ROOM_LIST_WIDTH {
+        let filter = self.filtered_space().map(|space| {
+            safe_unlock(&space)
+                .as_name()
+                .unwrap_or_else(|| UNKNOWN_NAME.clone())
+        });
+        let filter_title = filter.as_ref().map_or_else(
+            || " All spaces".to_owned(),
+            |name| format!(" {name}"),
+        );
+
+        let mut block = if area.width >= *ROOM_LIST_WIDTH {
             Block::bordered()
                 .border_style(Style::default().fg(Color::Gray))
                 .title_bottom(INSTRUCTIONS.line.clone())
         } else {
             Block::bordered().border_style(Color::Gray)
         };
+        if filter.is_some() || self.has_spaces() {
+            block = block.title(
+                InstructionsBuilder::default()
+                    .text(&filter_title)
+                    .key("C-p")
+                    .build()
+                    .line,
+            );
+        }
+        let networks = self.networks();
+        if !networks.is_empty() {
+            let network_title = self
+                .network_filter
+                .as_ref()
+                .and_then(|filter| networks.iter().find(|(id, _)| id == filter))
+                .map_or_else(
+                    || " All networks".to_owned(),
+                    |(_, name)| format!(" {name}"),
+                );
+            block = block.title(
+                InstructionsBuilder::default()
+                    .text(&network_title)
+                    .key("C-w")
+                    .build()
+                    .line
+                    .right_aligned(),
+            );
+        }
+        let indicators = bridge_states(&self.rooms)
+            .into_iter()
+            .filter_map(|(bridge, state)| {
+                let (_, colour) = state_label(&state?);
+                let (marker, _) = network_marker(bridge.as_name());
+                Some(Span::styled(
+                    format!(" {marker}{STATE_MARKER} "),
+                    Style::new().fg(colour),
+                ))
+            })
+            .collect::<Vec<_>>();
+        if !indicators.is_empty() {
+            block = block.title(Line::from(indicators).centered());
+        }
 
         let list = List::new(name_list).block(block);
 
@@ -125,34 +246,102 @@ impl RoomList {
         self.is_loading = false;
     }
 
-    /// Returns the indices to slice the rooms displayed in the area
-    ///
-    /// # Returns
-    ///
-    /// A tuple with (start, current, end).
+    /// Lists the entries of the room list, section by section: the direct
+    /// chats, the spaces with their rooms, the other rooms, then the archived
+    /// rooms.
     ///
-    /// - start: Index of the first room to display
-    /// - current: relative index within the displayed slice of the currently
-    ///   selected room
-    /// - end: Index to stop the slice
-    #[expect(clippy::arithmetic_side_effects, reason = "checked")]
-    fn get_section_delimitations(&self, area: Rect) -> (usize, usize, usize) {
-        let nb_rooms = safe_unlock(&self.rooms).len();
-        let current_index = self.selected_room;
-
-        let nb_rooms_displayed = usize::from(area.height - 2);
-
-        let limit = nb_rooms_displayed >> 1_u32;
-
-        let (start, stop) = if current_index <= limit {
-            (0, nb_rooms_displayed.min(nb_rooms))
-        } else if current_index >= nb_rooms - limit {
-            (nb_rooms.saturating_sub(nb_rooms_displayed), nb_rooms)
-        } else {
-            (current_index - limit, current_index + limit)
-        };
+    /// If the list is filtered to a space, only this space and its sub-spaces
+    /// are listed, with their rooms. If the list is filtered to a network,
+    /// only the rooms bridged from this network are listed, with the spaces
+    /// that contain some of them. The empty sections are omitted.
+    fn entries(&self) -> Vec<Entry> {
+        let (spaces, mut rooms): (Vec<_>, Vec<_>) = safe_unlock(&self.rooms)
+            .iter()
+            .cloned()
+            .partition(|room| safe_unlock(room).is_space());
+        let filter = self.filtered_space();
+        rooms.retain(|room| self.is_in_network(room));
+
+        let mut entries = vec![];
+        let shown_spaces = spaces.iter().filter(|space| {
+            filter.as_ref().is_none_or(|filter| {
+                Arc::ptr_eq(space, filter) || is_in_space(space, filter)
+            })
+        });
+        for space in shown_spaces {
+            let space_rooms = rooms
+                .iter()
+                .filter(|room| is_in_space(room, space))
+                .map(|room| Entry::new(room, Style::new()))
+                .collect::<Vec<_>>();
+            if self.network_filter.is_some() && space_rooms.is_empty() {
+                continue;
+            }
+            let id = safe_unlock(space).id().to_string();
+            let collapsed = self.collapsed.contains(&id);
+            entries.push(Entry::space(space, id, collapsed));
+            if !collapsed {
+                entries.extend(space_rooms);
+            }
+        }
+        if filter.is_some() {
+            return entries;
+        }
+
+        let mut direct = vec![];
+        let mut others = vec![];
+        for room in &rooms {
+            if spaces.iter().any(|space| is_in_space(room, space)) {
+                continue;
+            }
+            let entry = Entry::new(room, Style::new());
+            if safe_unlock(room).is_direct() {
+                direct.push(entry);
+            } else {
+                others.push(entry);
+            }
+        }
+        let archived = safe_unlock(&self.archived)
+            .iter()
+            .filter(|room| self.is_in_network(room))
+            .map(|room| {
+                Entry::new(room, Style::new().add_modifier(Modifier::DIM))
+            })
+            .collect::<Vec<_>>();
 
-        (start, current_index - start, stop)
+        let mut sections = vec![];
+        if !direct.is_empty() {
+            sections.push(Entry::Header(DIRECT_HEADER));
+            sections.extend(direct);
+        }
+        sections.append(&mut entries);
+        for (header, section) in [
+            (
+                if spaces.is_empty() { ROOMS_HEADER } else { ORPHANS_HEADER },
+                others,
+            ),
+            (ARCHIVED_HEADER, archived),
+        ] {
+            if !section.is_empty() {
+                sections.push(Entry::Header(header));
+                sections.extend(section);
+            }
+        }
+        sections
+    }
+
+    /// Returns the space the list is filtered to, if any
+    pub fn filtered_space(&self) -> Option<Arc<Mutex<DisplayRoom>>> {
+        let id = self.space_filter.as_ref()?;
+        safe_unlock(&self.rooms)
+            .iter()
+            .find(|room| safe_unlock(room).id().as_str() == id)
+            .cloned()
+    }
+
+    /// Checks if the user joined at least one space
+    fn has_spaces(&self) -> bool {
+        safe_unlock(&self.rooms).iter().any(|room| safe_unlock(room).is_space())
     }
 
     /// Instructions to be displayed when no rooms are accessible from the user.
@@ -160,26 +349,177 @@ impl RoomList {
         InstructionsBuilder::default()
             .text("You don't have access to any rooms. Press")
             .key("C-n")
-            .text("to create a new chat or")
+            .text("to create a new chat,")
+            .key("C-d")
+            .text("to message someone or")
             .key("C-b")
             .text("to connect a bridge.")
             .build()
     }
 
+    /// Checks if a room is bridged from the network the list is filtered to,
+    /// if any
+    fn is_in_network(&self, room: &Arc<Mutex<DisplayRoom>>) -> bool {
+        self.network_filter.as_ref().is_none_or(|filter| {
+            safe_unlock(room)
+                .bridge()
+                .is_some_and(|bridge| bridge.as_protocol_id() == filter)
+        })
+    }
+
+    /// Lists the networks the rooms are bridged from, archived rooms
+    /// included, with their protocol id and their name
+    fn networks(&self) -> Vec<(String, String)> {
+        let mut networks: Vec<(String, String)> = vec![];
+        for list in [&self.rooms, &self.archived] {
+            for room in safe_unlock(list).iter() {
+                let room_locked = safe_unlock(room);
+                let Some(bridge) = room_locked.bridge() else { continue };
+                if networks.iter().all(|(id, _)| id != bridge.as_protocol_id())
+                {
+                    networks.push((
+                        bridge.as_protocol_id().to_owned(),
+                        bridge.as_protocol().to_owned(),
+                    ));
+                }
+            }
+        }
+        networks
+    }
+
+    /// Returns the number of rooms, archived included
+    fn nb_rooms(&self) -> usize {
+        safe_unlock(&self.rooms)
+            .len()
+            .saturating_add(safe_unlock(&self.archived).len())
+    }
+
     /// Create a new menu list with the same rooms than the chat page.
     ///
     /// The rooms and their content are loaded by the chat page in the backend.
-    pub const fn new(rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>) -> Self {
-        Self { rooms, selected_room: 0, is_loading: true }
+    pub const fn new(
+        rooms: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+        archived: Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    ) -> Self {
+        Self {
+            archived,
+            collapsed: HashSet::new(),
+            is_loading: true,
+            network_filter: None,
+            rooms,
+            selected_room: 0,
+            space_filter: None,
+        }
+    }
+
+    /// Returns the room or the space that is currently selected, if any
+    fn selected(&self) -> Option<Entry> {
+        self.entries()
+            .into_iter()
+            .filter(Entry::is_selectable)
+            .nth(self.selected_room)
+    }
+
+    /// Filters the list to the next space, or shows all the rooms again after
+    /// the last space.
+    pub fn switch_space(&mut self) {
+        let spaces = safe_unlock(&self.rooms)
+            .iter()
+            .filter_map(|room| {
+                let room_locked = safe_unlock(room);
+                room_locked.is_space().then(|| room_locked.id().to_string())
+            })
+            .collect::<Vec<_>>();
+        self.space_filter = match &self.space_filter {
+            None => spaces.into_iter().next(),
+            Some(current) =>
+                spaces.into_iter().skip_while(|id| id != current).nth(1),
+        };
+        self.selected_room = 0;
+    }
+
+    /// Filters the list to the rooms bridged from the next network, or shows
+    /// all the rooms again after the last network.
+    pub fn switch_network(&mut self) {
+        let mut networks = self.networks().into_iter().map(|(id, _)| id);
+        self.network_filter = match &self.network_filter {
+            None => networks.next(),
+            Some(current) => networks.skip_while(|id| id != current).nth(1),
+        };
+        self.selected_room = 0;
+    }
+}
+
+/// Entry of the room list
+enum Entry {
+    /// Header of a section
+    Header(&'static str),
+    /// Room that can be selected
+    Room {
+        /// Name displayed for the room
+        name:    Arc<str>,
+        /// Protocol id of the network the room is bridged from, if any
+        network: Option<String>,
+        /// Room opened when the entry is selected
+        room:    Arc<Mutex<DisplayRoom>>,
+        /// Style of the entry when it isn't selected
+        style:   Style,
+    },
+    /// Space, displayed before its rooms, that can be selected to collapse
+    /// or expand its rooms
+    Space {
+        /// Whether the rooms of the space are hidden
+        collapsed: bool,
+        /// Id of the space
+        id:        String,
+        /// Name of the space
+        name:      Arc<str>,
+    },
+}
+
+impl Entry {
+    /// Checks if the entry can be selected, i.e., if it isn't a header
+    const fn is_selectable(&self) -> bool {
+        !matches!(self, Self::Header(_))
+    }
+
+    /// Creates the entry of a room.
+    ///
+    /// Direct chats are named after the other party, and bridged rooms are
+    /// marked with their network.
+    fn new(room: &Arc<Mutex<DisplayRoom>>, style: Style) -> Self {
+        let room_locked = safe_unlock(room);
+        let name = room_locked
+            .direct_name()
+            .map(Arc::from)
+            .or_else(|| room_locked.as_name())
+            .unwrap_or_else(|| UNKNOWN_NAME.clone());
+        let network = room_locked
+            .bridge()
+            .map(|bridge| bridge.as_protocol_id().to_owned());
+        drop(room_locked);
+        Self::Room { name, network, room: Arc::clone(room), style }
+    }
+
+    /// Creates the entry of a space with the given id
+    fn space(
+        space: &Arc<Mutex<DisplayRoom>>,
+        id: String,
+        collapsed: bool,
+    ) -> Self {
+        let name = safe_unlock(space)
+            .as_name()
+            .unwrap_or_else(|| UNKNOWN_NAME.clone());
+        Self::Space { collapsed, id, name }
     }
 }
 
 impl Component for RoomList {
     type ResponseData = Infallible;
-    type UpdateState = usize;
+    type UpdateState = Arc<Mutex<DisplayRoom>>;
 
     fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
-        if safe_unlock(&self.rooms).is_empty() {
+        if self.nb_rooms() == 0 {
             if self.is_loading {
                 Self::draw_loading(frame, area);
             } else {
@@ -192,20 +532,74 @@ impl Component for RoomList {
 
     async fn on_event(&mut self, event: Event) -> Option<Self::UpdateState> {
         let key_event = event.as_key_press_event()?;
+        let nb_entries =
+            self.entries().iter().filter(|entry| entry.is_selectable()).count();
+        self.selected_room =
+            self.selected_room.min(nb_entries.saturating_sub(1));
         match key_event.code {
             KeyCode::Up => {
                 self.selected_room = self.selected_room.saturating_sub(1);
             }
             KeyCode::Down => {
-                let len = safe_unlock(&self.rooms).len();
                 let new_index = self.selected_room.saturating_add(1);
-                if new_index < len {
+                if new_index < nb_entries {
                     self.selected_room = new_index;
                 }
             }
-            KeyCode::Right => return Some(self.selected_room),
+            KeyCode::Right => match self.selected()? {
+                Entry::Header(_) => (),
+                Entry::Room { room, .. } => return Some(room),
+                Entry::Space { id, .. } =>
+                    if !self.collapsed.remove(&id) {
+                        self.collapsed.insert(id);
+                    },
+            },
             _ => (),
         }
         None
     }
 }
+
+/// Returns the indices to slice the entries displayed in the area
+///
+/// # Returns
+///
+/// A tuple with (start, current, end).
+///
+/// - start: Index of the first entry to display
+/// - current: relative index within the displayed slice of the currently
+///   selected entry
+/// - end: Index to stop the slice
+#[expect(clippy::arithmetic_side_effects, reason = "checked")]
+fn get_section_delimitations(
+    area: Rect,
+    nb_entries: usize,
+    selected: usize,
+) -> (usize, usize, usize) {
+    let current_index = selected.min(nb_entries.saturating_sub(1));
+
+    let nb_entries_displayed = usize::from(area.height - 2);
+
+    let limit = nb_entries_displayed >> 1_u32;
+
+    let (start, stop) = if current_index <= limit {
+        (0, nb_entries_displayed.min(nb_entries))
+    } else if current_index >= nb_entries - limit {
+        (nb_entries.saturating_sub(nb_entries_displayed), nb_entries)
+    } else {
+        (current_index - limit, current_index + limit)
+    };
+
+    (start, current_index - start, stop)
+}
+
+/// Checks if a room belongs to a space.
+///
+/// The room and the space must be different, as both are locked.
+fn is_in_space(
+    room: &Arc<Mutex<DisplayRoom>>,
+    space: &Arc<Mutex<DisplayRoom>>,
+) -> bool {
+    let space_locked = safe_unlock(space);
+    safe_unlock(room).is_in_space(&space_locked)
+}
@@ -1,5 +1,6 @@
 //! Main page displayed with the chats
 
+mod bridges;
 mod current_room;
 mod menu;
 
@@ -10,71 +11,191 @@ use core::time::Duration;
 use std::sync::Mutex;
 use std::thread;
 
+use crs_backend::bridge::KnownBridge;
 use crs_backend::room::DisplayRoom;
 use crs_backend::user::User;
 use ratatui::Frame;
 use ratatui::crossterm::event::{Event, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
+use crate::app::chat::bridges::{default_bridge, poll_bridges_periodically};
 use crate::app::chat::current_room::{
-    CreateRoomAction, CurrentRoom, UpdateCurrentRoomPanel
+    CurrentRoom, RoomRequest, UpdateCurrentRoomPanel
 };
 use crate::app::chat::menu::{ROOM_LIST_WIDTH, RoomList};
 use crate::ui::component::Component;
 use crate::utils::safe_unlock;
 
+/// Number of times to look for a new room among the loaded rooms, once per
+/// second, before giving up on opening it
+const ROOM_LOAD_ATTEMPTS: u32 = 30;
+
 /// This page renders and gives the user an interface to list the chat and
 /// communicate in those chats.
 pub struct ChatPage {
+    /// Rooms that were left by the user
+    archived:       Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
     /// Currently opened room
-    current_room: CurrentRoom,
+    current_room:   CurrentRoom,
     /// Menu with the list of rooms
-    menu:         RoomList,
+    menu:           RoomList,
+    /// Room created or joined by the user, sent by a background task once it
+    /// was loaded, applied before the next draw
+    opened_room:    Arc<Mutex<Option<UpdateCurrentRoomPanel>>>,
+    /// Update of the chat panel sent by a background task, e.g. the portal
+    /// of a bridged chat once it was created, applied before the next draw
+    pending_update: Arc<Mutex<Option<UpdateCurrentRoomPanel>>>,
     /// Rooms visible by the user
-    rooms:        Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
+    rooms:          Arc<Mutex<Vec<Arc<Mutex<DisplayRoom>>>>>,
     /// User to interact with matrix server
-    user:         Arc<User>,
+    user:           Arc<User>,
 }
 
 impl ChatPage {
+    /// Applies the update of the chat panel sent by a background task, if
+    /// any, and the results of the background tasks of its popups.
+    ///
+    /// The portal of a bridged chat is only opened if the user is still
+    /// waiting for it, i.e., if they didn't leave the popup in the meantime,
+    /// whereas the rooms created or joined by the user are always opened.
+    pub fn apply_pending_update(&mut self) {
+        self.current_room.apply_pending_update();
+        let opened_room = safe_unlock(&self.opened_room).take();
+        if let Some(update) = opened_room {
+            self.current_room.update(update);
+        }
+        let Some(update) = safe_unlock(&self.pending_update).take() else {
+            return;
+        };
+        if self.current_room.is_starting_bridged_chat() {
+            self.current_room.update(update);
+        }
+    }
+
     /// Create a new chat page with the given logged in user
     ///
     /// The rooms and their content will load in the background.
     pub fn new(user: Arc<User>) -> Self {
         let rooms = Arc::new(Mutex::new(vec![]));
-        let mut menu = RoomList::new(Arc::clone(&rooms));
+        let archived = Arc::new(Mutex::new(vec![]));
+        let mut menu = RoomList::new(Arc::clone(&rooms), Arc::clone(&archived));
         menu.end_loading(); // TODO
-        let this =
-            Self { rooms, user, menu, current_room: CurrentRoom::default() };
+        let this = Self {
+            archived,
+            opened_room: Arc::new(Mutex::new(None)),
+            pending_update: Arc::new(Mutex::new(None)),
+            rooms,
+            user,
+            menu,
+            current_room: CurrentRoom::default(),
+        };
         this.synchronise_rooms();
+        poll_bridges_periodically(Arc::clone(&this.user));
         this
     }
 
+    /// Waits in the background for a room created or joined by the user to be
+    /// loaded, and opens it, as the chat with the bot of a bridge if
+    /// `is_bridge` is set.
+    fn open_when_loaded(&self, room_id: String, is_bridge: bool) {
+        let opened_room = Arc::clone(&self.opened_room);
+        let rooms = Arc::clone(&self.rooms);
+        let _handle = tokio::spawn(async move {
+            let update = match find_loaded_room(&rooms, &room_id).await {
+                Some(room) if is_bridge =>
+                    UpdateCurrentRoomPanel::BridgeBot(room),
+                Some(room) => UpdateCurrentRoomPanel::NewRoom(room),
+                None => UpdateCurrentRoomPanel::Error(format!(
+                    "The room {room_id} didn't load, open it from the list of \
+                     rooms"
+                )),
+            };
+            *safe_unlock(&opened_room) = Some(update);
+        });
+    }
+
     /// Synchronise the existing rooms, including name and messages
+    ///
+    /// The rooms that were left are moved to the archived rooms, and back to
+    /// the rooms once they are joined again. The rooms that were forgotten are
+    /// removed.
     #[expect(clippy::unwrap_used, reason = "inside of thread")] // TODO: handle it
     fn synchronise_rooms(&self) {
         let rooms = Arc::clone(&self.rooms);
+        let archived = Arc::clone(&self.archived);
         let user = Arc::clone(&self.user);
         let _handle = tokio::spawn(async move {
             loop {
                 let local_rooms = Arc::clone(&rooms);
+                let local_archived = Arc::clone(&archived);
+                let loaded = Arc::new(Mutex::new(vec![]));
+                let local_loaded = Arc::clone(&loaded);
                 let on_room_load = move |new_room: DisplayRoom| {
-                    let new_room_id = new_room.id();
-                    if let Some(old_room) = safe_unlock(&local_rooms)
-                        .iter_mut()
-                        .find(|room| safe_unlock(room).id() == new_room_id)
+                    let new_room_id = new_room.id().clone();
+                    let (list, other) = if new_room.is_left() {
+                        (&local_archived, &local_rooms)
+                    } else {
+                        (&local_rooms, &local_archived)
+                    };
+                    let mut other_locked = safe_unlock(other);
+                    let moved_room = other_locked
+                        .iter()
+                        .position(|room| safe_unlock(room).id() == &new_room_id)
+                        .map(|index| other_locked.remove(index));
+                    drop(other_locked);
+                    let mut list_locked = safe_unlock(list);
+                    if let Some(old_room) = list_locked
+                        .iter()
+                        .find(|room| safe_unlock(room).id() == &new_room_id)
                     {
                         safe_unlock(old_room).update_from(new_room);
+                    } else if let Some(moved_room) = moved_room {
+                        safe_unlock(&moved_room).update_from(new_room);
+                        list_locked.push(moved_room);
                     } else {
-                        safe_unlock(&local_rooms)
-                            .push(Arc::new(Mutex::new(new_room)));
+                        list_locked.push(Arc::new(Mutex::new(new_room)));
                     }
+                    drop(list_locked);
+                    safe_unlock(&local_loaded).push(new_room_id);
                 };
                 user.load_rooms(on_room_load).await.unwrap();
+                let loaded = safe_unlock(&loaded);
+                for list in [&rooms, &archived] {
+                    safe_unlock(list)
+                        .retain(|room| loaded.contains(safe_unlock(room).id()));
+                }
+                drop(loaded);
                 thread::sleep(Duration::from_secs(1));
             }
         });
     }
+
+    /// Waits in the background for the bridge to create the portal to the
+    /// chat with a contact, and opens it once it is in the rooms of the user.
+    fn wait_for_portal(&self, bridge: &'static KnownBridge, remote_id: String) {
+        let pending_update = Arc::clone(&self.pending_update);
+        let rooms = Arc::clone(&self.rooms);
+        let user = Arc::clone(&self.user);
+        let _handle = tokio::spawn(async move {
+            let update = match user.wait_for_portal(bridge, &remote_id).await {
+                Ok(portal) =>
+                    find_loaded_room(&rooms, portal.room_id().as_str())
+                        .await
+                        .map_or_else(
+                            || {
+                                UpdateCurrentRoomPanel::Error(
+                                    "The chat didn't load, open it from the \
+                                     list of rooms"
+                                        .to_owned(),
+                                )
+                            },
+                            UpdateCurrentRoomPanel::NewRoom,
+                        ),
+                Err(err) => UpdateCurrentRoomPanel::Error(err.to_string()),
+            };
+            *safe_unlock(&pending_update) = Some(update);
+        });
+    }
 }
 
 impl Component for ChatPage {
@@ -115,29 +236,117 @@ impl Component for ChatPage {
             return None;
         }
 
-        if let Some(index) = self.menu.on_event(event.clone()).await {
-            let new_room = Arc::clone(&safe_unlock(&self.rooms)[index]);
-            self.current_room.update(UpdateCurrentRoomPanel::NewRoom(new_room));
+        if key_event.code.is_char('o')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data = UpdateCurrentRoomPanel::Directory(
+                Arc::clone(&self.user),
+                self.menu.filtered_space(),
+            );
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if key_event.code.is_char('p')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            self.menu.switch_space();
+            return None;
+        }
+
+        if key_event.code.is_char('w')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            self.menu.switch_network();
+            return None;
+        }
+
+        if key_event.code.is_char('e')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data =
+                UpdateCurrentRoomPanel::BridgeCommands(Arc::clone(&self.rooms));
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if key_event.code.is_char('g')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data = UpdateCurrentRoomPanel::BridgedChat(
+                default_bridge(&self.rooms),
+            );
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if key_event.code.is_char('u')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data = UpdateCurrentRoomPanel::Contacts(
+                Arc::clone(&self.user),
+                Arc::clone(&self.rooms),
+            );
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if key_event.code.is_char('f')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data =
+                UpdateCurrentRoomPanel::Inbox(Arc::clone(&self.rooms));
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if key_event.code.is_char('t')
+            && key_event.modifiers & KeyModifiers::CONTROL
+                == KeyModifiers::CONTROL
+        {
+            let update_data =
+                UpdateCurrentRoomPanel::BridgeHealth(Arc::clone(&self.rooms));
+            self.current_room.update(update_data);
+            return None;
+        }
+
+        if let Some(room) = self.menu.on_event(event.clone()).await {
+            self.current_room.update(UpdateCurrentRoomPanel::NewRoom(room));
         } else {
-            let CreateRoomAction(name) =
-                self.current_room.on_event(event).await?;
-            let room_name = if name.is_empty() { None } else { Some(name) };
-            match self.user.create_room_with_name(room_name).await {
-                Ok(new_matrix_room) => loop {
-                    let tui_rooms = safe_unlock(&self.rooms);
-                    if let Some(tui_room) = tui_rooms.iter().find(|tui_room| {
-                        safe_unlock(tui_room).id() == new_matrix_room.room_id()
-                    }) {
-                        let current_room = Arc::clone(tui_room);
-                        drop(tui_rooms);
-                        self.current_room.update(
-                            UpdateCurrentRoomPanel::NewRoom(current_room),
-                        );
-                        break;
-                    }
-                    drop(tui_rooms);
-                    thread::sleep(Duration::from_secs(1));
-                },
+            let request = self.current_room.on_event(event).await?;
+            let is_bridge = matches!(request, RoomRequest::Bridge(_));
+            let created = match request {
+                RoomRequest::Bridge(bridge) =>
+                    self.user.open_bridge_bot(bridge).await,
+                RoomRequest::BridgedChat(bridge, remote_id) =>
+                    match self.user.start_bridged_chat(bridge, &remote_id).await
+                    {
+                        Ok(Some(portal)) => Ok(portal),
+                        Ok(None) => {
+                            self.wait_for_portal(bridge, remote_id);
+                            return None;
+                        }
+                        Err(err) => Err(err),
+                    },
+                RoomRequest::Create(options) =>
+                    self.user.create_room(options).await,
+                RoomRequest::Direct(user_id) =>
+                    self.user.create_dm(&user_id).await,
+                RoomRequest::Join(address) =>
+                    self.user.join_room(&address).await,
+            };
+            match created {
+                Ok(new_matrix_room) => self.open_when_loaded(
+                    new_matrix_room.room_id().to_string(),
+                    is_bridge,
+                ),
                 Err(err) => self
                     .current_room
                     .update(UpdateCurrentRoomPanel::Error(err.to_string())),
@@ -146,3 +355,25 @@ impl Component for ChatPage {
         None
     }
 }
+
+/// Waits for a room to be loaded among the rooms of the user, checking once
+/// per second, and returns it.
+///
+/// Returns `None` if the room still wasn't loaded after
+/// [`ROOM_LOAD_ATTEMPTS`] checks.
+async fn find_loaded_room(
+    rooms: &Mutex<Vec<Arc<Mutex<DisplayRoom>>>>,
+    room_id: &str,
+) -> Option<Arc<Mutex<DisplayRoom>>> {
+    for _ in 0..ROOM_LOAD_ATTEMPTS {
+        let loaded = safe_unlock(rooms)
+            .iter()
+            .find(|room| safe_unlock(room).id().as_str() == room_id)
+            .cloned();
+        if loaded.is_some() {
+            return loaded;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    None
+}
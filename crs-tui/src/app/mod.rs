@@ -31,6 +31,14 @@ pub struct App {
 }
 
 impl App {
+    /// Applies the updates sent by the background tasks of the current
+    /// screen, before it is drawn
+    pub fn apply_pending_update(&mut self) {
+        if let Screen::Chat(chat) = &mut self.screen {
+            chat.apply_pending_update();
+        }
+    }
+
     /// Create a new page after an error at login
     ///
     /// This will repon the login page with the error message
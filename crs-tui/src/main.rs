@@ -61,6 +61,7 @@ mod tui;
 mod ui;
 mod utils;
 
+use crs_backend::bridge::check_ghost_namespaces;
 use dotenv::dotenv;
 
 use crate::credentials::Credentials;
@@ -71,6 +72,7 @@ async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
     dotenv()?;
+    check_ghost_namespaces()?;
     let credentials = Credentials::from_env();
     let mut tui = Tui::new(credentials).await?;
     let res = tui.run().await;
@@ -89,6 +89,7 @@ impl Tui {
     /// If no event occurs, the app is refr5sh every 200 ms.
     pub async fn run(&mut self) -> Result<(), io::Error> {
         loop {
+            self.app.apply_pending_update();
             self.draw()?;
             if poll(Duration::from_millis(200))? {
                 let event = read()?;
@@ -0,0 +1,238 @@
+//! Code blocks of the messages: extraction, syntax highlighting and wrapping
+//!
+//! The grammars and the theme are the ones bundled with [`syntect`], no network
+//! access is needed.
+
+use core::mem::take;
+use std::sync::LazyLock;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use unicode_width::UnicodeWidthChar as _;
+
+/// Number of spaces to replace tabulations with
+const TAB_WIDTH: usize = 4;
+
+/// Name of the bundled theme used to highlight the code
+const THEME_NAME: &str = "base16-ocean.dark";
+
+/// Bundled grammars to parse the code
+static SYNTAXES: LazyLock<SyntaxSet> =
+    LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Bundled theme to highlight the code
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults().themes.remove(THEME_NAME).unwrap_or_default()
+});
+
+/// Block of code, with the language it is written in if it was specified
+pub struct CodeBlock {
+    /// Raw content of the block
+    content:  String,
+    /// Language tag of the block, e.g. `rust`
+    language: Option<String>,
+}
+
+impl CodeBlock {
+    /// Returns the raw content of the block
+    pub fn as_content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the language tag of the block
+    pub fn as_language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Highlights the lines of the block with the grammar of its language
+    ///
+    /// If the language is unknown or unspecified, the lines are returned
+    /// without highlighting.
+    pub fn highlight(&self) -> Vec<Line<'static>> {
+        let plain = Style::new().fg(Color::Yellow);
+
+        let Some(syntax) = self
+            .language
+            .as_deref()
+            .and_then(|language| SYNTAXES.find_syntax_by_token(language))
+        else {
+            return self
+                .content
+                .lines()
+                .map(|line| Line::styled(expand_tabs(line), plain))
+                .collect();
+        };
+
+        let mut highlighter = HighlightLines::new(syntax, &THEME);
+        LinesWithEndings::from(&self.content)
+            .map(|line| {
+                highlighter.highlight_line(line, &SYNTAXES).map_or_else(
+                    |_| Line::styled(expand_tabs(line), plain),
+                    |ranges| {
+                        Line::from(
+                            ranges
+                                .into_iter()
+                                .map(|(style, text)| {
+                                    let colour = Color::Rgb(
+                                        style.foreground.r,
+                                        style.foreground.g,
+                                        style.foreground.b,
+                                    );
+                                    Span::styled(
+                                        expand_tabs(text),
+                                        Style::new().fg(colour),
+                                    )
+                                })
+                                .collect::<Vec<_>>(),
+                        )
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Creates an empty code block
+    pub const fn new(language: Option<String>) -> Self {
+        Self { content: String::new(), language }
+    }
+
+    /// Appends some code at the end of the block
+    pub fn push_str(&mut self, code: &str) {
+        self.content.push_str(code);
+    }
+
+    /// Removes the trailing line break of the block
+    pub fn trim_end(&mut self) {
+        if self.content.ends_with('\n') {
+            self.content.pop();
+        }
+    }
+}
+
+/// Part of the body of a message: either formatted text or a block of code
+pub enum Segment<'text> {
+    /// Block of code, to be highlighted
+    Code(CodeBlock),
+    /// Already formatted lines of text
+    Text(Vec<Line<'text>>),
+}
+
+/// Replaces the tabulations with spaces and removes the line break
+fn expand_tabs(line: &str) -> String {
+    line.trim_end_matches(['\n', '\r']).replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// Splits a Markdown body into formatted text and fenced code blocks.
+///
+/// The text is formatted with [`tui_markdown`]. A code block that isn't closed
+/// runs until the end of the body.
+pub fn parse_markdown(body: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    let mut text_start = 0;
+    let mut offset = 0_usize;
+    let mut fence: Option<(String, CodeBlock)> = None;
+
+    for line in body.split_inclusive('\n') {
+        let line_start = offset;
+        offset = offset.saturating_add(line.len());
+        let trimmed = line.trim();
+
+        if let Some((marker, block)) = &mut fence {
+            if trimmed.starts_with(marker.as_str())
+                && trimmed.chars().all(|ch| marker.starts_with(ch))
+            {
+                if let Some((_, mut finished)) = fence.take() {
+                    finished.trim_end();
+                    segments.push(Segment::Code(finished));
+                }
+                text_start = offset;
+            } else {
+                block.push_str(line);
+            }
+            continue;
+        }
+
+        let fence_char = if trimmed.starts_with("```") {
+            '`'
+        } else if trimmed.starts_with("~~~") {
+            '~'
+        } else {
+            continue;
+        };
+
+        let marker: String =
+            trimmed.chars().take_while(|ch| *ch == fence_char).collect();
+        let language = trimmed
+            .trim_start_matches(fence_char)
+            .split_whitespace()
+            .next()
+            .map(str::to_owned);
+
+        push_markdown(&mut segments, body.get(text_start..line_start));
+        fence = Some((marker, CodeBlock::new(language)));
+    }
+
+    if let Some((_, mut block)) = fence {
+        block.trim_end();
+        segments.push(Segment::Code(block));
+    } else {
+        push_markdown(&mut segments, body.get(text_start..));
+    }
+
+    segments
+}
+
+/// Formats some Markdown text, and pushes it if it isn't empty.
+fn push_markdown<'text>(
+    segments: &mut Vec<Segment<'text>>,
+    text: Option<&'text str>,
+) {
+    if let Some(markdown) = text
+        && !markdown.trim().is_empty()
+    {
+        segments.push(Segment::Text(tui_markdown::from_str(markdown).lines));
+    }
+}
+
+/// Wraps a line at the given width, keeping its styles and its whitespaces.
+///
+/// The width is the display width of the characters, e.g. 2 columns for the
+/// wide characters, which are never split across two lines.
+pub fn wrap(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 || line.width() <= width {
+        return vec![line];
+    }
+
+    let mut lines = vec![];
+    let mut current = Line::default();
+    let mut current_width = 0_usize;
+
+    for span in line.spans {
+        let mut chunk = String::new();
+        for ch in span.content.chars() {
+            let ch_width = ch.width().unwrap_or(0);
+            if current_width > 0
+                && current_width.saturating_add(ch_width) > width
+            {
+                if !chunk.is_empty() {
+                    current
+                        .push_span(Span::styled(take(&mut chunk), span.style));
+                }
+                lines.push(take(&mut current));
+                current_width = 0;
+            }
+            chunk.push(ch);
+            current_width = current_width.saturating_add(ch_width);
+        }
+        if !chunk.is_empty() {
+            current.push_span(Span::styled(chunk, span.style));
+        }
+    }
+
+    lines.push(current);
+    lines
+}
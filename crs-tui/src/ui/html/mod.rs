@@ -0,0 +1,451 @@
+//! Renders the HTML bodies of the messages (the `formatted_body` with the
+//! `org.matrix.custom.html` format) to ratatui text.
+//!
+//! The preformatted elements are extracted as code blocks, to be highlighted.
+//!
+//! Only the tags allowed by the [Matrix specification][1] are interpreted, the
+//! other ones are discarded but their content is kept. The content of the
+//! `<mx-reply>`, `<script>` and `<style>` tags is dropped.
+//!
+//! [1]: <https://spec.matrix.org/latest/client-server-api/#mroommessage-msgtypes>
+
+#[cfg(test)]
+mod tests;
+mod tokens;
+
+use core::mem::take;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+use crate::ui::code::{CodeBlock, Segment};
+use crate::ui::html::tokens::{Tag, Token, tokenise};
+
+/// Prefix to display at the start of each line of a quote
+const QUOTE_PREFIX: &str = "\u{2502} ";
+
+/// Width of the horizontal rules
+const RULE_WIDTH: usize = 20;
+
+/// Link prefix of the user and room pills
+const PILL_PREFIX: &str = "https://matrix.to/#/";
+
+/// Effect of an element, to be undone when the element is closed
+#[derive(PartialEq, Eq)]
+enum Effect {
+    /// The content of the element is not displayed
+    Hidden,
+    /// The element is a link, and its target is displayed after the text
+    Link,
+    /// The element is a list, ordered or not
+    List,
+    /// The element doesn't have any effect, other than its style
+    None,
+    /// The content is a code block
+    Preformatted,
+    /// The content is displayed as a quote
+    Quote,
+}
+
+/// Opened element, that will be closed by a closing tag
+struct Element {
+    /// Effect to undo when the element is closed
+    effect:       Effect,
+    /// Whether the element is on its own lines
+    is_block:     bool,
+    /// Name of the tag that opened the element
+    name:         String,
+    /// Whether the element pushed a style on the style stack
+    pushes_style: bool,
+}
+
+/// Reason for which an HTML body can't be rendered
+struct Unsupported;
+
+/// State of the rendering of an HTML body
+#[derive(Default)]
+struct Renderer {
+    /// Code block being read, if inside a preformatted element
+    code:        Option<CodeBlock>,
+    /// Spans of the line being built
+    current:     Vec<Span<'static>>,
+    /// Whether the line being built has content, other than the prefixes
+    has_content: bool,
+    /// Number of opened elements whose content must not be displayed
+    hidden:      usize,
+    /// Lines that are finished
+    lines:       Vec<Line<'static>>,
+    /// Opened links, with their target and the text displayed so far
+    links:       Vec<(String, String)>,
+    /// Opened lists: `None` for bullet lists, the next number for ordered
+    /// lists
+    lists:       Vec<Option<u32>>,
+    /// Marker of a list item, to display at the start of the next line
+    marker:      Option<String>,
+    /// Stack of the opened elements
+    opened:      Vec<Element>,
+    /// Number of opened quotes
+    quotes:      usize,
+    /// Finished segments of the body
+    segments:    Vec<Segment<'static>>,
+    /// Stack of styles, the last one is applied to the text
+    styles:      Vec<Style>,
+}
+
+impl Renderer {
+    /// Finishes the current line and starts a new one
+    fn break_line(&mut self) {
+        if !self.has_content {
+            self.start_line();
+        }
+        self.lines.push(Line::from(take(&mut self.current)));
+        self.has_content = false;
+    }
+
+    /// Closes the last element with the given name, as well as all the
+    /// elements opened after it.
+    ///
+    /// Closing tags without opening tags are ignored.
+    fn close(&mut self, name: &str) {
+        if !self.opened.iter().any(|element| element.name == name) {
+            return;
+        }
+        while let Some(element) = self.opened.pop() {
+            let is_last = element.name == name;
+            self.close_element(&element);
+            if is_last {
+                break;
+            }
+        }
+    }
+
+    /// Undoes the effects of an element
+    fn close_element(&mut self, element: &Element) {
+        if element.is_block {
+            self.end_block();
+        }
+        if element.pushes_style {
+            self.styles.pop();
+        }
+        match element.effect {
+            Effect::Hidden => self.hidden = self.hidden.saturating_sub(1),
+            Effect::Link => {
+                if let Some((href, text)) = self.links.pop()
+                    && href != text.trim()
+                {
+                    let target = Span::styled(
+                        format!(" <{href}>"),
+                        Style::new().fg(Color::DarkGray),
+                    );
+                    self.push_span(target);
+                }
+            }
+            Effect::List => {
+                self.lists.pop();
+            }
+            Effect::None => (),
+            Effect::Preformatted =>
+                if let Some(mut block) = self.code.take() {
+                    block.trim_end();
+                    self.flush_lines();
+                    self.segments.push(Segment::Code(block));
+                },
+            Effect::Quote => self.quotes = self.quotes.saturating_sub(1),
+        }
+    }
+
+    /// Ends the current line, if it isn't empty
+    fn end_block(&mut self) {
+        if self.has_content {
+            self.break_line();
+        }
+    }
+
+    /// Closes the remaining elements and returns the rendered segments
+    fn finish(mut self) -> Option<Vec<Segment<'static>>> {
+        while let Some(element) = self.opened.pop() {
+            self.close_element(&element);
+        }
+        self.end_block();
+        self.flush_lines();
+        (!self.segments.is_empty()).then_some(self.segments)
+    }
+
+    /// Moves the finished lines to a new text segment
+    fn flush_lines(&mut self) {
+        if !self.lines.is_empty() {
+            self.segments.push(Segment::Text(take(&mut self.lines)));
+        }
+    }
+
+    /// Opens an element from its opening tag
+    fn open(&mut self, tag: &Tag) -> Result<(), Unsupported> {
+        let name = tag.as_name();
+        if matches!(name, "table" | "thead" | "tbody" | "tr" | "th" | "td") {
+            return Err(Unsupported);
+        }
+        if self.open_void(tag) {
+            return Ok(());
+        }
+
+        let (effect, is_block) = self.open_effect(tag);
+        let style = self.open_style(tag);
+
+        if is_block {
+            self.end_block();
+        }
+        match effect {
+            Effect::Hidden => self.hidden = self.hidden.saturating_add(1),
+            Effect::Preformatted if self.code.is_none() =>
+                self.code = Some(CodeBlock::new(None)),
+            Effect::Quote => self.quotes = self.quotes.saturating_add(1),
+            Effect::Link
+            | Effect::List
+            | Effect::None
+            | Effect::Preformatted => (),
+        }
+        if let Some(added) = style {
+            self.styles.push(self.style().patch(added));
+        }
+
+        self.opened.push(Element {
+            effect,
+            is_block,
+            name: name.to_owned(),
+            pushes_style: style.is_some(),
+        });
+
+        Ok(())
+    }
+
+    /// Returns the effect of an element and whether it is a block.
+    ///
+    /// Lists, list items and links are registered.
+    fn open_effect(&mut self, tag: &Tag) -> (Effect, bool) {
+        match tag.as_name() {
+            "mx-reply" | "script" | "style" => (Effect::Hidden, false),
+            "pre" => (Effect::Preformatted, true),
+            "blockquote" => (Effect::Quote, true),
+            "p" | "div" | "details" | "summary" | "caption" | "h1" | "h2"
+            | "h3" | "h4" | "h5" | "h6" => (Effect::None, true),
+            "ul" => {
+                self.lists.push(None);
+                (Effect::List, true)
+            }
+            "ol" => {
+                let start = tag
+                    .attribute("start")
+                    .and_then(|start| start.parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(Some(start));
+                (Effect::List, true)
+            }
+            "li" => {
+                self.end_block();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        let marker = format!("{number}. ");
+                        *number = number.saturating_add(1);
+                        marker
+                    }
+                    _ => "- ".to_owned(),
+                });
+                (Effect::None, true)
+            }
+            "code" => {
+                if let Some(block) = &mut self.code
+                    && block.as_language().is_none()
+                {
+                    *block = CodeBlock::new(language(tag));
+                }
+                (Effect::None, false)
+            }
+            "a" if !is_pill(tag) => {
+                let href = tag.attribute("href").unwrap_or_default();
+                self.links.push((href.to_owned(), String::new()));
+                (Effect::Link, false)
+            }
+            _ => (Effect::None, false),
+        }
+    }
+
+    /// Returns the style added by an element, if it has one
+    ///
+    /// The reason of a spoiler is displayed before the hidden content.
+    fn open_style(&mut self, tag: &Tag) -> Option<Style> {
+        let dark_grey = Style::new().fg(Color::DarkGray);
+        match tag.as_name() {
+            "b" | "strong" => Some(Style::new().bold()),
+            "i" | "em" => Some(Style::new().italic()),
+            "u" => Some(Style::new().underlined()),
+            "del" | "s" | "strike" => Some(Style::new().crossed_out()),
+            "code" if self.code.is_none() =>
+                Some(Style::new().fg(Color::Yellow)),
+            "blockquote" => Some(Style::new().fg(Color::Gray)),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" =>
+                Some(Style::new().bold().fg(Color::Magenta)),
+            "a" if is_pill(tag) => Some(Style::new().bold().fg(Color::Cyan)),
+            "a" => Some(Style::new().underlined().fg(Color::Blue)),
+            "span" | "font" => tag.attribute("data-mx-spoiler").map_or_else(
+                || {
+                    tag.attribute("data-mx-color")
+                        .or_else(|| tag.attribute("color"))
+                        .and_then(parse_colour)
+                        .map(|colour| Style::new().fg(colour))
+                },
+                |reason| {
+                    if !reason.is_empty() {
+                        self.push_span(Span::styled(
+                            format!("({reason}) "),
+                            dark_grey,
+                        ));
+                    }
+                    Some(dark_grey.bg(Color::DarkGray))
+                },
+            ),
+            _ => None,
+        }
+    }
+
+    /// Renders the elements that don't have any content, e.g. `<br>`
+    ///
+    /// # Returns
+    ///
+    /// `true` if the element was void and rendered.
+    fn open_void(&mut self, tag: &Tag) -> bool {
+        let dark_grey = Style::new().fg(Color::DarkGray);
+        match tag.as_name() {
+            "br" =>
+                if let Some(block) = &mut self.code {
+                    block.push_str("\n");
+                } else {
+                    self.break_line();
+                },
+            "hr" => {
+                self.end_block();
+                self.push_span(Span::styled(
+                    "\u{2500}".repeat(RULE_WIDTH),
+                    dark_grey,
+                ));
+                self.end_block();
+            }
+            "img" => {
+                let alt = tag.attribute("alt").unwrap_or("image");
+                self.push_span(Span::styled(format!("[{alt}]"), dark_grey));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Pushes a span at the end of the current line
+    fn push_span(&mut self, span: Span<'static>) {
+        if self.hidden != 0 {
+            return;
+        }
+        if !self.has_content {
+            self.start_line();
+        }
+        self.current.push(span);
+        self.has_content = true;
+    }
+
+    /// Pushes some text, with the current style.
+    ///
+    /// Inside of a preformatted element, the text is added to the code block.
+    /// Otherwise, the whitespaces are collapsed.
+    fn push_text(&mut self, text: &str) {
+        if self.hidden != 0 {
+            return;
+        }
+
+        if let Some(block) = &mut self.code {
+            block.push_str(text);
+            return;
+        }
+
+        for (_, link_text) in &mut self.links {
+            link_text.push_str(text);
+        }
+
+        let mut collapsed = String::with_capacity(text.len());
+        for ch in text.chars() {
+            if !ch.is_whitespace() {
+                collapsed.push(ch);
+            } else if !collapsed.ends_with(' ')
+                && (self.has_content || !collapsed.is_empty())
+            {
+                collapsed.push(' ');
+            }
+        }
+        if !collapsed.is_empty() {
+            self.push_span(Span::styled(collapsed, self.style()));
+        }
+    }
+
+    /// Pushes the prefixes of a new line: quotes, list indentations and list
+    /// markers.
+    fn start_line(&mut self) {
+        for _ in 0..self.quotes {
+            self.current.push(Span::styled(
+                QUOTE_PREFIX,
+                Style::new().fg(Color::DarkGray),
+            ));
+        }
+        let depth = self.lists.len();
+        if let Some(marker) = self.marker.take() {
+            self.current.push(Span::from("  ".repeat(depth.saturating_sub(1))));
+            self.current.push(Span::styled(marker, Style::new().bold()));
+        } else if depth != 0 {
+            self.current.push(Span::from("  ".repeat(depth)));
+        }
+    }
+
+    /// Returns the style to apply to the text
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+}
+
+/// Checks if a link is a user or room pill
+fn is_pill(tag: &Tag) -> bool {
+    tag.attribute("href")
+        .and_then(|href| href.strip_prefix(PILL_PREFIX))
+        .is_some_and(|target| target.starts_with(['@', '!', '#']))
+}
+
+/// Returns the language of a `<code>` element, from its `language-*` class
+fn language(tag: &Tag) -> Option<String> {
+    tag.attribute("class")?
+        .split_whitespace()
+        .find_map(|class| class.strip_prefix("language-"))
+        .map(str::to_owned)
+}
+
+/// Parses a `#rrggbb` colour
+fn parse_colour(colour: &str) -> Option<Color> {
+    let hex = colour.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(Color::from_u32)
+}
+
+/// Renders an HTML body to ratatui lines and code blocks
+///
+/// # Returns
+///
+/// `None` if the HTML is malformed, if it contains elements that can't be
+/// rendered (e.g. tables) or if nothing would be displayed. The plain text
+/// body should be displayed instead.
+pub fn render(html: &str) -> Option<Vec<Segment<'static>>> {
+    let mut renderer = Renderer::default();
+    for token in tokenise(html)? {
+        match token {
+            Token::Open(tag) => renderer.open(&tag).ok()?,
+            Token::Close(name) => renderer.close(&name),
+            Token::Text(text) => renderer.push_text(&text),
+        }
+    }
+    renderer.finish()
+}
@@ -0,0 +1,103 @@
+//! Tests of the tokenisation and of the rendering of the HTML bodies
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    reason = "tests"
+)]
+
+use crate::ui::code::Segment;
+use crate::ui::html::render;
+use crate::ui::html::tokens::{Token, tokenise};
+
+/// Renders an HTML body that must be displayed as text only, and returns its
+/// lines
+fn render_lines(html: &str) -> Vec<String> {
+    let segments = render(html).unwrap();
+    let [Segment::Text(lines)] = segments.as_slice() else {
+        panic!("expected a single text segment");
+    };
+    lines
+        .iter()
+        .map(|line| {
+            line.spans.iter().map(|span| span.content.as_ref()).collect()
+        })
+        .collect()
+}
+
+/// Returns the texts of the tokens, ignoring the tags
+fn texts(tokens: &[Token]) -> Vec<&str> {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text.as_str()),
+            Token::Close(_) | Token::Open(_) => None,
+        })
+        .collect()
+}
+
+/// The named and numeric references are decoded, in the texts and in the
+/// attributes, and the unknown or unterminated ones are kept as is.
+#[test]
+fn entities() {
+    let tokens = tokenise(
+        "<a href='?a=1&amp;b=2'>a &amp; b &lt;&#x41;&#66;&gt;</a> &unknown; \
+         &amp",
+    )
+    .unwrap();
+
+    let Token::Open(link) = &tokens[0] else {
+        panic!("expected an opening tag");
+    };
+    assert_eq!(link.as_name(), "a");
+    assert_eq!(link.attribute("href"), Some("?a=1&b=2"));
+    assert_eq!(texts(&tokens), ["a & b <AB>", " &unknown; &amp"]);
+}
+
+/// The comments are skipped up to their end, even if they contain tags or
+/// `>`.
+#[test]
+fn comments() {
+    let tokens = tokenise("a<!-- <b>bold</b> -> -->b<!---->c").unwrap();
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(texts(&tokens), ["a", "b", "c"]);
+}
+
+/// The items of the nested lists are indented under their parent item, and
+/// the ordered lists are numbered from their start.
+#[test]
+fn nested_lists() {
+    let lines = render_lines(
+        "<ul><li>one<ul><li>two</li><li>three</li></ul></li><li>four</li></\
+         ul><ol start=\"3\"><li>five</li><li>six</li></ol>",
+    );
+    assert_eq!(lines, [
+        "- one",
+        "  - two",
+        "  - three",
+        "- four",
+        "3. five",
+        "4. six"
+    ]);
+}
+
+/// The quote of the replied message is dropped, only the reply is displayed.
+#[test]
+fn strip_reply() {
+    let lines = render_lines(
+        "<mx-reply><blockquote><a href=\"https://matrix.to/#/!room\">In reply \
+         to</a> quoted</blockquote></mx-reply>reply",
+    );
+    assert_eq!(lines, ["reply"]);
+}
+
+/// The HTML whose tags or comments are never closed is rejected, for the
+/// plain text body to be displayed instead.
+#[test]
+fn unclosed_tags() {
+    assert!(tokenise("<b>bold</b").is_none());
+    assert!(tokenise("<a href=\"https://matrix.org>link").is_none());
+    assert!(tokenise("text<!-- comment").is_none());
+    assert!(render("<b>bold</b").is_none());
+}
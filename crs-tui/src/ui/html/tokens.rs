@@ -0,0 +1,209 @@
+//! Splits an HTML string into opening tags, closing tags and texts
+
+use core::iter::Peekable;
+use core::mem::take;
+use core::str::Chars;
+
+/// Opening HTML tag, with its attributes
+pub struct Tag {
+    /// Attributes of the tag, with their values
+    attributes: Vec<(String, String)>,
+    /// Lowercase name of the tag
+    name:       String,
+}
+
+impl Tag {
+    /// Returns the name of the tag, in lowercase
+    pub fn as_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of an attribute, if the tag has it
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Element of an HTML document
+pub enum Token {
+    /// Closing tag, e.g. `</b>`, with its lowercase name
+    Close(String),
+    /// Opening tag, e.g. `<a href="https://matrix.org">`
+    Open(Tag),
+    /// Text between two tags, with the entities decoded
+    Text(String),
+}
+
+/// Decodes a character reference, without the leading `&` and the trailing
+/// `;`
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = if let Some(hex) =
+                number.strip_prefix('x').or_else(|| number.strip_prefix('X'))
+            {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                number.parse().ok()?
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// Replaces the character references (e.g. `&amp;`) by the characters they
+/// represent.
+///
+/// Unknown references are kept as is.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '&' {
+            decoded.push(ch);
+            continue;
+        }
+        let mut entity = String::new();
+        while let Some(next) =
+            chars.next_if(|next| next.is_ascii_alphanumeric() || *next == '#')
+        {
+            entity.push(next);
+        }
+        if let Some(decoded_entity) = decode_entity(&entity)
+            && chars.next_if_eq(&';').is_some()
+        {
+            decoded.push(decoded_entity);
+        } else {
+            decoded.push('&');
+            decoded.push_str(&entity);
+        }
+    }
+    decoded
+}
+
+/// Parses the inside of a tag, between `<` and `>`.
+///
+/// Returns `None` for comments, doctypes and processing instructions.
+fn parse_tag(raw: &str) -> Option<Token> {
+    let mut chars = raw.trim().chars().peekable();
+    if chars.next_if(|ch| matches!(ch, '!' | '?')).is_some() {
+        return None;
+    }
+    let is_closing = chars.next_if_eq(&'/').is_some();
+
+    let name = read_while(&mut chars, |ch| {
+        !ch.is_whitespace() && !matches!(ch, '/' | '=')
+    })
+    .to_lowercase();
+
+    if is_closing {
+        return Some(Token::Close(name));
+    }
+
+    let mut attributes = vec![];
+    loop {
+        read_while(&mut chars, |ch| ch.is_whitespace() || ch == '/');
+        let attribute = read_while(&mut chars, |ch| {
+            !ch.is_whitespace() && !matches!(ch, '/' | '=')
+        });
+        if attribute.is_empty() {
+            break;
+        }
+        read_while(&mut chars, char::is_whitespace);
+        let value = if chars.next_if_eq(&'=').is_some() {
+            read_while(&mut chars, char::is_whitespace);
+            if let Some(quote) = chars.next_if(|ch| matches!(ch, '"' | '\'')) {
+                let quoted = read_while(&mut chars, |ch| ch != quote);
+                chars.next();
+                quoted
+            } else {
+                read_while(&mut chars, |ch| !ch.is_whitespace())
+            }
+        } else {
+            String::new()
+        };
+        attributes.push((attribute.to_lowercase(), decode_entities(&value)));
+    }
+
+    Some(Token::Open(Tag { attributes, name }))
+}
+
+/// Consumes the characters while they match the predicate, and returns them.
+fn read_while<Predicate>(
+    chars: &mut Peekable<Chars<'_>>,
+    predicate: Predicate,
+) -> String
+where
+    Predicate: Fn(char) -> bool,
+{
+    let mut read = String::new();
+    while let Some(ch) = chars.next_if(|ch| predicate(*ch)) {
+        read.push(ch);
+    }
+    read
+}
+
+/// Splits an HTML string into tokens
+///
+/// Comments and doctypes are discarded. The comments are skipped up to their
+/// `-->`, as they may contain `>` or tags.
+///
+/// # Returns
+///
+/// `None` if the HTML is malformed, i.e., if a tag or a comment is never
+/// closed.
+pub fn tokenise(html: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut chars = html.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '<' {
+            text.push(ch);
+            continue;
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(decode_entities(&take(&mut text))));
+        }
+
+        if let Some(comment) = chars.as_str().strip_prefix("!--") {
+            let (_, after) = comment.split_once("-->")?;
+            chars = after.chars();
+            continue;
+        }
+
+        let mut raw = String::new();
+        let mut quote = None;
+        loop {
+            let next = chars.next()?;
+            match quote {
+                Some(opening) if opening == next => quote = None,
+                None if next == '>' => break,
+                None if matches!(next, '"' | '\'') => quote = Some(next),
+                Some(_) | None => (),
+            }
+            raw.push(next);
+        }
+
+        if let Some(token) = parse_tag(&raw) {
+            tokens.push(token);
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(decode_entities(&text)));
+    }
+
+    Some(tokens)
+}
@@ -24,6 +24,8 @@ pub struct Input<'label> {
     is_hidden: bool,
     /// Label to be placed on top of the input
     label:     Option<&'label str>,
+    /// Title to be placed on the top border of the input
+    title:     Option<&'label str>,
     /// Value inside the input
     value:     String,
 }
@@ -60,8 +62,12 @@ impl<'label> Input<'label> {
             Text::from(self.value.as_str())
         };
 
-        let paragraph = Paragraph::new(text)
-            .block(Block::bordered().border_style(self.border_style()));
+        let mut block = Block::bordered().border_style(self.border_style());
+        if let Some(title) = self.title {
+            block = block.title(title);
+        }
+
+        let paragraph = Paragraph::new(text).block(block);
 
         frame.render_widget(paragraph, area);
     }
@@ -92,6 +98,7 @@ impl<'label> Input<'label> {
             is_active: false,
             is_hidden: false,
             label:     None,
+            title:     None,
             value:     String::new(),
         }
     }
@@ -106,6 +113,11 @@ impl<'label> Input<'label> {
         self.has_error = has_error;
     }
 
+    /// Sets the title displayed on the top border of the input
+    pub const fn set_title(&mut self, title: Option<&'label str>) {
+        self.title = title;
+    }
+
     /// Sets the inner value of the input
     pub fn set_value(&mut self, value: String) {
         self.value = value;
@@ -136,6 +148,12 @@ impl<'label> Input<'label> {
         self
     }
 
+    /// Add a title on the top border of the input
+    pub const fn with_title(mut self, title: &'label str) -> Self {
+        self.title = Some(title);
+        self
+    }
+
     /// Set the initial value of the input
     pub fn with_value(mut self, value: String) -> Self {
         self.value = value;
@@ -1,7 +1,10 @@
 //! Module with a couple of reusable functions and components for the UI
 
+pub mod code;
 pub mod component;
 pub mod derive;
+pub mod html;
 pub mod input;
 pub mod prompt;
+pub mod qr;
 pub mod widgets;
@@ -15,11 +15,13 @@ use crate::ui::component::Component;
 pub struct Candidates<T: Display> {
     /// List of all the possible candidates, whether they correspond to the
     /// search or not
-    all:      Vec<T>,
+    all:         Vec<T>,
     /// Currently selected item, if used with entries
-    cursor:   Option<usize>,
-    /// Display of the candidates that match the search
-    matching: Vec<String>,
+    cursor:      Option<usize>,
+    /// Whether the case is ignored when matching the search
+    ignore_case: bool,
+    /// Indices in `all` of the candidates that match the search
+    matching:    Vec<usize>,
 }
 
 impl<T: Display> Candidates<T> {
@@ -58,19 +60,44 @@ impl<T: Display> Candidates<T> {
 
     /// Returns a new empty [`Candidates`] with the given list of candidates
     pub const fn new(list: Vec<T>) -> Self {
-        Self { cursor: None, all: list, matching: vec![] }
+        Self {
+            cursor:      None,
+            all:         list,
+            ignore_case: false,
+            matching:    vec![],
+        }
+    }
+
+    /// Returns the candidate under the cursor, if any
+    pub fn selected(&self) -> Option<&T> {
+        let index = self.matching.get(self.cursor?)?;
+        self.all.get(*index)
     }
 
-    /// Returns the first possible entries that match the search
+    /// Returns the first possible entries that match the search, ignoring
+    /// the case if [`Self::with_ignore_case`] was set.
     pub fn update_matching(&mut self, input: &str) {
+        let normalise = |text: String| {
+            if self.ignore_case { text.to_lowercase() } else { text }
+        };
+        let search = normalise(input.to_owned());
         self.matching = self
             .all
             .iter()
-            .filter_map(|entry| {
-                let formatted = format!("{entry}");
-                formatted.contains(input).then_some(formatted)
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                normalise(format!("{entry}")).contains(&search).then_some(index)
             })
             .collect();
+        if self.cursor.is_some_and(|cursor| cursor >= self.matching.len()) {
+            self.cursor = None;
+        }
+    }
+
+    /// Ignores the case when matching the search
+    pub const fn with_ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
     }
 }
 
@@ -89,6 +116,7 @@ impl<T: Display> Component for Candidates<T> {
         let lines = self
             .matching
             .iter()
+            .filter_map(|index| self.all.get(*index))
             .enumerate()
             .map(|(idx, entry)| {
                 if self.cursor.is_some_and(|cursor| cursor == idx) {
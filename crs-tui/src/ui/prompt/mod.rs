@@ -1,6 +1,6 @@
 //! Component that displays an input at the middle of the page
 
-mod candidates;
+pub mod candidates;
 
 use core::convert::Infallible;
 use core::fmt::Display;
@@ -141,7 +141,7 @@ pub enum Status {
 impl Status {
     /// Returns the message to display, as well as the colour if it has a
     /// mesaage.
-    const fn as_content(&self) -> Option<(&str, Color)> {
+    pub const fn as_content(&self) -> Option<(&str, Color)> {
         match self {
             Self::None => None,
             Self::Error(message) => Some((message.as_str(), Color::Red)),
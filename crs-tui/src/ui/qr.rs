@@ -0,0 +1,77 @@
+//! Renders the QR codes sent as images in the terminal, e.g. by the bots of
+//! the bridges to log in.
+//!
+//! The decoded content is encoded again, and each character displays two
+//! modules of the code with an upper half block: the foreground is the top
+//! module, and the background is the bottom one. This keeps the modules
+//! square enough to be scanned by a phone.
+
+use crs_backend::message::QrCode;
+use qrcode::{Color as QrColour, QrCode as QrEncoder};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Character displaying a module on the top half and one on the bottom half
+const HALF_BLOCK: &str = "\u{2580}";
+
+/// Number of light modules around the code, needed by the scanners
+const QUIET_ZONE: usize = 2;
+
+/// Returns the colour of the terminal cell for a module of the code
+const fn colour(is_dark: bool) -> Color {
+    if is_dark { Color::Black } else { Color::White }
+}
+
+/// Checks if a module of the code is dark, the coordinates including the
+/// quiet zone.
+fn is_dark(modules: &[QrColour], width: usize, x: usize, y: usize) -> bool {
+    let (Some(x), Some(y)) =
+        (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE))
+    else {
+        return false;
+    };
+    x < width
+        && y.checked_mul(width)
+            .and_then(|row| row.checked_add(x))
+            .and_then(|index| modules.get(index))
+            == Some(&QrColour::Dark)
+}
+
+/// Renders a QR code, with a caption above it.
+///
+/// An expired QR code is replaced by a note, so that it isn't scanned.
+pub fn qr_lines(qr_code: &QrCode) -> Vec<Line<'static>> {
+    let content = match qr_code {
+        QrCode::Expired =>
+            return vec![Line::styled(
+                "QR code expired, a newer one was sent",
+                Style::new().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            )],
+        QrCode::Valid(content) => content,
+    };
+    let Ok(encoder) = QrEncoder::new(content.as_bytes()) else {
+        return vec![Line::from(format!("QR code: {content}"))];
+    };
+    let width = encoder.width();
+    let modules = encoder.to_colors();
+    let size = width.saturating_add(QUIET_ZONE.saturating_mul(2));
+
+    let mut lines =
+        vec![Line::styled("Scan this QR code:", Style::new().fg(Color::Green))];
+    lines.extend((0..size).step_by(2).map(|y| {
+        Line::from(
+            (0..size)
+                .map(|x| {
+                    let top = is_dark(&modules, width, x, y);
+                    let bottom =
+                        is_dark(&modules, width, x, y.saturating_add(1));
+                    Span::styled(
+                        HALF_BLOCK,
+                        Style::new().fg(colour(top)).bg(colour(bottom)),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+    }));
+    lines
+}
@@ -2,8 +2,12 @@
 
 extern crate alloc;
 use alloc::sync::Arc;
+use std::io::{self, Write as _};
 use std::sync::{LazyLock, Mutex, MutexGuard};
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+
 /// String to display when failed to fetch the room's name
 pub static UNKNOWN_NAME: LazyLock<Arc<str>> =
     LazyLock::new(|| Arc::from("<unknown name>"));
@@ -22,3 +26,18 @@ pub fn safe_unlock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
         }
     }
 }
+
+/// Copies some text to the clipboard of the terminal.
+///
+/// This uses the OSC 52 escape sequence, so it also works over SSH, as long as
+/// the terminal emulator supports it.
+///
+/// # Errors
+///
+/// Returns an error if the escape sequence couldn't be written to the
+/// terminal.
+pub fn copy_to_clipboard(text: &str) -> Result<(), io::Error> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
@@ -72,6 +72,7 @@ echo "CRS_SERVER_URL=$url
 CRS_TMUX_SESSION=$session
 CRS_USER=$user
 CRS_PASSWORD=$password
+${WHATSAPP_GHOST_NAMESPACE:+WHATSAPP_GHOST_NAMESPACE='$WHATSAPP_GHOST_NAMESPACE'}
 " >../.env
 
 ############